@@ -0,0 +1,419 @@
+use dioxus::prelude::*;
+
+use crate::models::command::ApplicationCommand;
+use crate::models::types::Snowflake;
+use crate::store::{GUILD_MEMBERS, GUILDS};
+use crate::utils::cdn;
+
+pub const MAX_SUGGESTIONS: usize = 10;
+// discord waits for two characters before suggesting emojis, otherwise every `:)` would pop it up
+const MIN_EMOJI_QUERY: usize = 2;
+// https://docs.discord.sex/resources/channel#channel-type
+const GUILD_CATEGORY: u8 = 4;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Trigger {
+	Mention,
+	Channel,
+	Emoji,
+	Command,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Suggestion {
+	pub key:    String,
+	pub label:  String,
+	pub detail: Option<String>,
+	pub image:  Option<String>,
+	pub text:   String,         // what goes into the composer, eg. @cow
+	pub syntax: Option<String>, // what `text` becomes when sending, eg. <@1234>
+}
+
+/// the word at the cursor if it starts with a trigger: the trigger, where the word starts and what's been typed after the trigger
+pub fn active_query(
+	text: &str,
+	cursor: usize,
+) -> Option<(Trigger, usize, &str)> {
+	let before = text.get(..cursor)?;
+	let start = before
+		.rfind(char::is_whitespace)
+		.map_or(0, |i| i + before[i..].chars().next().unwrap().len_utf8());
+	let word = &before[start..];
+
+	let trigger = match word.chars().next()? {
+		| '@' => Trigger::Mention,
+		| '#' => Trigger::Channel,
+		| ':' => Trigger::Emoji,
+		// commands only work at the very start of a message
+		| '/' if start == 0 => Trigger::Command,
+		| _ => return None,
+	};
+	let query = &word[1..];
+
+	if trigger == Trigger::Emoji && (query.len() < MIN_EMOJI_QUERY || query.contains(':')) {
+		return None;
+	}
+
+	Some((trigger, start, query))
+}
+
+/// replaces the word from `start` to `cursor` with `insert` plus a space, returns the new text and cursor
+pub fn complete(
+	text: &str,
+	start: usize,
+	cursor: usize,
+	insert: &str,
+) -> (String, usize) {
+	let mut completed = String::with_capacity(text.len() + insert.len() + 1);
+	completed.push_str(&text[..start]);
+	completed.push_str(insert);
+	completed.push(' ');
+	let new_cursor = completed.len();
+	completed.push_str(text[cursor..].strip_prefix(' ').unwrap_or(&text[cursor..]));
+
+	(completed, new_cursor)
+}
+
+/// something autocompleted into the composer, its friendly text starts at byte `start`
+#[derive(Clone, PartialEq, Debug)]
+pub struct Completion {
+	pub start:  usize,
+	pub text:   String,
+	pub syntax: String,
+}
+
+/// moves the completions along with an edit from `old` to `new`, the ones the edit touched are dropped
+pub fn remap(
+	completions: &mut Vec<Completion>,
+	old: &str,
+	new: &str,
+) {
+	let prefix = old.bytes().zip(new.bytes()).take_while(|(a, b)| a == b).count();
+	let max_suffix = old.len().min(new.len()) - prefix;
+	let suffix = old
+		.bytes()
+		.rev()
+		.zip(new.bytes().rev())
+		.take(max_suffix)
+		.take_while(|(a, b)| a == b)
+		.count();
+	// the bytes from `prefix` to `edited_end` in `old` got replaced
+	let edited_end = old.len() - suffix;
+
+	completions.retain_mut(|completion| {
+		let end = completion.start + completion.text.len();
+		if end <= prefix {
+			true
+		} else if completion.start >= edited_end {
+			completion.start = completion.start + new.len() - old.len();
+			true
+		} else {
+			false
+		}
+	});
+}
+
+/// swaps the friendly text of everything that got autocompleted back to the real syntax, anything typed
+/// that happens to look the same stays as it is
+pub fn to_syntax(
+	text: &str,
+	completions: &[Completion],
+) -> String {
+	let mut completions: Vec<_> = completions.iter().collect();
+	// from the back, so replacing one doesn't move the ones still to go
+	completions.sort_by_key(|c| std::cmp::Reverse(c.start));
+
+	let mut text = text.to_string();
+	for completion in completions {
+		let range = completion.start..completion.start + completion.text.len();
+		// `@cow` with more typed straight after it is someone else
+		let joined = text
+			.get(range.end..)
+			.and_then(|rest| rest.chars().next())
+			.is_some_and(|c| c.is_alphanumeric() || c == '_');
+		if !joined && text.get(range.clone()) == Some(completion.text.as_str()) {
+			text.replace_range(range, &completion.syntax);
+		}
+	}
+	text
+}
+
+/// the dom counts selection offsets in utf-16 code units, rust strings in bytes
+pub fn utf16_to_byte(
+	text: &str,
+	offset: usize,
+) -> usize {
+	let mut units = 0;
+	for (index, c) in text.char_indices() {
+		if units >= offset {
+			return index;
+		}
+		units += c.len_utf16();
+	}
+	text.len()
+}
+
+pub fn byte_to_utf16(
+	text: &str,
+	offset: usize,
+) -> usize {
+	text[..offset].encode_utf16().count()
+}
+
+pub fn suggestions(
+	trigger: Trigger,
+	query: &str,
+	guild_id: Option<Snowflake>,
+	commands: &[ApplicationCommand],
+) -> Vec<Suggestion> {
+	let query = query.to_lowercase();
+	let mut found = Vec::new();
+
+	match trigger {
+		| Trigger::Mention => {
+			if let Some(guild_id) = guild_id {
+				for member in GUILD_MEMBERS.read().search(guild_id, &query, MAX_SUGGESTIONS) {
+					let Some(user) = &member.user else {
+						continue;
+					};
+
+					found.push(Suggestion {
+						key:    format!("user-{}", user.id),
+						label:  member
+							.nick
+							.clone()
+							.unwrap_or_else(|| user.display_name().to_string()),
+						detail: Some(user.username.clone()),
+						image:  Some(cdn::avatar(user, 32)),
+						text:   format!("@{}", user.username),
+						syntax: Some(format!("<@{}>", user.id)),
+					});
+				}
+
+				if let Some(guild) = GUILDS.read().guild(guild_id) {
+					// the @everyone role shares its id with the guild
+					for role in guild.roles.iter().filter(|r| {
+						r.mentionable && r.id != guild_id && r.name.to_lowercase().starts_with(&query)
+					}) {
+						found.push(Suggestion {
+							key:    format!("role-{}", role.id),
+							label:  format!("@{}", role.name),
+							detail: None,
+							image:  None,
+							text:   format!("@{}", role.name),
+							syntax: Some(format!("<@&{}>", role.id)),
+						});
+					}
+				}
+			}
+
+			for special in ["everyone", "here"] {
+				if special.starts_with(&query) {
+					found.push(Suggestion {
+						key:    special.to_string(),
+						label:  format!("@{}", special),
+						detail: None,
+						image:  None,
+						text:   format!("@{}", special),
+						syntax: None,
+					});
+				}
+			}
+		},
+		| Trigger::Channel => {
+			let guilds = GUILDS.read();
+			let channels = guild_id
+				.and_then(|id| guilds.guild(id))
+				.map(|g| g.channels.as_slice())
+				.unwrap_or_default();
+
+			for channel in channels.iter().filter(|c| c.r#type != GUILD_CATEGORY) {
+				let Some(name) = &channel.name else {
+					continue;
+				};
+				if name.to_lowercase().starts_with(&query) {
+					found.push(Suggestion {
+						key:    format!("channel-{}", channel.id),
+						label:  format!("#{}", name),
+						detail: None,
+						image:  None,
+						text:   format!("#{}", name),
+						syntax: Some(format!("<#{}>", channel.id)),
+					});
+				}
+			}
+		},
+		| Trigger::Emoji => {
+			let guilds = GUILDS.read();
+			// the current guild's emojis first
+			let mut sorted: Vec<_> = guilds.guilds().iter().collect();
+			sorted.sort_by_key(|g| Some(g.id) != guild_id);
+
+			for emoji in sorted.iter().flat_map(|g| &g.emojis).filter(|e| e.available) {
+				let (Some(id), Some(name)) = (emoji.id, &emoji.name) else {
+					continue;
+				};
+				if name.to_lowercase().contains(&query) {
+					let prefix = if emoji.animated { "a" } else { "" };
+					found.push(Suggestion {
+						key:    format!("emoji-{}", id),
+						label:  format!(":{}:", name),
+						detail: None,
+						image:  Some(cdn::emoji(id, emoji.animated, 32)),
+						text:   format!(":{}:", name),
+						syntax: Some(format!("<{}:{}:{}>", prefix, name, id)),
+					});
+				}
+			}
+
+			for emoji in emojis::iter() {
+				if let Some(shortcode) = emoji.shortcodes().find(|s| s.starts_with(&query)) {
+					found.push(Suggestion {
+						key:    format!("unicode-{}", emoji.as_str()),
+						label:  format!("{} :{}:", emoji.as_str(), shortcode),
+						detail: None,
+						image:  None,
+						text:   emoji.as_str().to_string(),
+						syntax: None,
+					});
+				}
+				if found.len() >= MAX_SUGGESTIONS {
+					break;
+				}
+			}
+		},
+		| Trigger::Command => {
+			for command in commands
+				.iter()
+				.filter(|c| c.r#type == 1 && c.name.starts_with(&query))
+			{
+				found.push(Suggestion {
+					key:    format!("command-{}", command.id),
+					label:  format!("/{}", command.name),
+					detail: Some(command.description.clone()),
+					image:  None,
+					text:   format!("/{}", command.name),
+					syntax: None,
+				});
+			}
+		},
+	}
+
+	found.truncate(MAX_SUGGESTIONS);
+	found
+}
+
+#[component]
+pub fn Autocomplete(
+	suggestions: Vec<Suggestion>,
+	selected: usize,
+	on_select: EventHandler<usize>,
+) -> Element {
+	rsx! {
+		div {
+			class: "autocomplete",
+			for (index, suggestion) in suggestions.iter().enumerate() {
+				div {
+					key: "{suggestion.key}",
+					class: if index == selected { "autocomplete-item selected" } else { "autocomplete-item" },
+					// mousedown instead of click so the textarea doesn't lose focus first
+					onmousedown: move |event| {
+						event.prevent_default();
+						on_select.call(index);
+					},
+					if let Some(image) = &suggestion.image {
+						img { class: "autocomplete-image", src: "{image}", alt: "" }
+					}
+					span { class: "autocomplete-label", "{suggestion.label}" }
+					if let Some(detail) = &suggestion.detail {
+						span { class: "autocomplete-detail", "{detail}" }
+					}
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn finds_the_word_at_the_cursor() {
+		assert_eq!(active_query("hi @co", 6), Some((Trigger::Mention, 3, "co")));
+		assert_eq!(active_query("hi @co there", 6), Some((Trigger::Mention, 3, "co")));
+		assert_eq!(active_query("#", 1), Some((Trigger::Channel, 0, "")));
+		assert_eq!(active_query("hi @co there", 12), None);
+		assert_eq!(active_query("email@me", 8), None);
+	}
+
+	#[test]
+	fn emoji_and_command_rules() {
+		assert_eq!(active_query(":c", 2), None);
+		assert_eq!(active_query(":co", 3), Some((Trigger::Emoji, 0, "co")));
+		assert_eq!(active_query(":cow:", 5), None);
+		assert_eq!(active_query("/ban", 4), Some((Trigger::Command, 0, "ban")));
+		assert_eq!(active_query("a /ban", 6), None);
+	}
+
+	#[test]
+	fn completion_replaces_the_query() {
+		assert_eq!(complete("hi @co", 3, 6, "@cow"), ("hi @cow ".to_string(), 8));
+		assert_eq!(complete("@co there", 0, 3, "@cow"), ("@cow there".to_string(), 5));
+	}
+
+	fn completion(
+		start: usize,
+		text: &str,
+		syntax: &str,
+	) -> Completion {
+		Completion {
+			start,
+			text: text.to_string(),
+			syntax: syntax.to_string(),
+		}
+	}
+
+	#[test]
+	fn only_completed_text_becomes_syntax() {
+		let completions = vec![completion(0, "@cowboy", "<@2>"), completion(12, "@cow", "<@1>")];
+		assert_eq!(to_syntax("@cowboy and @cow", &completions), "<@2> and <@1>");
+
+		// typed by hand, in an email or in code, none of these got completed
+		let completions = vec![completion(4, "@cow", "<@1>")];
+		assert_eq!(
+			to_syntax("hi, @cow @cowboy me@cow.dev `@cow`", &completions),
+			"hi, <@1> @cowboy me@cow.dev `@cow`"
+		);
+	}
+
+	#[test]
+	fn completions_follow_edits() {
+		let mut completions = vec![completion(3, "@cow", "<@1>")];
+
+		// typing before it moves it along
+		remap(&mut completions, "hi @cow ", "oh hi @cow ");
+		assert_eq!(completions, vec![completion(6, "@cow", "<@1>")]);
+
+		// typing after it doesn't
+		remap(&mut completions, "oh hi @cow ", "oh hi @cow boy");
+		assert_eq!(to_syntax("oh hi @cow boy", &completions), "oh hi <@1> boy");
+
+		// running it into the next word makes it something else
+		remap(&mut completions, "oh hi @cow boy", "oh hi @cowboy");
+		assert_eq!(to_syntax("oh hi @cowboy", &completions), "oh hi @cowboy");
+
+		// and editing it gets rid of it
+		remap(&mut completions, "oh hi @cowboy", "oh hi @cwboy");
+		assert!(completions.is_empty());
+	}
+
+	#[test]
+	fn utf16_offsets() {
+		let text = "🐄 @co";
+
+		assert_eq!(utf16_to_byte(text, 3), 5);
+		assert_eq!(byte_to_utf16(text, 5), 3);
+		assert_eq!(utf16_to_byte(text, 100), text.len());
+	}
+}
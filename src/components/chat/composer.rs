@@ -0,0 +1,389 @@
+use std::collections::HashSet;
+use std::error::Error;
+use std::sync::atomic::AtomicU16;
+
+use dioxus::prelude::*;
+use serde_json::json;
+
+use super::autocomplete::{
+	Autocomplete,
+	Completion,
+	MAX_SUGGESTIONS,
+	Suggestion,
+	Trigger,
+	active_query,
+	byte_to_utf16,
+	complete,
+	remap,
+	suggestions,
+	to_syntax,
+	utf16_to_byte,
+};
+use super::{ChatState, create_message, get_command_index};
+use crate::models::chat::{AllowedMentions, CreateMessageRequest, Message, MessageReference};
+use crate::models::command::ApplicationCommand;
+use crate::models::types::Snowflake;
+use crate::store::messages::{PendingMessage, PendingState, Reply};
+use crate::store::{GUILD_MEMBERS, GUILDS, MESSAGES, USERS};
+use crate::utils::gateway;
+use crate::utils::platform;
+
+static NONCE_INCREMENT: AtomicU16 = AtomicU16::new(0);
+
+// the textarea's cursor, in utf16 code units. through eval since there's no dom to hold on to on desktop
+const GET_SELECTION: &str = r#"
+	const id = await dioxus.recv();
+	dioxus.send(document.getElementById(id)?.selectionStart ?? null);
+"#;
+const SET_SELECTION: &str = r#"
+	const [id, value, position] = await dioxus.recv();
+	const el = document.getElementById(id);
+	if (el) {
+		el.value = value;
+		el.setSelectionRange(position, position);
+	}
+"#;
+
+fn input_id(channel_id: Snowflake) -> String { format!("composer-{}", channel_id) }
+
+async fn selection_start(channel_id: Snowflake) -> Option<usize> {
+	let mut eval = document::eval(GET_SELECTION);
+	eval.send(input_id(channel_id)).ok()?;
+	eval.recv().await.ok().flatten()
+}
+
+fn set_selection(
+	channel_id: Snowflake,
+	value: &str,
+	position: usize,
+) {
+	let _ = document::eval(SET_SELECTION).send((input_id(channel_id), value, position));
+}
+
+/// sends whatever is queued for the channel, one message at a time.
+/// does nothing if a send is already in flight, that one picks up the rest of the queue
+pub(super) fn send_queued(channel_id: Snowflake) {
+	// not tied to whoever asked, switching channels or the retry button unmounting would drop it mid-send
+	spawn_forever(drain_queue(channel_id, |channel_id, request| async move {
+		create_message(channel_id, &request).await
+	}));
+}
+
+/// puts a claimed message back in the queue if its send is dropped halfway, or nothing would send it again
+struct Claimed {
+	channel_id: Snowflake,
+	nonce:      Snowflake,
+	done:       bool,
+}
+
+impl Drop for Claimed {
+	fn drop(&mut self) {
+		// nothing to put back if the whole app's going away
+		if !self.done && Runtime::current().is_ok() {
+			MESSAGES.write().unclaim_pending(self.channel_id, self.nonce);
+		}
+	}
+}
+
+async fn drain_queue<F, Fut>(
+	channel_id: Snowflake,
+	send: F,
+) where
+	F: Fn(Snowflake, CreateMessageRequest) -> Fut,
+	Fut: Future<Output = Result<Message, Box<dyn Error>>>,
+{
+	loop {
+		let next = MESSAGES.write().next_pending(channel_id);
+		let Some(pending) = next else {
+			break;
+		};
+		let mut claimed = Claimed {
+			channel_id,
+			nonce: pending.nonce,
+			done: false,
+		};
+
+		let request = CreateMessageRequest {
+			content: pending.content,
+			nonce: Some(pending.nonce.to_string()),
+			enforce_nonce: true,
+			message_reference: pending.reply_to.map(|reply| MessageReference {
+				r#type:             0,
+				message_id:         Some(reply.message_id),
+				channel_id:         Some(channel_id),
+				guild_id:           None,
+				fail_if_not_exists: Some(false),
+			}),
+			allowed_mentions: pending.reply_to.map(|reply| AllowedMentions {
+				parse:        vec!["users".to_string(), "roles".to_string(), "everyone".to_string()],
+				replied_user: reply.mention,
+			}),
+		};
+
+		let result = send(channel_id, request).await;
+		claimed.done = true;
+		match result {
+			| Ok(message) => MESSAGES.write().insert(message),
+			| Err(e) => {
+				platform::error(&format!("Failed to send message: {}", e));
+				MESSAGES.write().fail_pending(channel_id, pending.nonce);
+			},
+		}
+	}
+}
+
+fn last_own_message(channel_id: Snowflake) -> Option<Snowflake> {
+	let current = USERS.peek().current()?.id;
+
+	MESSAGES
+		.peek()
+		.channel(channel_id)?
+		.messages
+		.iter()
+		.rev()
+		.find(|m| m.author.id == current)
+		.map(|m| m.id)
+}
+
+#[component]
+pub fn Composer(channel_id: Snowflake) -> Element {
+	let mut state = use_context::<ChatState>();
+	let mut content = use_signal(String::new);
+	let mut cursor = use_signal(|| 0); // in bytes
+	let mut selected = use_signal(|| 0);
+	let mut dismissed = use_signal(|| false);
+	// everything autocompleted so far, kept in step with the text as it's edited
+	let mut completions = use_signal(Vec::<Completion>::new);
+	let mut commands = use_signal(|| None::<Vec<ApplicationCommand>>);
+	let mut searched = use_hook(|| CopyValue::new(HashSet::<String>::new()));
+
+	let guild_id = GUILDS.read().find_channel(channel_id).map(|(g, _)| g.id);
+
+	let text = content();
+	let active = active_query(&text, cursor()).filter(|_| !dismissed());
+	let found = match active {
+		| Some((trigger, _, query)) => {
+			let commands = commands.read();
+			suggestions(trigger, query, guild_id, commands.as_deref().unwrap_or_default())
+		},
+		| None => Vec::new(),
+	};
+	// where the word being completed starts, if there's anything to pick from
+	let completing = active.map(|(_, start, _)| start).filter(|_| !found.is_empty());
+	let selected_index = selected().min(found.len().saturating_sub(1));
+
+	let update_cursor = move || async move {
+		let position = selection_start(channel_id).await;
+		let value = content.peek();
+		cursor.set(position.map_or(value.len(), |p| utf16_to_byte(&value, p)));
+	};
+
+	let oninput = move |event: FormEvent| async move {
+		let value = event.value();
+		remap(&mut completions.write(), &content.peek(), &value);
+		content.set(value.clone());
+		update_cursor().await;
+		dismissed.set(false);
+		selected.set(0);
+
+		match active_query(&value, *cursor.peek()) {
+			// only hit the gateway if the members we know about don't fill the list
+			| Some((Trigger::Mention, _, query)) if !query.is_empty() => {
+				let query = query.to_lowercase();
+				if let Some(guild_id) = guild_id
+					&& GUILD_MEMBERS.peek().search(guild_id, &query, MAX_SUGGESTIONS).len() < MAX_SUGGESTIONS
+					&& searched.write().insert(query.clone())
+				{
+					gateway::send(8, json!({
+						"guild_id": guild_id.to_string(),
+						"query": query,
+						"limit": MAX_SUGGESTIONS,
+						"presences": false,
+					}));
+				}
+			},
+			| Some((Trigger::Command, ..)) if commands.peek().is_none() => {
+				commands.set(Some(Vec::new()));
+				spawn(async move {
+					match get_command_index(channel_id).await {
+						| Ok(index) => commands.set(Some(index.application_commands)),
+						| Err(e) => platform::error(&format!("Failed to load commands: {}", e)),
+					}
+				});
+			},
+			| _ => {},
+		}
+	};
+
+	let mut select = move |suggestion: Suggestion, start: usize| {
+		let (text, position) = complete(&content.peek(), start, *cursor.peek(), &suggestion.text);
+
+		remap(&mut completions.write(), &content.peek(), &text);
+		if let Some(syntax) = suggestion.syntax {
+			completions.write().push(Completion {
+				start,
+				text: suggestion.text,
+				syntax,
+			});
+		}
+		// set it on the element first so the cursor ends up after the completion instead of at the end
+		set_selection(channel_id, &text, byte_to_utf16(&text, position));
+
+		cursor.set(position);
+		content.set(text);
+	};
+
+	let mut send = move || {
+		let text = to_syntax(&content.peek(), &completions.peek()).trim().to_string();
+		if text.is_empty() {
+			return;
+		}
+
+		MESSAGES.write().add_pending(channel_id, PendingMessage {
+			nonce:    Snowflake::generate(0, 0, &NONCE_INCREMENT),
+			content:  text,
+			reply_to: state.reply_to.take(),
+			state:    PendingState::Queued,
+		});
+		content.set(String::new());
+		completions.write().clear();
+
+		send_queued(channel_id);
+	};
+
+	let onkeydown = {
+		let found = found.clone();
+		move |event: KeyboardEvent| {
+			if let Some(start) = completing {
+				let handled = match event.key() {
+					| Key::ArrowUp => {
+						selected.set((selected_index + found.len() - 1) % found.len());
+						true
+					},
+					| Key::ArrowDown => {
+						selected.set((selected_index + 1) % found.len());
+						true
+					},
+					| Key::Enter | Key::Tab => {
+						select(found[selected_index].clone(), start);
+						true
+					},
+					| Key::Escape => {
+						dismissed.set(true);
+						true
+					},
+					| _ => false,
+				};
+
+				if handled {
+					event.prevent_default();
+					return;
+				}
+			}
+
+			match event.key() {
+				| Key::Enter if !event.modifiers().contains(Modifiers::SHIFT) => {
+					event.prevent_default();
+					send();
+				},
+				| Key::ArrowUp if content.read().is_empty() => {
+					if let Some(message_id) = last_own_message(channel_id) {
+						event.prevent_default();
+						state.editing.set(Some(message_id));
+					}
+				},
+				| Key::Escape => state.reply_to.set(None),
+				| _ => {},
+			}
+		}
+	};
+
+	let reply_to = (state.reply_to)();
+	let replying_to = reply_to.and_then(|reply| {
+		MESSAGES
+			.read()
+			.channel(channel_id)?
+			.messages
+			.iter()
+			.find(|m| m.id == reply.message_id)
+			.map(|m| m.author.display_name().to_string())
+	});
+
+	rsx! {
+		div {
+			class: "composer",
+
+			if let Some(start) = completing {
+				Autocomplete {
+					suggestions: found.clone(),
+					selected: selected_index,
+					on_select: move |index: usize| select(found[index].clone(), start),
+				}
+			}
+
+			if let Some(reply) = reply_to {
+				div {
+					class: "composer-reply",
+					span { "Replying to " strong { {replying_to.unwrap_or_default()} } }
+					button {
+						class: if reply.mention { "composer-reply-mention on" } else { "composer-reply-mention" },
+						title: "Mention the author",
+						onclick: move |_| {
+							state.reply_to.set(Some(Reply {
+								mention: !reply.mention,
+								..reply
+							}));
+						},
+						if reply.mention { "@ ON" } else { "@ OFF" }
+					}
+					button {
+						class: "composer-reply-cancel",
+						onclick: move |_| state.reply_to.set(None),
+						"✕"
+					}
+				}
+			}
+
+			textarea {
+				id: input_id(channel_id),
+				class: "composer-input",
+				rows: 1,
+				placeholder: "Message",
+				value: "{content}",
+				oninput,
+				onkeydown,
+				onkeyup: move |_| update_cursor(),
+				onclick: move |_| update_cursor(),
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use futures_util::FutureExt;
+
+	use super::*;
+	use crate::testing::in_runtime;
+
+	#[tokio::test]
+	async fn cancelled_sends_go_back_in_the_queue() {
+		in_runtime(async {
+			let channel = Snowflake::new(1);
+			MESSAGES.write().add_pending(channel, PendingMessage {
+				nonce:    Snowflake::new(100),
+				content:  "hi".to_string(),
+				reply_to: None,
+				state:    PendingState::Queued,
+			});
+
+			// a send that never finishes, dropped after it's started like a cancelled task would be
+			let send = drain_queue(channel, |_, _| std::future::pending());
+			assert!(send.now_or_never().is_none());
+
+			let next = MESSAGES.write().next_pending(channel);
+			assert_eq!(next.map(|p| p.nonce), Some(Snowflake::new(100)));
+		})
+		.await;
+	}
+}
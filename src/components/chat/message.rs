@@ -0,0 +1,292 @@
+use dioxus::prelude::*;
+
+use super::composer::send_queued;
+use super::{ChatState, delete_message, edit_message};
+use crate::components::markdown::Markdown;
+use crate::components::modals::confirm::ConfirmModal;
+use crate::components::users::user_profile_popup::ProfileTrigger;
+use crate::models::chat::Message;
+use crate::models::types::Snowflake;
+use crate::store::messages::{PendingMessage, PendingState, Reply};
+use crate::store::{MESSAGES, USERS};
+use crate::utils::{cdn, platform, time};
+
+// https://docs.discord.sex/resources/message#message-type
+const REPLY: u8 = 19;
+
+fn delete(
+	channel_id: Snowflake,
+	message_id: Snowflake,
+) {
+	spawn(async move {
+		match delete_message(channel_id, message_id).await {
+			| Ok(()) => MESSAGES.write().delete(channel_id, &[message_id]),
+			| Err(e) => platform::error(&format!("Failed to delete message: {}", e)),
+		}
+	});
+}
+
+#[component]
+pub fn MessageItem(
+	message: Message,
+	grouped: bool,
+	highlighted: bool,
+) -> Element {
+	let mut state = use_context::<ChatState>();
+	let mut confirm_delete = use_signal(|| false);
+	let mut edit_error = use_signal(|| None::<String>);
+
+	let author = &message.author;
+	let name = message
+		.member
+		.as_ref()
+		.and_then(|m| m.nick.as_deref())
+		.unwrap_or(author.display_name());
+	let sent_at = message.id.timestamp() as i64;
+	let own = USERS.read().current().is_some_and(|u| u.id == author.id);
+	let editing = (state.editing)() == Some(message.id);
+	let (channel_id, message_id) = (message.channel_id, message.id);
+
+	// the editor unmounts once editing stops, so the edit is sent from here and the editor only closes once
+	// it's saved
+	let save_edit = move |text: String| {
+		edit_error.set(None);
+		spawn(async move {
+			match edit_message(channel_id, message_id, text).await {
+				| Ok(message) => {
+					MESSAGES.write().replace(message);
+					if *state.editing.peek() == Some(message_id) {
+						state.editing.set(None);
+					}
+				},
+				| Err(e) => {
+					platform::error(&format!("Failed to edit message: {}", e));
+					edit_error.set(Some("Couldn't save your edit, try again.".to_string()));
+				},
+			}
+		});
+	};
+
+	let mut class = String::from("message");
+	if grouped {
+		class.push_str(" grouped");
+	}
+	if highlighted {
+		class.push_str(" highlighted");
+	}
+
+	rsx! {
+		div {
+			id: "message-{message.id}",
+			class,
+
+			if message.r#type == REPLY {
+				div {
+					class: "message-reply-preview",
+					match &message.referenced_message {
+						Some(referenced) => rsx! {
+							span { class: "message-reply-author", "@{referenced.author.display_name()}" }
+							span { class: "message-reply-content", "{referenced.content}" }
+						},
+						None => rsx! {
+							span { class: "message-reply-deleted", "Original message was deleted" }
+						},
+					}
+				}
+			}
+
+			if grouped {
+				span { class: "message-hover-time", {time::format(sent_at, 't')} }
+			} else {
+				ProfileTrigger {
+					user_id: author.id,
+					class: "message-avatar",
+					img { src: cdn::avatar(author, 80), alt: "" }
+				}
+				div {
+					class: "message-header",
+					ProfileTrigger {
+						user_id: author.id,
+						class: "message-author",
+						"{name}"
+					}
+					span { class: "message-timestamp", {time::format(sent_at, 'f')} }
+				}
+			}
+
+			if editing {
+				MessageEditor {
+					initial: message.content.clone(),
+					error: edit_error(),
+					on_save: save_edit,
+					on_empty: move |_| confirm_delete.set(true),
+				}
+			} else {
+				div {
+					class: "message-content",
+					Markdown { content: message.content.clone() }
+					if message.edited_timestamp.is_some() {
+						span { class: "message-edited", " (edited)" }
+					}
+				}
+			}
+
+			div {
+				class: "message-actions",
+				button {
+					title: "Reply",
+					onclick: move |_| {
+						state.reply_to.set(Some(Reply {
+							message_id,
+							mention: true,
+						}));
+					},
+					"Reply"
+				}
+				if own {
+					button {
+						title: "Edit",
+						onclick: move |_| {
+							edit_error.set(None);
+							state.editing.set(Some(message_id));
+						},
+						"Edit"
+					}
+					button {
+						class: "danger",
+						title: "Delete, hold shift to skip confirmation",
+						onclick: move |event: MouseEvent| {
+							if event.modifiers().contains(Modifiers::SHIFT) {
+								delete(channel_id, message_id);
+							} else {
+								confirm_delete.set(true);
+							}
+						},
+						"Delete"
+					}
+				}
+			}
+
+			if confirm_delete() {
+				ConfirmModal {
+					title: "Delete Message",
+					message: "Are you sure you want to delete this message?",
+					confirm_label: "Delete",
+					on_confirm: move |_| {
+						confirm_delete.set(false);
+						state.editing.set(None);
+						delete(channel_id, message_id);
+					},
+					on_cancel: move |_| confirm_delete.set(false),
+				}
+			}
+		}
+	}
+}
+
+/// enter saves, escape cancels and saving it empty asks to delete the message instead
+#[component]
+fn MessageEditor(
+	initial: String,
+	error: Option<String>,
+	on_save: EventHandler<String>,
+	on_empty: EventHandler<()>,
+) -> Element {
+	let mut state = use_context::<ChatState>();
+	let mut content = use_signal(|| initial.clone());
+
+	let mut save = move || {
+		let text = content.peek().trim().to_string();
+		if text.is_empty() {
+			on_empty.call(());
+			return;
+		}
+
+		if text == initial {
+			state.editing.set(None);
+			return;
+		}
+		on_save.call(text);
+	};
+
+	rsx! {
+		if let Some(error) = error {
+			p { class: "error", "{error}" }
+		}
+		textarea {
+			class: "message-editor",
+			value: "{content}",
+			onmounted: move |event| async move {
+				let _ = event.set_focus(true).await;
+			},
+			oninput: move |event| content.set(event.value()),
+			onkeydown: move |event: KeyboardEvent| match event.key() {
+				| Key::Enter if !event.modifiers().contains(Modifiers::SHIFT) => {
+					event.prevent_default();
+					save();
+				},
+				| Key::Escape => state.editing.set(None),
+				| _ => {},
+			},
+		}
+	}
+}
+
+#[component]
+pub fn PendingMessageItem(
+	channel_id: Snowflake,
+	message: PendingMessage,
+	grouped: bool,
+) -> Element {
+	let current = USERS.read().current().cloned();
+	let nonce = message.nonce;
+
+	let class = match (message.state, grouped) {
+		| (PendingState::Failed, true) => "message pending failed grouped",
+		| (PendingState::Failed, false) => "message pending failed",
+		| (_, true) => "message pending grouped",
+		| (_, false) => "message pending",
+	};
+
+	rsx! {
+		div {
+			class,
+
+			if let (false, Some(user)) = (grouped, &current) {
+				img {
+					class: "message-avatar",
+					src: cdn::avatar(user, 80),
+					alt: "",
+				}
+				div {
+					class: "message-header",
+					span { class: "message-author", "{user.display_name()}" }
+				}
+			}
+
+			div {
+				class: "message-content",
+				Markdown { content: message.content.clone() }
+			}
+
+			if message.state == PendingState::Failed {
+				div {
+					class: "message-failed",
+					"Message failed to send. "
+					button {
+						onclick: move |_| {
+							MESSAGES.write().retry_pending(channel_id, nonce);
+							send_queued(channel_id);
+						},
+						"Retry"
+					}
+					button {
+						class: "danger",
+						onclick: move |_| MESSAGES.write().remove_pending(channel_id, nonce),
+						"Delete"
+					}
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,368 @@
+use std::collections::HashSet;
+
+use dioxus::prelude::*;
+
+use super::{ack_message, get_channel_messages};
+use super::message::{MessageItem, PendingMessageItem};
+use crate::components::virtual_list::VirtualList;
+use crate::models::chat::{Message, MessageHistoryRequest};
+use crate::models::types::Snowflake;
+use crate::store::cache;
+use crate::store::messages::PAGE_SIZE;
+use crate::store::{MESSAGES, READ_STATES, RELATIONSHIPS, USERS};
+use crate::utils::platform;
+
+// messages from the same author within this window get grouped under one header
+const GROUP_TIMEOUT_MS: u64 = 7 * 60 * 1000;
+const CHANNEL_START_KEY: &str = "channel-start";
+// how long to sit at the bottom of a channel before acking it, so new messages in a busy channel don't spam acks
+const ACK_DELAY_MS: u32 = 1500;
+
+fn is_grouped(
+	previous: Option<&Message>,
+	message: &Message,
+) -> bool {
+	let Some(previous) = previous else {
+		return false;
+	};
+
+	// https://docs.discord.sex/resources/message#message-type, replies always get their own header
+	previous.author.id == message.author.id
+		&& matches!(previous.r#type, 0 | 19)
+		&& message.r#type == 0
+		&& message.id.timestamp().saturating_sub(previous.id.timestamp()) < GROUP_TIMEOUT_MS
+}
+
+/// where the new messages divider goes: the first message after what was acked,
+/// unless the acked message isn't loaded, then everything on screen would count as new
+fn divider_index(
+	messages: &[Message],
+	last_acked: Option<Snowflake>,
+	has_more_before: bool,
+) -> Option<usize> {
+	let acked = last_acked?;
+	let index = messages.iter().position(|m| m.id > acked)?;
+
+	(index > 0 || !has_more_before).then_some(index)
+}
+
+/// the run of consecutive messages from blocked users that `index` falls in, as its first index and length
+fn blocked_run(
+	messages: &[Message],
+	index: usize,
+	is_blocked: impl Fn(Snowflake) -> bool,
+) -> Option<(usize, usize)> {
+	let blocked = |m: &Message| is_blocked(m.author.id);
+	if !blocked(messages.get(index)?) {
+		return None;
+	}
+
+	let start = messages[..index].iter().rposition(|m| !blocked(m)).map_or(0, |i| i + 1);
+	let len = messages[start..].iter().take_while(|m| blocked(m)).count();
+
+	Some((start, len))
+}
+
+async fn load_page(
+	channel_id: Snowflake,
+	request: MessageHistoryRequest,
+) -> Option<Vec<Message>> {
+	match get_channel_messages(channel_id, request).await {
+		| Ok(page) => {
+			USERS.write().extend_from_messages(&page);
+			Some(page)
+		},
+		| Err(e) => {
+			platform::error(&format!("Failed to load messages: {}", e));
+			None
+		},
+	}
+}
+
+/// should be keyed by channel and jump target, everything here assumes those never change while mounted
+#[component]
+pub fn MessageList(
+	channel_id: Snowflake,
+	around: Option<Snowflake>,
+) -> Element {
+	let mut loaded = use_signal(|| false);
+	let mut loading = use_signal(|| false);
+	let mut at_bottom = use_signal(|| around.is_none());
+	// the divider stays where it was when the channel got opened, even after acking
+	let last_acked = use_hook(|| READ_STATES.peek().last_acked(channel_id));
+	let mut ack_task = use_hook(|| CopyValue::new(None::<Task>));
+	// blocked runs that got expanded, by their first message
+	let mut revealed = use_signal(HashSet::<Snowflake>::new);
+
+	use_effect(move || {
+		let newest = MESSAGES
+			.read()
+			.channel(channel_id)
+			.filter(|c| !c.has_more_after)
+			.and_then(|c| c.newest());
+		let Some(newest) = newest.filter(|_| loaded() && at_bottom()) else {
+			return;
+		};
+		if READ_STATES.peek().last_acked(channel_id) >= Some(newest) {
+			return;
+		}
+
+		if let Some(task) = ack_task.write().take() {
+			task.cancel();
+		}
+		ack_task.set(Some(spawn(async move {
+			platform::sleep(ACK_DELAY_MS).await;
+
+			READ_STATES.write().ack(channel_id, newest, 0);
+			let token = READ_STATES.peek().ack_token.clone();
+			match ack_message(channel_id, newest, token).await {
+				| Ok(response) => READ_STATES.write().ack_token = response.token,
+				| Err(e) => platform::error(&format!("Failed to ack message: {}", e)),
+			}
+		})));
+	});
+
+	use_hook(move || {
+		spawn(async move {
+			match around {
+				| Some(message_id) => {
+					let request = MessageHistoryRequest {
+						around: Some(message_id),
+						limit: PAGE_SIZE,
+						..Default::default()
+					};
+
+					if let Some(page) = load_page(channel_id, request).await {
+						MESSAGES.write().set_around(channel_id, page);
+					}
+				},
+				| None => {
+					let stale = MESSAGES
+						.peek()
+						.channel(channel_id)
+						.is_none_or(|c| c.has_more_after || c.cached);
+
+					if stale {
+						let request = MessageHistoryRequest {
+							limit: PAGE_SIZE,
+							..Default::default()
+						};
+
+						if let Some(page) = load_page(channel_id, request).await {
+							MESSAGES.write().set_latest(channel_id, page);
+							cache::save_channel(channel_id).await;
+						}
+					}
+				},
+			}
+
+			loaded.set(true);
+		});
+	});
+
+	// whatever came in live while the channel was open
+	use_drop(move || {
+		spawn_forever(cache::save_channel(channel_id));
+	});
+
+	let load_older = move |_| {
+		if loading() {
+			return;
+		}
+
+		let oldest = match MESSAGES.peek().channel(channel_id) {
+			| Some(c) if c.has_more_before => c.oldest(),
+			| _ => return,
+		};
+
+		loading.set(true);
+		spawn(async move {
+			let request = MessageHistoryRequest {
+				before: oldest,
+				limit: PAGE_SIZE,
+				..Default::default()
+			};
+
+			if let Some(page) = load_page(channel_id, request).await {
+				MESSAGES.write().prepend(channel_id, page);
+			}
+			loading.set(false);
+		});
+	};
+
+	let load_newer = move |_| {
+		if loading() {
+			return;
+		}
+
+		let newest = match MESSAGES.peek().channel(channel_id) {
+			| Some(c) if c.has_more_after => c.newest(),
+			| _ => return,
+		};
+
+		loading.set(true);
+		spawn(async move {
+			let request = MessageHistoryRequest {
+				after: newest,
+				limit: PAGE_SIZE,
+				..Default::default()
+			};
+
+			if let Some(page) = load_page(channel_id, request).await {
+				MESSAGES.write().append(channel_id, page);
+			}
+			loading.set(false);
+		});
+	};
+
+	// the start of channel banner takes up the first row once there's nothing older left
+	let render_item = use_callback(move |index: usize| {
+		let store = MESSAGES.read();
+		let Some(channel) = store.channel(channel_id) else {
+			return rsx! {};
+		};
+
+		let offset = usize::from(!channel.has_more_before);
+		if index < offset {
+			return rsx! {
+				div { class: "channel-start", "This is the start of the channel" }
+			};
+		}
+
+		let index = index - offset;
+		let Some(message) = channel.messages.get(index) else {
+			// local echoes go after everything else
+			let index = index - channel.messages.len();
+			let current = USERS.read().current().map(|u| u.id);
+			let grouped = index > 0 || channel.messages.last().is_some_and(|m| Some(m.author.id) == current);
+
+			return rsx! {
+				PendingMessageItem {
+					channel_id,
+					message: channel.pending[index].clone(),
+					grouped,
+				}
+			};
+		};
+
+		let relationships = RELATIONSHIPS.read();
+		let run = blocked_run(&channel.messages, index, |id| relationships.is_blocked(id))
+			.map(|(start, len)| (start, len, channel.messages[start].id))
+			.filter(|(_, _, first)| !revealed.read().contains(first));
+		if let Some((start, len, first)) = run {
+			if start != index {
+				return rsx! {};
+			}
+
+			return rsx! {
+				div {
+					class: "blocked-messages",
+					span { if len == 1 { "1 blocked message" } else { "{len} blocked messages" } }
+					button {
+						onclick: move |_| {
+							revealed.write().insert(first);
+						},
+						"Show"
+					}
+				}
+			};
+		}
+
+		let divider = divider_index(&channel.messages, last_acked, channel.has_more_before) == Some(index);
+
+		rsx! {
+			if divider {
+				div { class: "new-messages-divider", span { "New Messages" } }
+			}
+			MessageItem {
+				message: message.clone(),
+				grouped: !divider && is_grouped(index.checked_sub(1).map(|p| &channel.messages[p]), message),
+				highlighted: around == Some(message.id),
+			}
+		}
+	});
+
+	let store = MESSAGES.read();
+	let channel = store.channel(channel_id).filter(|c| loaded() || c.cached);
+
+	rsx! {
+		match channel {
+			Some(channel) => rsx! {
+				VirtualList {
+					class: "message-list",
+					keys: (!channel.has_more_before)
+						.then(|| CHANNEL_START_KEY.to_string())
+						.into_iter()
+						.chain(channel.messages.iter().map(|m| m.id.to_string()))
+						.chain(
+							channel
+								.pending
+								.iter()
+								.filter(|_| !channel.has_more_after)
+								.map(|p| format!("pending-{}", p.nonce)),
+						)
+						.collect::<Vec<_>>(),
+					render_item,
+					estimated_height: 44.0,
+					stick_to_bottom: around.is_none(),
+					scroll_to: around.map(|id| id.to_string()),
+					on_reach_start: load_older,
+					on_reach_end: load_newer,
+					on_bottom_change: move |bottom| at_bottom.set(bottom),
+				}
+			},
+			None => rsx! {
+				div { class: "message-list-loading", "Loading messages..." }
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	fn message(id: u64) -> Message {
+		from(id, 2)
+	}
+
+	fn from(
+		id: u64,
+		author: u64,
+	) -> Message {
+		serde_json::from_value(json!({
+			"id": id.to_string(),
+			"channel_id": "1",
+			"author": { "id": author.to_string(), "username": "cow" },
+			"timestamp": "2025-04-10T18:21:33.146000+00:00",
+		}))
+		.unwrap()
+	}
+
+	#[test]
+	fn divider_goes_after_the_acked_message() {
+		let messages = vec![message(10), message(20), message(30)];
+		let id = Snowflake::new;
+
+		assert_eq!(divider_index(&messages, Some(id(10)), true), Some(1));
+		assert_eq!(divider_index(&messages, Some(id(30)), true), None);
+		assert_eq!(divider_index(&messages, None, false), None);
+		// the acked message isn't loaded yet
+		assert_eq!(divider_index(&messages, Some(id(5)), true), None);
+		assert_eq!(divider_index(&messages, Some(id(5)), false), Some(0));
+	}
+
+	#[test]
+	fn blocked_runs_cover_consecutive_messages() {
+		let messages = vec![from(1, 2), from(2, 3), from(3, 3), from(4, 2), from(5, 3)];
+		let blocked = |id: Snowflake| id == Snowflake::new(3);
+
+		assert_eq!(blocked_run(&messages, 0, blocked), None);
+		assert_eq!(blocked_run(&messages, 1, blocked), Some((1, 2)));
+		assert_eq!(blocked_run(&messages, 2, blocked), Some((1, 2)));
+		assert_eq!(blocked_run(&messages, 4, blocked), Some((4, 1)));
+		assert_eq!(blocked_run(&messages, 5, blocked), None);
+	}
+}
@@ -0,0 +1,115 @@
+mod autocomplete;
+mod composer;
+mod message;
+mod message_list;
+
+use std::error::Error;
+
+use composer::Composer;
+use dioxus::prelude::*;
+use message_list::MessageList;
+
+use crate::models::chat::{CreateMessageRequest, EditMessageRequest, Message, MessageHistoryRequest};
+use crate::models::command::ApplicationCommandIndex;
+use crate::models::read_state::{AckRequest, AckResponse};
+use crate::models::types::Snowflake;
+use crate::store::messages::Reply;
+use crate::utils::request::RequestClient;
+
+async fn get_channel_messages(
+	channel_id: Snowflake,
+	request: MessageHistoryRequest,
+) -> Result<Vec<Message>, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	// todo catch the permission errors for no VIEW_CHANNEL perms and no READ_MESSAGE_HISTORY perms
+	client
+		.get_with_query(&format!("/channels/{}/messages", channel_id), &request)
+		.await
+}
+
+async fn create_message(
+	channel_id: Snowflake,
+	request: &CreateMessageRequest,
+) -> Result<Message, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.post(&format!("/channels/{}/messages", channel_id), request)
+		.await
+}
+
+async fn edit_message(
+	channel_id: Snowflake,
+	message_id: Snowflake,
+	content: String,
+) -> Result<Message, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.patch(
+			&format!("/channels/{}/messages/{}", channel_id, message_id),
+			&EditMessageRequest { content },
+		)
+		.await
+}
+
+async fn delete_message(
+	channel_id: Snowflake,
+	message_id: Snowflake,
+) -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.delete(&format!("/channels/{}/messages/{}", channel_id, message_id))
+		.await
+}
+
+async fn get_command_index(channel_id: Snowflake) -> Result<ApplicationCommandIndex, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.get(&format!("/channels/{}/application-command-index", channel_id))
+		.await
+}
+
+async fn ack_message(
+	channel_id: Snowflake,
+	message_id: Snowflake,
+	token: Option<String>,
+) -> Result<AckResponse, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.post(
+			&format!("/channels/{}/messages/{}/ack", channel_id, message_id),
+			&AckRequest { token },
+		)
+		.await
+}
+
+// shared between the message list and the composer
+#[derive(Clone, Copy)]
+struct ChatState {
+	reply_to: Signal<Option<Reply>>,
+	editing:  Signal<Option<Snowflake>>,
+}
+
+#[component]
+pub fn Chat(
+	channel_id: Snowflake,
+	around: Option<Snowflake>,
+) -> Element {
+	use_context_provider(|| ChatState {
+		reply_to: Signal::new(None),
+		editing:  Signal::new(None),
+	});
+
+	rsx! {
+		div {
+			class: "chat",
+			MessageList { channel_id, around }
+			Composer { channel_id }
+		}
+	}
+}
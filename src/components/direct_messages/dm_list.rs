@@ -0,0 +1,154 @@
+use dioxus::prelude::*;
+
+use super::{close_private_channel, open_private_channel};
+use crate::Route;
+use crate::components::modals::confirm::ConfirmModal;
+use crate::components::modals::recipient_picker::RecipientPicker;
+use crate::models::chat::Channel;
+use crate::models::types::Snowflake;
+use crate::store::private_channels::{display_name, recipients};
+use crate::store::{PRIVATE_CHANNELS, READ_STATES, USERS};
+use crate::utils::cdn;
+
+// https://docs.discord.sex/resources/channel#channel-type
+const GROUP_DM: u8 = 3;
+// group dms fit 10 people including us
+pub const MAX_GROUP_RECIPIENTS: usize = 9;
+
+#[component]
+pub fn DmList() -> Element {
+	let route = use_route::<Route>();
+	let selected: Option<Snowflake> = match &route {
+		| Route::UserChat { channel_id } => channel_id.parse().ok(),
+		| _ => None,
+	};
+	let mut creating = use_signal(|| false);
+
+	let channels: Vec<Channel> = PRIVATE_CHANNELS.read().sorted().into_iter().cloned().collect();
+
+	rsx! {
+		nav {
+			class: "dm-list",
+			Link {
+				class: if route == (Route::Friends {}) { "dm-list-friends selected" } else { "dm-list-friends" },
+				to: Route::Friends {},
+				"Friends"
+			}
+
+			div {
+				class: "dm-list-header",
+				span { "Direct Messages" }
+				button {
+					title: "Create DM",
+					onclick: move |_| creating.set(true),
+					"+"
+				}
+			}
+
+			for channel in channels {
+				DmItem {
+					key: "{channel.id}",
+					selected: selected == Some(channel.id),
+					channel,
+				}
+			}
+
+			if creating() {
+				RecipientPicker {
+					title: "Select Friends",
+					confirm_label: "Create DM",
+					max: MAX_GROUP_RECIPIENTS,
+					on_confirm: move |users| {
+						creating.set(false);
+						open_private_channel(users);
+					},
+					on_cancel: move |_| creating.set(false),
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn DmItem(
+	channel: Channel,
+	selected: bool,
+) -> Element {
+	let mut confirm_leave = use_signal(|| false);
+
+	let recipients = recipients(&channel, &USERS.read());
+	let name = display_name(&channel, &recipients);
+	let is_group = channel.r#type == GROUP_DM;
+	let icon = match (&channel.icon, recipients.first()) {
+		| (Some(hash), _) => Some(cdn::channel_icon(channel.id, hash, 64)),
+		| (None, Some(user)) if !is_group => Some(cdn::avatar(user, 64)),
+		| _ => None,
+	};
+	let subtitle = is_group.then(|| format!("{} Members", recipients.len() + 1));
+
+	let unread = READ_STATES.read().is_unread(&channel);
+	let mentions = READ_STATES.read().mention_count(channel.id);
+	let channel_id = channel.id;
+
+	let mut class = String::from("dm-item");
+	for (flag, name) in [(selected, " selected"), (unread, " unread")] {
+		if flag {
+			class.push_str(name);
+		}
+	}
+
+	rsx! {
+		div {
+			class,
+			Link {
+				class: "dm-item-link",
+				to: Route::UserChat {
+					channel_id: channel_id.to_string(),
+				},
+				match icon {
+					Some(icon) => rsx! {
+						img { class: "dm-item-icon", src: icon, alt: "" }
+					},
+					None => rsx! {
+						div { class: "dm-item-icon group" }
+					},
+				}
+				div {
+					class: "dm-item-text",
+					span { class: "dm-item-name", "{name}" }
+					if let Some(subtitle) = subtitle {
+						span { class: "dm-item-subtitle", "{subtitle}" }
+					}
+				}
+				if mentions > 0 {
+					span { class: "mention-badge", "{mentions}" }
+				}
+			}
+			button {
+				class: "dm-item-close",
+				title: if is_group { "Leave Group" } else { "Close DM" },
+				onclick: move |_| {
+					if is_group {
+						confirm_leave.set(true);
+					} else {
+						close_private_channel(channel_id, selected);
+					}
+				},
+				"×"
+			}
+
+			if confirm_leave() {
+				ConfirmModal {
+					title: "Leave '{name}'",
+					message: "Are you sure you want to leave {name}? You won't be able to rejoin this group unless you are re-invited.",
+					confirm_label: "Leave Group",
+					on_confirm: move |_| {
+						confirm_leave.set(false);
+						close_private_channel(channel_id, selected);
+					},
+					on_cancel: move |_| confirm_leave.set(false),
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,102 @@
+pub mod dm_list;
+pub mod user_profile;
+
+use std::error::Error;
+
+use dioxus::prelude::*;
+use serde_json::json;
+
+use crate::Route;
+use crate::models::chat::{Channel, CreatePrivateChannelRequest};
+use crate::models::types::Snowflake;
+use crate::store::{PRIVATE_CHANNELS, USERS};
+use crate::utils::platform;
+use crate::utils::request::RequestClient;
+
+async fn create_private_channel(recipients: Vec<Snowflake>) -> Result<Channel, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.post("/users/@me/channels", &CreatePrivateChannelRequest { recipients })
+		.await
+}
+
+// closes a dm, or leaves a group dm
+async fn delete_channel(channel_id: Snowflake) -> Result<Channel, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client.delete(&format!("/channels/{}", channel_id)).await
+}
+
+pub async fn add_recipient(
+	channel_id: Snowflake,
+	user_id: Snowflake,
+) -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.put(&format!("/channels/{}/recipients/{}", channel_id, user_id), &json!({}))
+		.await
+}
+
+pub async fn remove_recipient(
+	channel_id: Snowflake,
+	user_id: Snowflake,
+) -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.delete(&format!("/channels/{}/recipients/{}", channel_id, user_id))
+		.await
+}
+
+/// makes a dm with one user or a group dm with several and opens it, a dm that already exists just gets opened
+pub fn open_private_channel(recipients: Vec<Snowflake>) {
+	let navigator = navigator();
+
+	if let [user_id] = recipients[..]
+		&& let Some(channel_id) = PRIVATE_CHANNELS.peek().dm_with(user_id).map(|c| c.id)
+	{
+		navigator.push(Route::UserChat {
+			channel_id: channel_id.to_string(),
+		});
+		return;
+	}
+
+	// whatever was clicked is often closing right as this starts, eg. the profile popup
+	spawn_forever(async move {
+		match create_private_channel(recipients).await {
+			| Ok(channel) => {
+				let channel_id = channel.id;
+				USERS.write().extend(channel.recipients.clone());
+				PRIVATE_CHANNELS.write().upsert(channel);
+
+				navigator.push(Route::UserChat {
+					channel_id: channel_id.to_string(),
+				});
+			},
+			| Err(e) => platform::error(&format!("Failed to open DM: {}", e)),
+		}
+	});
+}
+
+/// closes a dm or leaves a group, back to the friends page if it was open
+pub fn close_private_channel(
+	channel_id: Snowflake,
+	was_open: bool,
+) {
+	let navigator = navigator();
+
+	// the CHANNEL_DELETE can unmount the item before this gets to navigate
+	spawn_forever(async move {
+		match delete_channel(channel_id).await {
+			| Ok(_) => {
+				PRIVATE_CHANNELS.write().remove(channel_id);
+				if was_open {
+					navigator.push(Route::Friends {});
+				}
+			},
+			| Err(e) => platform::error(&format!("Failed to close DM: {}", e)),
+		}
+	});
+}
@@ -1 +1,30 @@
-// like the fullscreen user popup but its a sidebar yk? just integrate src\components\users\user_profile_popup.rs whenever thats made
+use dioxus::prelude::*;
+
+use crate::components::users::user_profile_popup::ProfileCard;
+use crate::components::users::{open_profile_modal, use_profile};
+use crate::models::types::Snowflake;
+
+/// the popup's profile as a sidebar next to a 1:1 dm, should be keyed by user
+#[component]
+pub fn DmUserProfile(user_id: Snowflake) -> Element {
+	let profile = use_profile(user_id, None);
+
+	rsx! {
+		aside {
+			class: "dm-user-profile",
+			match profile() {
+				Some(profile) => rsx! {
+					ProfileCard { profile, guild_id: None }
+					button {
+						class: "profile-view-full",
+						onclick: move |_| open_profile_modal(user_id),
+						"View Full Profile"
+					}
+				},
+				None => rsx! {
+					div { class: "profile-loading", "Loading..." }
+				},
+			}
+		}
+	}
+}
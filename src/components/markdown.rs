@@ -0,0 +1,186 @@
+use dioxus::prelude::*;
+
+use crate::store::{GUILDS, USERS};
+use crate::utils::markdown::{self, Node};
+use crate::utils::{cdn, time};
+
+#[component]
+pub fn Markdown(content: String) -> Element {
+	let nodes = markdown::parse(&content);
+	let class = if markdown::is_emoji_only(&nodes) {
+		"markdown jumbo"
+	} else {
+		"markdown"
+	};
+
+	rsx! {
+		div {
+			class,
+			{render_nodes(&nodes)}
+		}
+	}
+}
+
+fn render_nodes(nodes: &[Node]) -> Element {
+	rsx! {
+		for node in nodes {
+			{render(node)}
+		}
+	}
+}
+
+fn render(node: &Node) -> Element {
+	match node {
+		| Node::Text(text) => rsx! { "{text}" },
+		| Node::Emoji(emoji) => rsx! {
+			span { class: "emoji", "{emoji}" }
+		},
+		| Node::CustomEmoji { name, id, animated } => rsx! {
+			img {
+				class: "emoji",
+				src: cdn::emoji(*id, *animated, 48),
+				alt: ":{name}:",
+				title: ":{name}:",
+			}
+		},
+		| Node::Bold(children) => rsx! {
+			strong { {render_nodes(children)} }
+		},
+		| Node::Italic(children) => rsx! {
+			em { {render_nodes(children)} }
+		},
+		| Node::Underline(children) => rsx! {
+			u { {render_nodes(children)} }
+		},
+		| Node::Strikethrough(children) => rsx! {
+			s { {render_nodes(children)} }
+		},
+		| Node::Spoiler(children) => rsx! {
+			Spoiler { children: render_nodes(children) }
+		},
+		| Node::InlineCode(code) => rsx! {
+			code { class: "inline-code", "{code}" }
+		},
+		| Node::CodeBlock { language, code } => rsx! {
+			pre {
+				code {
+					class: language.as_ref().map(|l| format!("language-{}", l)),
+					"{code}"
+				}
+			}
+		},
+		| Node::BlockQuote(children) => rsx! {
+			blockquote { {render_nodes(children)} }
+		},
+		| Node::Heading { level, children } => match level {
+			| 1 => rsx! { h1 { {render_nodes(children)} } },
+			| 2 => rsx! { h2 { {render_nodes(children)} } },
+			| _ => rsx! { h3 { {render_nodes(children)} } },
+		},
+		| Node::Subtext(children) => rsx! {
+			small { class: "subtext", {render_nodes(children)} }
+		},
+		| Node::List { start: Some(start), items } => rsx! {
+			ol {
+				start: "{start}",
+				for item in items {
+					li { {render_nodes(item)} }
+				}
+			}
+		},
+		| Node::List { start: None, items } => rsx! {
+			ul {
+				for item in items {
+					li { {render_nodes(item)} }
+				}
+			}
+		},
+		| Node::Link { url, children } => rsx! {
+			a {
+				href: "{url}",
+				title: "{url}",
+				target: "_blank",
+				rel: "noreferrer noopener",
+				{render_nodes(children)}
+			}
+		},
+		| Node::Url(url) => rsx! {
+			a {
+				href: "{url}",
+				target: "_blank",
+				rel: "noreferrer noopener",
+				"{url}"
+			}
+		},
+		| Node::UserMention(user_id) => {
+			let name = USERS
+				.read()
+				.user(*user_id)
+				.map(|u| u.display_name().to_string())
+				.unwrap_or_else(|| "unknown-user".to_string());
+
+			rsx! {
+				span { class: "mention", "@{name}" }
+			}
+		},
+		| Node::RoleMention(role_id) => match GUILDS.read().find_role(*role_id) {
+			| Some(role) if role.color != 0 => rsx! {
+				span {
+					class: "mention",
+					style: "color: #{role.color:06x};",
+					"@{role.name}"
+				}
+			},
+			| Some(role) => rsx! {
+				span { class: "mention", "@{role.name}" }
+			},
+			| None => rsx! {
+				span { class: "mention", "@unknown-role" }
+			},
+		},
+		| Node::ChannelMention(channel_id) => match GUILDS.read().find_channel(*channel_id) {
+			| Some((guild, channel)) => {
+				let name = channel.name.clone().unwrap_or_default();
+				rsx! {
+					Link {
+						class: "mention",
+						to: "/channels/{guild.id}/{channel.id}",
+						"#{name}"
+					}
+				}
+			},
+			| None => rsx! {
+				span { class: "mention", "#unknown" }
+			},
+		},
+		| Node::Everyone => rsx! {
+			span { class: "mention", "@everyone" }
+		},
+		| Node::Here => rsx! {
+			span { class: "mention", "@here" }
+		},
+		| Node::Timestamp { unix, style } => {
+			let unix_ms = unix.saturating_mul(1000);
+			rsx! {
+				time {
+					class: "timestamp",
+					title: time::format(unix_ms, 'F'),
+					{time::format(unix_ms, *style)}
+				}
+			}
+		},
+	}
+}
+
+#[component]
+fn Spoiler(children: Element) -> Element {
+	let mut revealed = use_signal(|| false);
+
+	rsx! {
+		span {
+			class: if revealed() { "spoiler revealed" } else { "spoiler" },
+			onclick: move |_| revealed.set(true),
+			{children}
+		}
+	}
+}
@@ -6,3 +6,10 @@ mod server_list;
 pub use server_list::ServerList;
 
 pub mod chat;
+pub mod direct_messages;
+pub mod markdown;
+pub mod relationships;
+pub mod server;
+pub mod status;
+pub mod users;
+pub mod virtual_list;
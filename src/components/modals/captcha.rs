@@ -0,0 +1,63 @@
+use dioxus::prelude::*;
+
+use crate::models::error::CaptchaRequired;
+use crate::utils::platform;
+
+const CONTAINER_ID: &str = "captcha-container";
+
+// loads hcaptcha on first use and sends the solution back once it's solved
+const RENDER_HCAPTCHA: &str = r#"
+	const [container, sitekey, rqdata] = await dioxus.recv();
+	if (!window.hcaptcha) {
+		await new Promise((resolve, reject) => {
+			const script = document.createElement("script");
+			script.src = "https://js.hcaptcha.com/1/api.js?render=explicit";
+			script.onload = resolve;
+			script.onerror = reject;
+			document.head.appendChild(script);
+		});
+	}
+	const id = hcaptcha.render(container, { sitekey, callback: (key) => dioxus.send(key) });
+	if (rqdata) {
+		hcaptcha.setData(id, { rqdata });
+	}
+"#;
+
+/// `on_solved` gets the captcha key, which goes in the X-Captcha-Key header of the retried request
+#[component]
+pub fn CaptchaModal(
+	challenge: CaptchaRequired,
+	on_solved: EventHandler<String>,
+	on_cancel: EventHandler<()>,
+) -> Element {
+	let render = move |_| {
+		let challenge = challenge.clone();
+
+		spawn(async move {
+			let mut eval = document::eval(RENDER_HCAPTCHA);
+			let _ = eval.send((CONTAINER_ID, &challenge.captcha_sitekey, &challenge.captcha_rqdata));
+
+			match eval.recv::<String>().await {
+				| Ok(key) => on_solved.call(key),
+				| Err(e) => platform::error(&format!("Captcha failed: {:?}", e)),
+			}
+		});
+	};
+
+	rsx! {
+		div {
+			class: "modal-overlay",
+			onclick: move |_| on_cancel.call(()),
+			div {
+				class: "modal-content captcha",
+				onclick: move |event| event.stop_propagation(),
+				h2 { "Are you human?" }
+				div { id: CONTAINER_ID, onmounted: render }
+				div {
+					class: "modal-actions",
+					button { onclick: move |_| on_cancel.call(()), "Cancel" }
+				}
+			}
+		}
+	}
+}
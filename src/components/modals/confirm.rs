@@ -0,0 +1,32 @@
+use dioxus::prelude::*;
+
+#[component]
+pub fn ConfirmModal(
+	title: String,
+	message: String,
+	confirm_label: String,
+	on_confirm: EventHandler<()>,
+	on_cancel: EventHandler<()>,
+) -> Element {
+	rsx! {
+		div {
+			class: "modal-overlay",
+			onclick: move |_| on_cancel.call(()),
+			div {
+				class: "modal-content",
+				onclick: move |event| event.stop_propagation(),
+				h2 { "{title}" }
+				p { "{message}" }
+				div {
+					class: "modal-actions",
+					button { onclick: move |_| on_cancel.call(()), "Cancel" }
+					button {
+						class: "danger",
+						onclick: move |_| on_confirm.call(()),
+						"{confirm_label}"
+					}
+				}
+			}
+		}
+	}
+}
@@ -1,32 +1,189 @@
+use std::error::Error;
+
 use dioxus::prelude::*;
 
+use crate::models::mfa::{MfaMethod, MfaMethods, MfaSmsRequest};
+use crate::utils::platform;
+use crate::utils::request::RequestClient;
+use crate::utils::sudo::{self, MFA_PROMPT};
+
+// runs the webauthn ceremony for discord's credential request options and sends back the json assertion
+const WEBAUTHN_GET: &str = r#"
+	const options = JSON.parse(await dioxus.recv()).publicKey;
+	const decode = (s) => Uint8Array.from(atob(s.replace(/-/g, "+").replace(/_/g, "/")), (c) => c.charCodeAt(0));
+	const encode = (b) => btoa(String.fromCharCode(...new Uint8Array(b))).replace(/\+/g, "-").replace(/\//g, "_").replace(/=+$/, "");
+	options.challenge = decode(options.challenge);
+	for (const credential of options.allowCredentials ?? []) {
+		credential.id = decode(credential.id);
+	}
+	const credential = await navigator.credentials.get({ publicKey: options });
+	dioxus.send(JSON.stringify({
+		id: credential.id,
+		rawId: encode(credential.rawId),
+		type: credential.type,
+		response: {
+			authenticatorData: encode(credential.response.authenticatorData),
+			clientDataJSON: encode(credential.response.clientDataJSON),
+			signature: encode(credential.response.signature),
+			userHandle: credential.response.userHandle ? encode(credential.response.userHandle) : null,
+		},
+	}));
+"#;
+
+async fn send_sudo_sms(ticket: String) -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.post::<_, serde_json::Value>("/mfa/sms/send", &MfaSmsRequest { ticket })
+		.await?;
+	Ok(())
+}
+
+fn placeholder(method: MfaMethods) -> &'static str {
+	match method {
+		| MfaMethods::totp => "6-digit authentication code",
+		| MfaMethods::sms => "6-digit code sent to your phone",
+		| MfaMethods::backup => "8-digit backup code",
+		| MfaMethods::password => "Password",
+		| MfaMethods::webauthn => "",
+	}
+}
+
+/// asks for whichever of `methods` the user picks, used for both login and sudo mode.
+/// `on_submit` gets the method and its code, password or webauthn assertion
 #[component]
-pub fn Modal(on_submit: EventHandler<String>) -> Element {
-	let mut input_value = use_signal(|| "".to_string());
+pub fn MfaModal(
+	methods: Vec<MfaMethod>,
+	#[props(default)] error: Option<String>,
+	on_submit: EventHandler<(MfaMethods, String)>,
+	on_send_sms: EventHandler<()>,
+	on_cancel: EventHandler<()>,
+) -> Element {
+	let mut selected = use_signal(|| methods.first().map(|m| m.r#type));
+	let mut code = use_signal(String::new);
+	let mut sms_sent = use_signal(|| false);
+
+	let Some(method) = selected() else {
+		return rsx! {};
+	};
+	let challenge = methods
+		.iter()
+		.find(|m| m.r#type == MfaMethods::webauthn)
+		.and_then(|m| m.challenge.clone());
+	// backup codes aren't their own method in sudo mode, totp just allows them
+	let mut types: Vec<MfaMethods> = methods.iter().map(|m| m.r#type).collect();
+	if methods.iter().any(|m| m.backup_codes_allowed == Some(true)) && !types.contains(&MfaMethods::backup) {
+		types.push(MfaMethods::backup);
+	}
+	let others: Vec<MfaMethods> = types.into_iter().filter(|&m| m != method).collect();
+
+	let use_security_key = move |_| {
+		let Some(challenge) = challenge.clone() else {
+			return;
+		};
+
+		spawn(async move {
+			let mut eval = document::eval(WEBAUTHN_GET);
+			let _ = eval.send(challenge);
+
+			match eval.recv::<String>().await {
+				| Ok(assertion) => on_submit.call((MfaMethods::webauthn, assertion)),
+				| Err(e) => platform::error(&format!("Security key failed: {:?}", e)),
+			}
+		});
+	};
 
 	rsx! {
 		div {
 			class: "modal-overlay",
 			div {
-				class: "modal-content",
-				form {
-					onsubmit: move |event: Event<FormData>| {
-						let value = event.values().get("mfa_code")
-							.and_then(|val| val.get(0).cloned())
-							.unwrap_or_default();
-
-						on_submit.call(value);
-					},
-					input {
-						r#type: "text",
-						name: "mfa_code",
-						value: "{input_value}",
-						oninput: move |event| input_value.set(event.value()),
-						placeholder: "Enter MFA Code"
-					},
-					button { r#type: "submit", "Submit" }
+				class: "modal-content mfa",
+				h2 { "Multi-Factor Authentication" }
+				if let Some(error) = error {
+					p { class: "error", "{error}" }
+				}
+
+				if method == MfaMethods::webauthn {
+					button { onclick: use_security_key, "Use Security Key" }
+				} else {
+					form {
+						onsubmit: move |event: Event<FormData>| {
+							event.prevent_default();
+							on_submit.call((method, code().trim().to_string()));
+						},
+						if method == MfaMethods::sms {
+							button {
+								r#type: "button",
+								onclick: move |_| {
+									sms_sent.set(true);
+									on_send_sms.call(());
+								},
+								if sms_sent() { "Resend Code" } else { "Send Code" }
+							}
+						}
+						input {
+							r#type: if method == MfaMethods::password { "password" } else { "text" },
+							autocomplete: if method == MfaMethods::password { "current-password" } else { "one-time-code" },
+							value: "{code}",
+							oninput: move |event| code.set(event.value()),
+							placeholder: placeholder(method),
+						}
+						button { r#type: "submit", disabled: code().trim().is_empty(), "Confirm" }
+					}
+				}
+
+				if !others.is_empty() {
+					div {
+						class: "mfa-methods",
+						span { "Use another method:" }
+						for other in others {
+							button {
+								class: "link",
+								onclick: move |_| {
+									selected.set(Some(other));
+									code.set(String::new());
+								},
+								"{other.label()}"
+							}
+						}
+					}
+				}
+
+				div {
+					class: "modal-actions",
+					button { onclick: move |_| on_cancel.call(()), "Cancel" }
 				}
 			}
 		}
 	}
 }
+
+/// rendered once at the root, answers whatever request is waiting on sudo mode
+#[component]
+pub fn SudoMfaPrompt() -> Element {
+	let Some((ticket, methods, error)) = MFA_PROMPT
+		.read()
+		.as_ref()
+		.map(|p| (p.ticket.clone(), p.methods.clone(), p.error.clone()))
+	else {
+		return rsx! {};
+	};
+
+	rsx! {
+		MfaModal {
+			key: "{ticket}",
+			methods,
+			error,
+			on_send_sms: move |_| {
+				let ticket = ticket.clone();
+				spawn(async move {
+					if let Err(e) = send_sudo_sms(ticket).await {
+						platform::error(&format!("Error sending sms MFA code: {}", e));
+					}
+				});
+			},
+			on_submit: move |response| sudo::answer(Some(response)),
+			on_cancel: move |_| sudo::answer(None),
+		}
+	}
+}
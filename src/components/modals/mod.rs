@@ -1 +1,5 @@
+pub mod captcha;
+pub mod confirm;
 pub mod mfa_code;
+pub mod recipient_picker;
+pub mod session_expired;
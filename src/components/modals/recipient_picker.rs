@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+use dioxus::prelude::*;
+
+use crate::models::types::Snowflake;
+use crate::models::user::user::PartialUser;
+use crate::models::relationship::FRIEND;
+use crate::store::{PRIVATE_CHANNELS, RELATIONSHIPS, USERS};
+use crate::utils::cdn;
+
+/// friends first, then everyone else we share a dm or group dm with
+fn candidates(exclude: &[Snowflake]) -> Vec<PartialUser> {
+	let users = USERS.read();
+	let current = users.current().map(|u| u.id);
+	let mut seen = HashSet::new();
+	let friends: Vec<Snowflake> = RELATIONSHIPS.read().of_type(FRIEND).iter().map(|r| r.id).collect();
+
+	friends
+		.into_iter()
+		.chain(
+			PRIVATE_CHANNELS
+				.read()
+				.sorted()
+				.into_iter()
+				.flat_map(|c| c.recipient_ids.clone()),
+		)
+		.filter(|id| Some(*id) != current && !exclude.contains(id) && seen.insert(*id))
+		.filter_map(|id| users.user(id).cloned())
+		.filter(|u| !u.bot)
+		.collect()
+}
+
+/// picks users for a new group dm or to add to one, `max` is how many more the group has room for
+#[component]
+pub fn RecipientPicker(
+	title: String,
+	confirm_label: String,
+	#[props(default)] exclude: Vec<Snowflake>,
+	max: usize,
+	on_confirm: EventHandler<Vec<Snowflake>>,
+	on_cancel: EventHandler<()>,
+) -> Element {
+	let mut query = use_signal(String::new);
+	let mut selected = use_signal(Vec::<Snowflake>::new);
+
+	let search = query().to_lowercase();
+	let users: Vec<PartialUser> = candidates(&exclude)
+		.into_iter()
+		.filter(|u| {
+			u.username.to_lowercase().contains(&search) || u.display_name().to_lowercase().contains(&search)
+		})
+		.collect();
+	let remaining = max.saturating_sub(selected.read().len());
+
+	rsx! {
+		div {
+			class: "modal-overlay",
+			onclick: move |_| on_cancel.call(()),
+			div {
+				class: "modal-content recipient-picker",
+				onclick: move |event| event.stop_propagation(),
+				h2 { "{title}" }
+				p { "You can add {remaining} more." }
+				input {
+					r#type: "text",
+					placeholder: "Type the username of a friend",
+					value: "{query}",
+					oninput: move |event| query.set(event.value()),
+				}
+
+				div {
+					class: "recipient-picker-list",
+					for user in users {
+						label {
+							key: "{user.id}",
+							class: "recipient-picker-item",
+							img { src: cdn::avatar(&user, 32), alt: "" }
+							span { "{user.display_name()}" }
+							span { class: "recipient-picker-username", "{user.username}" }
+							input {
+								r#type: "checkbox",
+								checked: selected.read().contains(&user.id),
+								disabled: remaining == 0 && !selected.read().contains(&user.id),
+								onchange: move |_| {
+									let mut selected = selected.write();
+									match selected.iter().position(|&id| id == user.id) {
+										| Some(index) => {
+											selected.remove(index);
+										},
+										| None => selected.push(user.id),
+									}
+								},
+							}
+						}
+					}
+				}
+
+				div {
+					class: "modal-actions",
+					button { onclick: move |_| on_cancel.call(()), "Cancel" }
+					button {
+						disabled: selected.read().is_empty(),
+						onclick: move |_| on_confirm.call(selected()),
+						"{confirm_label}"
+					}
+				}
+			}
+		}
+	}
+}
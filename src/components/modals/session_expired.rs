@@ -0,0 +1,36 @@
+use dioxus::prelude::*;
+
+use crate::Route;
+use crate::utils::token::SESSION_EXPIRED;
+use crate::views::end_session;
+
+/// shown when a request gets a 401, the token got revoked or expired somewhere else
+#[component]
+pub fn SessionExpired() -> Element {
+	if !SESSION_EXPIRED() {
+		return rsx! {};
+	}
+
+	rsx! {
+		div {
+			class: "modal-overlay",
+			div {
+				class: "modal-content",
+				h2 { "Session Expired" }
+				p { "You've been logged out. Log in again to keep going." }
+				div {
+					class: "modal-actions",
+					button {
+						onclick: move |_| {
+							spawn(async move {
+								end_session().await;
+								navigator().replace(Route::Login {});
+							});
+						},
+						"Log In"
+					}
+				}
+			}
+		}
+	}
+}
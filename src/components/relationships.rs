@@ -0,0 +1,102 @@
+use std::error::Error;
+
+use dioxus::prelude::*;
+
+use crate::models::relationship::{
+	BLOCKED,
+	CreateRelationshipRequest,
+	FriendRequest,
+	ModifyRelationshipRequest,
+};
+use crate::models::types::Snowflake;
+use crate::utils::platform;
+use crate::utils::request::RequestClient;
+
+pub async fn send_friend_request(username: String) -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.post("/users/@me/relationships", &FriendRequest {
+			username,
+			discriminator: None,
+		})
+		.await
+}
+
+async fn put_relationship(
+	user_id: Snowflake,
+	r#type: Option<u8>,
+) -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.put(
+			&format!("/users/@me/relationships/{}", user_id),
+			&CreateRelationshipRequest { r#type },
+		)
+		.await
+}
+
+// unfriends, unblocks, declines or cancels depending on what the relationship is
+async fn delete_relationship(user_id: Snowflake) -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.delete(&format!("/users/@me/relationships/{}", user_id))
+		.await
+}
+
+async fn modify_relationship(
+	user_id: Snowflake,
+	nickname: Option<String>,
+) -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.patch(
+			&format!("/users/@me/relationships/{}", user_id),
+			&ModifyRelationshipRequest { nickname },
+		)
+		.await
+}
+
+// the store gets updated by the RELATIONSHIP_* events these cause, so these only report failures
+
+/// sends a friend request, or accepts one if they already sent us one
+pub fn add_friend(user_id: Snowflake) {
+	spawn(async move {
+		if let Err(e) = put_relationship(user_id, None).await {
+			platform::error(&format!("Failed to add friend: {}", e));
+		}
+	});
+}
+
+pub fn block(user_id: Snowflake) {
+	spawn(async move {
+		if let Err(e) = put_relationship(user_id, Some(BLOCKED)).await {
+			platform::error(&format!("Failed to block user: {}", e));
+		}
+	});
+}
+
+pub fn remove_relationship(user_id: Snowflake) {
+	spawn(async move {
+		if let Err(e) = delete_relationship(user_id).await {
+			platform::error(&format!("Failed to remove relationship: {}", e));
+		}
+	});
+}
+
+/// an empty nickname clears it
+pub fn set_nickname(
+	user_id: Snowflake,
+	nickname: String,
+) {
+	let nickname = Some(nickname.trim().to_string()).filter(|n| !n.is_empty());
+
+	spawn(async move {
+		if let Err(e) = modify_relationship(user_id, nickname).await {
+			platform::error(&format!("Failed to set friend nickname: {}", e));
+		}
+	});
+}
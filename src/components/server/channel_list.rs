@@ -0,0 +1,381 @@
+use std::collections::HashSet;
+use std::error::Error;
+
+use dioxus::prelude::*;
+
+use crate::Route;
+use crate::models::chat::Channel;
+use crate::models::read_state::{BulkAckEntry, BulkAckRequest};
+use crate::models::types::Snowflake;
+use crate::store::{GUILD_MEMBERS, GUILDS, READ_STATES, SETTINGS, USERS};
+use crate::utils::cdn;
+use crate::utils::permissions::{VIEW_CHANNEL, channel_permissions};
+use crate::utils::platform;
+use crate::utils::request::RequestClient;
+use crate::utils::storage::{self, Storage};
+
+// https://docs.discord.sex/resources/channel#channel-type
+const GUILD_CATEGORY: u8 = 4;
+const VOICE_CHANNELS: [u8; 2] = [2, 13];
+
+async fn ack_bulk(read_states: Vec<BulkAckEntry>) -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.post("/read-states/ack-bulk", &BulkAckRequest { read_states })
+		.await
+}
+
+/// acks every unread channel and thread in the guild up to its latest message
+fn mark_guild_read(guild_id: Snowflake) {
+	let entries: Vec<BulkAckEntry> = {
+		let guilds = GUILDS.peek();
+		let read_states = READ_STATES.peek();
+		let Some(guild) = guilds.guild(guild_id) else {
+			return;
+		};
+
+		guild
+			.channels
+			.iter()
+			.chain(&guild.threads)
+			.filter(|c| read_states.is_unread(c) || read_states.mention_count(c.id) > 0)
+			.filter_map(|c| {
+				Some(BulkAckEntry {
+					channel_id:      c.id,
+					message_id:      c.last_message_id?,
+					read_state_type: 0,
+				})
+			})
+			.collect()
+	};
+	if entries.is_empty() {
+		return;
+	}
+
+	let mut read_states = READ_STATES.write();
+	for entry in &entries {
+		read_states.ack(entry.channel_id, entry.message_id, 0);
+	}
+
+	spawn(async move {
+		if let Err(e) = ack_bulk(entries).await {
+			platform::error(&format!("Failed to mark guild as read: {}", e));
+		}
+	});
+}
+
+/// a category, or none for the channels above every category, and the channels in it
+type Group<'a> = (Option<&'a Channel>, Vec<&'a Channel>);
+
+/// uncategorized channels first then every category, text channels go above voice ones like on discord.
+/// categories where everything is hidden get dropped, empty ones we can see stay
+fn group_channels<'a>(
+	channels: &'a [Channel],
+	can_view: impl Fn(&Channel) -> bool,
+) -> Vec<Group<'a>> {
+	let sort = |channels: &mut Vec<&Channel>| {
+		channels.sort_by_key(|c| (VOICE_CHANNELS.contains(&c.r#type), c.position, c.id));
+	};
+
+	let mut categories: Vec<&Channel> = channels.iter().filter(|c| c.r#type == GUILD_CATEGORY).collect();
+	categories.sort_by_key(|c| (c.position, c.id));
+
+	let mut uncategorized: Vec<&Channel> = channels
+		.iter()
+		.filter(|c| c.r#type != GUILD_CATEGORY && can_view(c))
+		.filter(|c| c.parent_id.is_none_or(|id| !categories.iter().any(|cat| cat.id == id)))
+		.collect();
+	sort(&mut uncategorized);
+
+	let mut groups = vec![(None, uncategorized)];
+	for category in categories {
+		let children: Vec<&Channel> = channels.iter().filter(|c| c.parent_id == Some(category.id)).collect();
+		let mut visible: Vec<&Channel> = children.iter().copied().filter(|c| can_view(c)).collect();
+		sort(&mut visible);
+
+		if !visible.is_empty() || (children.is_empty() && can_view(category)) {
+			groups.push((Some(category), visible));
+		}
+	}
+
+	groups
+}
+
+fn collapsed_key(guild_id: Snowflake) -> String { format!("collapsed_categories_{}", guild_id) }
+
+fn load_collapsed(guild_id: Snowflake) -> HashSet<Snowflake> {
+	storage::account().get(&collapsed_key(guild_id)).unwrap_or_default()
+}
+
+fn channel_icon(r#type: u8) -> &'static str {
+	match r#type {
+		| 2 => "🔊",
+		| 5 => "📢",
+		| 13 => "🎙",
+		| 15 => "💬",
+		| 16 => "🖼",
+		| _ => "#",
+	}
+}
+
+#[component]
+pub fn ChannelList(guild_id: Snowflake) -> Element {
+	let route = use_route::<Route>();
+	let selected: Option<Snowflake> = match route {
+		| Route::Channel { channel_id, .. } | Route::JumpToMessage { channel_id, .. } => channel_id.parse().ok(),
+		| _ => None,
+	};
+	let mut collapsed = use_signal(move || load_collapsed(guild_id));
+
+	let guilds = GUILDS.read();
+	let Some(guild) = guilds.guild(guild_id) else {
+		return rsx! {};
+	};
+
+	// without our member (no merged_members yet) there's nothing to check against, so show everything
+	let me = USERS.read().current().map(|u| u.id);
+	let roles = me.and_then(|id| GUILD_MEMBERS.read().member(guild_id, id).map(|m| m.roles.clone()));
+	let can_view = |channel: &Channel| match (me, &roles) {
+		| (Some(me), Some(roles)) => channel_permissions(guild, channel, me, roles) & VIEW_CHANNEL != 0,
+		| _ => true,
+	};
+
+	let read_states = READ_STATES.read();
+	let settings = SETTINGS.read();
+	let is_unread = |channel: &Channel| read_states.is_unread(channel) && !settings.is_channel_muted(guild_id, channel);
+
+	// joined threads, plus whichever one is open
+	let threads = |parent_id: Snowflake| -> Vec<Channel> {
+		guild
+			.threads
+			.iter()
+			.filter(|t| t.parent_id == Some(parent_id))
+			.filter(|t| !t.thread_metadata.as_ref().is_some_and(|m| m.archived))
+			.filter(|t| t.member.is_some() || Some(t.id) == selected)
+			.cloned()
+			.collect()
+	};
+
+	let mut toggle = move |category_id: Snowflake| {
+		let mut collapsed = collapsed.write();
+		if !collapsed.remove(&category_id) {
+			collapsed.insert(category_id);
+		}
+		if let Err(e) = storage::account().set(&collapsed_key(guild_id), &*collapsed) {
+			platform::error(&format!("Failed to save collapsed categories: {}", e));
+		}
+	};
+
+	let groups: Vec<_> = group_channels(&guild.channels, can_view)
+		.into_iter()
+		.map(|(category, channels)| {
+			let is_collapsed = category.is_some_and(|c| collapsed.read().contains(&c.id));
+			let channels: Vec<(Channel, Vec<Channel>)> = channels
+				.into_iter()
+				// collapsed categories still show what's open or unread
+				.filter(|c| !is_collapsed || Some(c.id) == selected || is_unread(c))
+				.map(|c| (c.clone(), threads(c.id)))
+				.collect();
+
+			(category.cloned(), is_collapsed, channels)
+		})
+		.collect();
+
+	rsx! {
+		nav {
+			class: "channel-list",
+			div {
+				class: "channel-list-header",
+				h2 { "{guild.name()}" }
+				button {
+					title: "Mark As Read",
+					onclick: move |_| mark_guild_read(guild_id),
+					"Mark As Read"
+				}
+			}
+
+			for (category, is_collapsed, channels) in groups {
+				div {
+					key: "{category.as_ref().map(|c| c.id.raw()).unwrap_or_default()}",
+					class: "channel-category",
+
+					if let Some(category) = category {
+						div {
+							class: if is_collapsed { "channel-category-header collapsed" } else { "channel-category-header" },
+							onclick: move |_| toggle(category.id),
+							span { class: "channel-category-arrow", if is_collapsed { "▸" } else { "▾" } }
+							span { "{category.name.clone().unwrap_or_default()}" }
+						}
+					}
+
+					for (channel, threads) in channels {
+						div {
+							key: "{channel.id}",
+							class: "channel-entry",
+
+							ChannelItem {
+								guild_id,
+								selected: selected == Some(channel.id),
+								channel: channel.clone(),
+							}
+							for thread in threads {
+								ChannelItem {
+									key: "{thread.id}",
+									guild_id,
+									selected: selected == Some(thread.id),
+									channel: thread,
+								}
+							}
+							if VOICE_CHANNELS.contains(&channel.r#type) {
+								VoiceOccupants { guild_id, channel_id: channel.id }
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn ChannelItem(
+	guild_id: Snowflake,
+	channel: Channel,
+	selected: bool,
+) -> Element {
+	let muted = SETTINGS.read().is_channel_muted(guild_id, &channel);
+	let unread = !muted && READ_STATES.read().is_unread(&channel);
+	let mentions = READ_STATES.read().mention_count(channel.id);
+	let is_thread = channel.thread_metadata.is_some();
+
+	let mut class = String::from(if is_thread { "channel-item thread" } else { "channel-item" });
+	for (flag, name) in [(selected, " selected"), (unread, " unread"), (muted, " muted")] {
+		if flag {
+			class.push_str(name);
+		}
+	}
+
+	rsx! {
+		Link {
+			class,
+			to: Route::Channel {
+				server_id:  guild_id.to_string(),
+				channel_id: channel.id.to_string(),
+			},
+			if !is_thread {
+				span { class: "channel-icon", {channel_icon(channel.r#type)} }
+			}
+			span { class: "channel-name", "{channel.name.clone().unwrap_or_default()}" }
+			if mentions > 0 {
+				span { class: "mention-badge", "{mentions}" }
+			}
+		}
+	}
+}
+
+#[component]
+fn VoiceOccupants(
+	guild_id: Snowflake,
+	channel_id: Snowflake,
+) -> Element {
+	let guilds = GUILDS.read();
+	let users = USERS.read();
+	let members = GUILD_MEMBERS.read();
+
+	let occupants: Vec<_> = guilds
+		.guild(guild_id)
+		.map(|g| g.voice_states.as_slice())
+		.unwrap_or_default()
+		.iter()
+		.filter(|s| s.channel_id == Some(channel_id))
+		.filter_map(|state| {
+			let member = state.member.as_ref().or(members.member(guild_id, state.user_id));
+			let user = users.user(state.user_id).or(member.and_then(|m| m.user.as_ref()))?;
+			let name = member.and_then(|m| m.nick.clone()).unwrap_or(user.display_name().to_string());
+
+			Some((state.clone(), cdn::avatar(user, 32), name))
+		})
+		.collect();
+
+	rsx! {
+		for (state, avatar, name) in occupants {
+			div {
+				key: "{state.user_id}",
+				class: "voice-occupant",
+				img { src: avatar, alt: "" }
+				span { "{name}" }
+				if state.self_stream {
+					span { class: "voice-live", "LIVE" }
+				}
+				if state.mute || state.self_mute {
+					span { class: "voice-icon", title: "Muted", "🔇" }
+				}
+				if state.deaf || state.self_deaf {
+					span { class: "voice-icon", title: "Deafened", "🎧" }
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	fn channel(
+		id: u64,
+		r#type: u8,
+		parent_id: Option<u64>,
+		position: i32,
+	) -> Channel {
+		serde_json::from_value(json!({
+			"id": id.to_string(),
+			"type": r#type,
+			"parent_id": parent_id.map(|p| p.to_string()),
+			"position": position,
+		}))
+		.unwrap()
+	}
+
+	fn ids(groups: &[Group]) -> Vec<(Option<u64>, Vec<u64>)> {
+		groups
+			.iter()
+			.map(|(category, channels)| (category.map(|c| c.id.raw()), channels.iter().map(|c| c.id.raw()).collect()))
+			.collect()
+	}
+
+	#[test]
+	fn groups_by_category_with_voice_last() {
+		let channels = vec![
+			channel(1, GUILD_CATEGORY, None, 1),
+			channel(2, GUILD_CATEGORY, None, 0),
+			channel(3, 2, Some(1), 0),
+			channel(4, 0, Some(1), 5),
+			channel(5, 0, Some(2), 0),
+			channel(6, 0, None, 3),
+		];
+
+		assert_eq!(ids(&group_channels(&channels, |_| true)), vec![
+			(None, vec![6]),
+			(Some(2), vec![5]),
+			(Some(1), vec![4, 3]),
+		]);
+	}
+
+	#[test]
+	fn hidden_channels_take_their_category_with_them() {
+		let channels = vec![
+			channel(1, GUILD_CATEGORY, None, 0),
+			channel(2, GUILD_CATEGORY, None, 1),
+			channel(3, 0, Some(1), 0),
+		];
+
+		assert_eq!(ids(&group_channels(&channels, |c| c.id.raw() != 3)), vec![
+			(None, vec![]),
+			(Some(2), vec![]),
+		]);
+	}
+}
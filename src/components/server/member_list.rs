@@ -0,0 +1,152 @@
+use dioxus::prelude::*;
+use serde_json::json;
+
+use crate::components::users::user_profile_popup::ProfileTrigger;
+use crate::components::virtual_list::VirtualList;
+use crate::models::guild::{MemberListItem, MemberListMember};
+use crate::models::types::Snowflake;
+use crate::store::{GUILDS, MEMBER_LISTS};
+use crate::utils::{cdn, gateway};
+
+// the gateway hands out the member list in chunks of this many rows
+const CHUNK_SIZE: usize = 100;
+
+/// ranges to subscribe to for the rows being rendered, the first chunk always has to be included
+fn chunk_ranges(
+	start: usize,
+	end: usize,
+) -> Vec<[usize; 2]> {
+	let mut ranges = vec![[0, CHUNK_SIZE - 1]];
+
+	for chunk in start / CHUNK_SIZE..=end.saturating_sub(1) / CHUNK_SIZE {
+		let range = [chunk * CHUNK_SIZE, (chunk + 1) * CHUNK_SIZE - 1];
+		if !ranges.contains(&range) {
+			ranges.push(range);
+		}
+	}
+
+	ranges
+}
+
+// https://docs.discord.sex/topics/gateway-events#lazy-request
+fn request_ranges(
+	guild_id: Snowflake,
+	channel_id: Snowflake,
+	ranges: Vec<[usize; 2]>,
+) {
+	gateway::send(
+		14,
+		json!({
+			"guild_id": guild_id,
+			"typing": true,
+			"activities": true,
+			"threads": true,
+			"channels": { channel_id.to_string(): ranges },
+		}),
+	);
+}
+
+#[component]
+fn MemberRow(member: MemberListMember) -> Element {
+	let Some(user) = &member.member.user else {
+		return rsx! {};
+	};
+
+	let name = member.member.nick.as_deref().unwrap_or(user.display_name());
+	let status = member
+		.presence
+		.as_ref()
+		.map(|p| p.status)
+		.unwrap_or_default();
+
+	rsx! {
+		ProfileTrigger {
+			user_id: user.id,
+			class: "member",
+			div {
+				class: "member-avatar",
+				img { src: cdn::avatar(user, 64), alt: "" }
+				span { class: "status {status.as_str()}" }
+			}
+			span { class: "member-name", "{name}" }
+		}
+	}
+}
+
+#[component]
+pub fn MemberList(
+	guild_id: Snowflake,
+	channel_id: Snowflake,
+) -> Element {
+	use_hook(move || request_ranges(guild_id, channel_id, chunk_ranges(0, 0)));
+
+	let render_item = use_callback(move |index: usize| {
+		let lists = MEMBER_LISTS.read();
+		let item = lists.list(guild_id).and_then(|l| l.items.get(index)).cloned().flatten();
+
+		match item {
+			| Some(MemberListItem::Group(group)) => {
+				let name = match group.id.as_str() {
+					| "online" => "Online".to_string(),
+					| "offline" => "Offline".to_string(),
+					| id => id
+						.parse()
+						.ok()
+						.and_then(|role_id| GUILDS.read().role(guild_id, role_id).map(|r| r.name.clone()))
+						.unwrap_or_default(),
+				};
+
+				rsx! {
+					h3 { class: "member-group", "{name} — {group.count}" }
+				}
+			},
+			| Some(MemberListItem::Member(member)) => rsx! {
+				MemberRow { member: *member }
+			},
+			| None => rsx! {
+				div { class: "member placeholder" }
+			},
+		}
+	});
+
+	let lists = MEMBER_LISTS.read();
+	let keys = lists
+		.list(guild_id)
+		.map(|list| {
+			list.items
+				.iter()
+				.enumerate()
+				.map(|(index, item)| match item {
+					| Some(MemberListItem::Group(group)) => format!("group-{}", group.id),
+					| Some(MemberListItem::Member(member)) => match &member.member.user {
+						| Some(user) => format!("member-{}", user.id),
+						| None => format!("placeholder-{}", index),
+					},
+					| None => format!("placeholder-{}", index),
+				})
+				.collect::<Vec<_>>()
+		})
+		.unwrap_or_default();
+
+	rsx! {
+		VirtualList {
+			class: "member-list",
+			keys,
+			render_item,
+			estimated_height: 44.0,
+			on_range_change: move |(start, end)| request_ranges(guild_id, channel_id, chunk_ranges(start, end)),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ranges_always_include_first_chunk() {
+		assert_eq!(chunk_ranges(0, 20), vec![[0, 99]]);
+		assert_eq!(chunk_ranges(150, 180), vec![[0, 99], [100, 199]]);
+		assert_eq!(chunk_ranges(190, 210), vec![[0, 99], [100, 199], [200, 299]]);
+	}
+}
@@ -0,0 +1,2 @@
+pub mod channel_list;
+pub mod member_list;
@@ -1,8 +1,338 @@
+use std::collections::HashSet;
+use std::error::Error;
+
 use dioxus::prelude::*;
+use serde_json::json;
+
+use crate::Route;
+use crate::components::modals::session_expired::SessionExpired;
+use crate::components::status::Status;
+use crate::components::users::user_profile::UserProfileModal;
+use crate::components::users::user_profile_popup::UserProfilePopup;
+use crate::models::guild::Guild;
+use crate::models::types::Snowflake;
+use crate::models::user::settings::{GuildFolder, GuildFolders, PartialUserSettingsProto, UpdateSettingsProtoRequest};
+use crate::store::settings::{FolderDrop, layout, move_guild};
+use crate::store::{GUILDS, PRIVATE_CHANNELS, READ_STATES, SETTINGS};
+use crate::utils::cdn;
+use crate::utils::gateway::use_gateway;
+use crate::utils::platform;
+use crate::utils::request::RequestClient;
+
+async fn save_guild_folders(
+	folders: Vec<GuildFolder>,
+	from_proto: bool,
+) -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	if from_proto {
+		let settings = PartialUserSettingsProto {
+			guild_folders: Some(GuildFolders {
+				folders:         folders.into_iter().map(Into::into).collect(),
+				guild_positions: Vec::new(),
+			}),
+		};
+		let request = UpdateSettingsProtoRequest {
+			settings: settings.to_base64(),
+		};
+
+		client
+			.patch::<_, serde_json::Value>("/users/@me/settings-proto/1", &request)
+			.await?;
+	} else {
+		client
+			.patch::<_, serde_json::Value>("/users/@me/settings", &json!({ "guild_folders": folders }))
+			.await?;
+	}
+
+	Ok(())
+}
+
+pub(crate) fn current_guild(route: &Route) -> Option<Snowflake> {
+	match route {
+		| Route::Roles { server_id } | Route::Channel { server_id, .. } | Route::JumpToMessage { server_id, .. } => {
+			server_id.parse().ok()
+		},
+		| _ => None,
+	}
+}
+
+/// whether anything in the guild is unread and how many unread mentions there are
+fn guild_badges(guild: &Guild) -> (bool, u32) {
+	let read_states = READ_STATES.read();
+	let channels = guild.channels.iter().chain(&guild.threads);
+
+	channels.fold((false, 0), |(unread, mentions), channel| {
+		(
+			unread || read_states.is_unread(channel),
+			mentions + read_states.mention_count(channel.id),
+		)
+	})
+}
+
+/// first letter of every word, what discord shows for guilds without an icon
+fn acronym(name: &str) -> String { name.split_whitespace().filter_map(|w| w.chars().next()).collect() }
 
 #[component]
 pub fn ServerList() -> Element {
+	use_gateway();
+
+	let route = use_route::<Route>();
+	let selected = current_guild(&route);
+	let dragging = use_signal(|| None::<Snowflake>);
+
+	let on_drop = use_callback(move |target: FolderDrop| {
+		let Some(guild_id) = dragging.peek().to_owned() else {
+			return;
+		};
+
+		let guild_ids: Vec<Snowflake> = GUILDS.peek().guilds().iter().map(|g| g.id).collect();
+		let mut folders = layout(&SETTINGS.peek().guild_folders, &guild_ids);
+		if !move_guild(&mut folders, guild_id, target) {
+			return;
+		}
+
+		let from_proto = SETTINGS.peek().from_proto;
+		SETTINGS.write().guild_folders = folders.clone();
+
+		spawn(async move {
+			if let Err(e) = save_guild_folders(folders, from_proto).await {
+				platform::error(&format!("Failed to save guild folders: {}", e));
+			}
+		});
+	});
+
+	let guild_ids: Vec<Snowflake> = GUILDS.read().guilds().iter().map(|g| g.id).collect();
+	let folders = layout(&SETTINGS.read().guild_folders, &guild_ids);
+	let in_dms = matches!(route, Route::Friends {} | Route::UserChat { .. });
+	let dm_mentions: u32 = PRIVATE_CHANNELS
+		.read()
+		.sorted()
+		.iter()
+		.map(|c| READ_STATES.read().mention_count(c.id))
+		.sum();
+
+	rsx! {
+		div {
+			class: "app",
+			nav {
+				class: "server-list",
+
+				div {
+					class: "guild-list-item",
+					div {
+						class: if in_dms { "guild-icon home selected" } else { "guild-icon home" },
+						title: "Direct Messages",
+						onclick: move |_| {
+							navigator().push(Route::Friends {});
+						},
+						"DMs"
+					}
+					Badges { unread: false, mentions: dm_mentions }
+				}
+				div { class: "server-list-separator" }
+
+				for folder in folders {
+					if folder.is_folder() {
+						FolderItem {
+							key: "folder-{folder.id.unwrap_or_default()}",
+							folder,
+							selected,
+							dragging,
+							on_drop,
+						}
+					} else {
+						GuildItem {
+							key: "{folder.guild_ids[0]}",
+							guild_id: folder.guild_ids[0],
+							selected: selected == Some(folder.guild_ids[0]),
+							dragging,
+							on_drop,
+						}
+					}
+				}
+
+				div {
+					class: "server-list-end",
+					ondragover: move |event| event.prevent_default(),
+					ondrop: move |event| {
+						event.prevent_default();
+						on_drop.call(FolderDrop::End);
+					},
+				}
+			}
+
+			div {
+				class: "server-content",
+				Outlet::<Route> {}
+			}
+
+			Status {}
+			UserProfilePopup {}
+			UserProfileModal {}
+			SessionExpired {}
+		}
+	}
+}
+
+#[component]
+fn FolderItem(
+	folder: GuildFolder,
+	selected: Option<Snowflake>,
+	dragging: Signal<Option<Snowflake>>,
+	on_drop: Callback<FolderDrop>,
+) -> Element {
+	// which folders are open only lives for the session, like on discord
+	let mut expanded = use_signal(HashSet::<i64>::new);
+
+	let folder_id = folder.id.unwrap_or_default();
+	let is_expanded = expanded.read().contains(&folder_id);
+	let first = folder.guild_ids[0];
+	let color = folder.color.map_or("inherit".to_string(), |c| format!("#{:06x}", c));
+
+	let guilds = GUILDS.read();
+	let (unread, mentions) = folder
+		.guild_ids
+		.iter()
+		.filter_map(|&id| guilds.guild(id))
+		.map(guild_badges)
+		.fold((false, 0), |(u, m), (unread, mentions)| (u || unread, m + mentions));
+	let previews: Vec<(Snowflake, Option<String>, String)> = folder
+		.guild_ids
+		.iter()
+		.take(4)
+		.filter_map(|&id| guilds.guild(id))
+		.map(|g| (g.id, g.icon().map(str::to_string), acronym(g.name())))
+		.collect();
+
+	rsx! {
+		div {
+			class: if is_expanded { "guild-folder expanded" } else { "guild-folder" },
+
+			DropBefore { target: FolderDrop::BeforeEntry(first), on_drop }
+			div {
+				class: "guild-folder-icon",
+				style: "color: {color};",
+				title: folder.name.clone().unwrap_or_default(),
+				onclick: move |_| {
+					let mut expanded = expanded.write();
+					if !expanded.remove(&folder_id) {
+						expanded.insert(folder_id);
+					}
+				},
+				ondragover: move |event| event.prevent_default(),
+				ondrop: move |event| {
+					event.prevent_default();
+					on_drop.call(FolderDrop::Into(first));
+				},
+
+				if !is_expanded {
+					for (id, icon, acronym) in previews {
+						match icon {
+							Some(hash) => rsx! {
+								img { key: "{id}", src: cdn::guild_icon(id, &hash, 32), alt: "" }
+							},
+							None => rsx! {
+								span { key: "{id}", "{acronym}" }
+							},
+						}
+					}
+				}
+			}
+			if !is_expanded {
+				Badges { unread, mentions }
+			}
+
+			if is_expanded {
+				for guild_id in folder.guild_ids.iter().copied() {
+					GuildItem {
+						key: "{guild_id}",
+						guild_id,
+						selected: selected == Some(guild_id),
+						dragging,
+						on_drop,
+					}
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn GuildItem(
+	guild_id: Snowflake,
+	selected: bool,
+	dragging: Signal<Option<Snowflake>>,
+	on_drop: Callback<FolderDrop>,
+) -> Element {
+	let guilds = GUILDS.read();
+	let Some(guild) = guilds.guild(guild_id) else {
+		return rsx! {};
+	};
+	let (unread, mentions) = guild_badges(guild);
+	let name = guild.name().to_string();
+
+	rsx! {
+		div {
+			class: "guild-list-item",
+
+			DropBefore { target: FolderDrop::Before(guild_id), on_drop }
+			div {
+				class: if selected { "guild-icon selected" } else { "guild-icon" },
+				title: "{name}",
+				draggable: "true",
+				onclick: move |_| {
+					navigator().push(format!("/channels/{}", guild_id));
+				},
+				ondragstart: move |_| dragging.set(Some(guild_id)),
+				ondragend: move |_| dragging.set(None),
+				ondragover: move |event| event.prevent_default(),
+				ondrop: move |event| {
+					event.prevent_default();
+					on_drop.call(FolderDrop::Into(guild_id));
+				},
+
+				match guild.icon() {
+					Some(hash) => rsx! {
+						img { src: cdn::guild_icon(guild_id, hash, 96), alt: "" }
+					},
+					None => rsx! { {acronym(&name)} },
+				}
+			}
+			Badges { unread, mentions }
+		}
+	}
+}
+
+/// thin strip above an item, dropping onto it inserts before that item instead of merging into it
+#[component]
+fn DropBefore(
+	target: FolderDrop,
+	on_drop: Callback<FolderDrop>,
+) -> Element {
+	rsx! {
+		div {
+			class: "guild-drop-before",
+			ondragover: move |event| event.prevent_default(),
+			ondrop: move |event| {
+				event.prevent_default();
+				on_drop.call(target);
+			},
+		}
+	}
+}
+
+#[component]
+fn Badges(
+	unread: bool,
+	mentions: u32,
+) -> Element {
 	rsx! {
-	   Outlet::<crate::Route> {}
+		if unread {
+			span { class: "guild-unread-indicator" }
+		}
+		if mentions > 0 {
+			span { class: "mention-badge", "{mentions}" }
+		}
 	}
 }
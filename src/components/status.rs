@@ -1 +1,193 @@
 // this gonna be that little ass thingy in the bottom left
+
+use dioxus::prelude::*;
+
+use crate::Route;
+use crate::components::modals::confirm::ConfirmModal;
+use crate::store::{USERS, cache};
+use crate::utils::cdn;
+use crate::utils::platform;
+use crate::utils::recorder;
+use crate::utils::token::ACCOUNTS;
+use crate::views::{log_out, reconnect, switch_account};
+
+#[component]
+pub fn Status() -> Element {
+	let mut switcher_open = use_signal(|| false);
+	let mut confirm_logout = use_signal(|| false);
+	let mut recording = use_signal(recorder::recording);
+
+	let Some(me) = USERS.read().current().cloned() else {
+		return rsx! {};
+	};
+	let others: Vec<_> = ACCOUNTS
+		.read()
+		.accounts
+		.iter()
+		.filter(|a| a.id != me.id)
+		.cloned()
+		.collect();
+
+	rsx! {
+		div {
+			class: "status",
+			div {
+				class: "status-user",
+				title: "Switch Accounts",
+				onclick: move |_| switcher_open.toggle(),
+				img { class: "avatar", src: cdn::avatar(&me, 32), alt: "" }
+				div {
+					class: "status-names",
+					span { class: "display-name", "{me.display_name()}" }
+					span { class: "username", "{me.username}" }
+				}
+			}
+
+			if switcher_open() {
+				div {
+					class: "account-switcher",
+					for account in others {
+						div {
+							key: "{account.id}",
+							class: "account",
+							onclick: move |_| {
+								switcher_open.set(false);
+								spawn(async move {
+									switch_account(account.id).await;
+									navigator().replace(Route::FateDecider {});
+								});
+							},
+							match &account.user {
+								Some(user) => rsx! {
+									img { class: "avatar", src: cdn::avatar(user, 32), alt: "" }
+									span { "{user.display_name()}" }
+								},
+								None => rsx! {
+									span { "{account.id}" }
+								},
+							}
+						}
+					}
+					button {
+						onclick: move |_| {
+							switcher_open.set(false);
+							navigator().push(Route::Login {});
+						},
+						"Add an Account"
+					}
+					button {
+						onclick: move |_| {
+							switcher_open.set(false);
+							navigator().push(Route::Sessions {});
+						},
+						"Devices"
+					}
+					button {
+						title: "Forget the messages and servers saved for offline use",
+						onclick: move |_| {
+							switcher_open.set(false);
+							spawn(cache::clear());
+						},
+						"Clear Cache"
+					}
+					button {
+						title: "Record what discord sends, without tokens, to reproduce bugs with later",
+						onclick: move |_| {
+							switcher_open.set(false);
+							if recording() {
+								spawn(recorder::stop());
+								recording.set(false);
+							} else {
+								// from the start of a session, so replays have READY to go on
+								recorder::start();
+								reconnect();
+								navigator().replace(Route::FateDecider {});
+							}
+						},
+						if recording() { "Stop Recording" } else { "Start Recording" }
+					}
+					button {
+						title: "Play back the last recording instead of connecting",
+						onclick: move |_| {
+							switcher_open.set(false);
+							spawn(async move {
+								if recorder::replaying() {
+									recorder::stop_replay();
+								} else if !recorder::start_replay().await {
+									platform::error("Nothing's been recorded yet");
+									return;
+								}
+								reconnect();
+								navigator().replace(Route::FateDecider {});
+							});
+						},
+						if recorder::replaying() { "Stop Replay" } else { "Replay Recording" }
+					}
+					button {
+						title: "Save the last recording, eg. to attach to a bug report",
+						onclick: move |_| {
+							switcher_open.set(false);
+							spawn(async move {
+								match recorder::export().await {
+									| Ok(true) => {},
+									| Ok(false) => platform::error("Nothing's been recorded yet"),
+									| Err(e) => platform::error(&format!("Failed to export the recording: {}", e)),
+								}
+							});
+						},
+						"Export Recording"
+					}
+					label {
+						class: "button",
+						title: "Load an exported recording to replay",
+						"Import Recording"
+						input {
+							r#type: "file",
+							accept: ".json",
+							hidden: true,
+							onchange: move |event| async move {
+								switcher_open.set(false);
+								let Some(files) = event.files() else {
+									return;
+								};
+								for name in files.files() {
+									let Some(json) = files.read_file_to_string(&name).await else {
+										continue;
+									};
+									if let Err(e) = recorder::import(&json).await {
+										platform::error(&format!("Failed to import {}: {}", name, e));
+									}
+								}
+							},
+						}
+					}
+					button {
+						class: "danger",
+						onclick: move |_| {
+							switcher_open.set(false);
+							confirm_logout.set(true);
+						},
+						"Log Out"
+					}
+				}
+			}
+		}
+
+		if confirm_logout() {
+			ConfirmModal {
+				title: "Log Out",
+				message: "Are you sure you want to log out?",
+				confirm_label: "Log Out",
+				on_confirm: move |_| {
+					confirm_logout.set(false);
+					spawn(async move {
+						log_out().await;
+						// the next saved account, or the login page if that was the last one
+						navigator().replace(Route::FateDecider {});
+					});
+				},
+				on_cancel: move |_| confirm_logout.set(false),
+			}
+		}
+	}
+}
@@ -0,0 +1,97 @@
+pub mod user_profile;
+pub mod user_profile_popup;
+
+use std::error::Error;
+
+use dioxus::html::geometry::PixelsRect;
+use dioxus::prelude::*;
+
+use crate::models::types::Snowflake;
+use crate::models::user::profile::{UpdateNoteRequest, UserNote, UserProfile, UserProfileRequest};
+use crate::store::USERS;
+use crate::utils::platform;
+use crate::utils::request::RequestClient;
+
+async fn get_user_profile(
+	user_id: Snowflake,
+	guild_id: Option<Snowflake>,
+) -> Result<UserProfile, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.get_with_query(&format!("/users/{}/profile", user_id), &UserProfileRequest {
+			with_mutual_guilds: true,
+			with_mutual_friends: true,
+			guild_id,
+		})
+		.await
+}
+
+// 404s when there's no note
+async fn get_note(user_id: Snowflake) -> Result<UserNote, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client.get(&format!("/users/@me/notes/{}", user_id)).await
+}
+
+async fn update_note(
+	user_id: Snowflake,
+	note: String,
+) -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.put(&format!("/users/@me/notes/{}", user_id), &UpdateNoteRequest { note })
+		.await
+}
+
+/// the clicked element's rect, for anchoring the popup next to it
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ProfileAnchor {
+	pub user_id: Snowflake,
+	pub left:    f64,
+	pub top:     f64,
+	pub right:   f64,
+}
+
+pub static PROFILE_POPUP: GlobalSignal<Option<ProfileAnchor>> = Signal::global(|| None);
+pub static PROFILE_MODAL: GlobalSignal<Option<Snowflake>> = Signal::global(|| None);
+
+pub fn open_profile_popup(
+	user_id: Snowflake,
+	rect: PixelsRect,
+) {
+	*PROFILE_POPUP.write() = Some(ProfileAnchor {
+		user_id,
+		left: rect.min_x(),
+		top: rect.min_y(),
+		right: rect.max_x(),
+	});
+}
+
+pub fn open_profile_modal(user_id: Snowflake) {
+	*PROFILE_POPUP.write() = None;
+	*PROFILE_MODAL.write() = Some(user_id);
+}
+
+/// fetches the profile once, so callers should be keyed by user and guild
+pub fn use_profile(
+	user_id: Snowflake,
+	guild_id: Option<Snowflake>,
+) -> Signal<Option<UserProfile>> {
+	let mut profile = use_signal(|| None);
+
+	use_hook(move || {
+		spawn(async move {
+			match get_user_profile(user_id, guild_id).await {
+				| Ok(fetched) => {
+					USERS.write().insert(fetched.user.clone().into());
+					profile.set(Some(fetched));
+				},
+				| Err(e) => platform::error(&format!("Failed to load profile: {}", e)),
+			}
+		});
+	});
+
+	profile
+}
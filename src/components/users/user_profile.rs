@@ -0,0 +1,150 @@
+use dioxus::prelude::*;
+
+use super::user_profile_popup::ProfileCard;
+use super::{PROFILE_MODAL, use_profile};
+use crate::Route;
+use crate::components::server_list::current_guild;
+use crate::models::types::Snowflake;
+use crate::models::user::profile::UserProfile;
+use crate::store::GUILDS;
+use crate::utils::cdn;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Tab {
+	UserInfo,
+	MutualServers,
+	MutualFriends,
+}
+
+fn close() { *PROFILE_MODAL.write() = None; }
+
+/// rendered once by the app layout, the full profile with mutuals
+#[component]
+pub fn UserProfileModal() -> Element {
+	let route = use_route::<Route>();
+	let Some(user_id) = PROFILE_MODAL() else {
+		return rsx! {};
+	};
+	let guild_id = current_guild(&route);
+
+	rsx! {
+		div {
+			class: "modal-overlay",
+			onclick: move |_| close(),
+			div {
+				class: "modal-content profile-modal",
+				onclick: move |event| event.stop_propagation(),
+				ModalContent { key: "{user_id}-{guild_id:?}", user_id, guild_id }
+			}
+		}
+	}
+}
+
+#[component]
+fn ModalContent(
+	user_id: Snowflake,
+	guild_id: Option<Snowflake>,
+) -> Element {
+	let profile = use_profile(user_id, guild_id);
+	let mut tab = use_signal(|| Tab::UserInfo);
+
+	let Some(profile) = profile() else {
+		return rsx! {
+			div { class: "profile-loading", "Loading..." }
+		};
+	};
+
+	let guilds = profile.mutual_guilds.clone().unwrap_or_default().len();
+	let friends = profile.mutual_friends.clone().unwrap_or_default().len();
+	let tabs = [
+		(Tab::UserInfo, "User Info".to_string()),
+		(Tab::MutualServers, format!("{} Mutual Servers", guilds)),
+		(Tab::MutualFriends, format!("{} Mutual Friends", friends)),
+	];
+
+	rsx! {
+		div {
+			class: "profile-tabs",
+			for (t, label) in tabs {
+				// bots can't have friends
+				if !(t == Tab::MutualFriends && profile.user.bot) {
+					button {
+						class: if t == tab() { "profile-tab selected" } else { "profile-tab" },
+						onclick: move |_| tab.set(t),
+						"{label}"
+					}
+				}
+			}
+		}
+
+		match tab() {
+			Tab::UserInfo => rsx! {
+				ProfileCard { profile, guild_id }
+			},
+			Tab::MutualServers => rsx! {
+				MutualServers { profile }
+			},
+			Tab::MutualFriends => rsx! {
+				MutualFriends { profile }
+			},
+		}
+	}
+}
+
+#[component]
+fn MutualServers(profile: UserProfile) -> Element {
+	let guilds = GUILDS.read();
+	let mutuals = profile.mutual_guilds.unwrap_or_default();
+
+	rsx! {
+		div {
+			class: "profile-mutuals",
+			if mutuals.is_empty() {
+				p { "No servers in common" }
+			}
+			for mutual in mutuals {
+				if let Some(guild) = guilds.guild(mutual.id) {
+					Link {
+						key: "{mutual.id}",
+						class: "profile-mutual",
+						to: Route::Roles {
+							server_id: mutual.id.to_string(),
+						},
+						onclick: move |_| close(),
+						if let Some(icon) = guild.icon() {
+							img { src: cdn::guild_icon(guild.id, icon, 64), alt: "" }
+						}
+						span { "{guild.name()}" }
+						if let Some(nick) = &mutual.nick {
+							span { class: "profile-mutual-nick", "{nick}" }
+						}
+					}
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn MutualFriends(profile: UserProfile) -> Element {
+	let mutuals = profile.mutual_friends.unwrap_or_default();
+
+	rsx! {
+		div {
+			class: "profile-mutuals",
+			if mutuals.is_empty() {
+				p { "No friends in common" }
+			}
+			for user in mutuals {
+				div {
+					key: "{user.id}",
+					class: "profile-mutual",
+					onclick: move |_| *PROFILE_MODAL.write() = Some(user.id),
+					img { src: cdn::avatar(&user, 64), alt: "" }
+					span { "{user.display_name()}" }
+					span { class: "profile-mutual-nick", "{user.username}" }
+				}
+			}
+		}
+	}
+}
@@ -0,0 +1,357 @@
+use std::rc::Rc;
+
+use dioxus::prelude::*;
+
+use super::{PROFILE_POPUP, ProfileAnchor, get_note, open_profile_modal, open_profile_popup, update_note, use_profile};
+use crate::Route;
+use crate::components::direct_messages::open_private_channel;
+use crate::components::markdown::Markdown;
+use crate::components::relationships::add_friend;
+use crate::components::server_list::current_guild;
+use crate::models::guild::Role;
+use crate::models::relationship::{FRIEND, OUTGOING_REQUEST};
+use crate::models::types::Snowflake;
+use crate::models::user::profile::{UserProfile, badges};
+use crate::store::{GUILDS, PRESENCES, RELATIONSHIPS, USERS};
+use crate::utils::{cdn, platform, time};
+
+const POPUP_WIDTH: f64 = 340.0;
+// roughly how tall a popup with a bio and a few roles ends up, good enough for keeping it on screen
+const POPUP_HEIGHT: f64 = 520.0;
+const GAP: f64 = 8.0;
+const VIEWPORT: &str = "dioxus.send([window.innerWidth, window.innerHeight]);";
+
+/// to the right of the anchor, or the left if that runs off screen, shifted up to fit vertically
+fn popup_position(
+	anchor: &ProfileAnchor,
+	viewport: (f64, f64),
+) -> (f64, f64) {
+	let (width, height) = viewport;
+	let left = if anchor.right + GAP + POPUP_WIDTH <= width {
+		anchor.right + GAP
+	} else {
+		(anchor.left - GAP - POPUP_WIDTH).max(GAP)
+	};
+	let top = anchor.top.min(height - POPUP_HEIGHT - GAP).max(GAP);
+
+	(left, top)
+}
+
+/// makes whatever it wraps open the user's profile popup when clicked
+#[component]
+pub fn ProfileTrigger(
+	user_id: Snowflake,
+	#[props(default)] class: String,
+	children: Element,
+) -> Element {
+	let mut element = use_signal(|| None::<Rc<MountedData>>);
+
+	rsx! {
+		div {
+			class: "profile-trigger {class}",
+			onmounted: move |event| element.set(Some(event.data())),
+			onclick: move |event| async move {
+				event.stop_propagation();
+				let Some(element) = element.peek().clone() else {
+					return;
+				};
+				if let Ok(rect) = element.get_client_rect().await {
+					open_profile_popup(user_id, rect);
+				}
+			},
+			{children}
+		}
+	}
+}
+
+/// rendered once by the app layout, shows whichever popup was opened last
+#[component]
+pub fn UserProfilePopup() -> Element {
+	let route = use_route::<Route>();
+	// asked again whenever it opens, the window can't be read synchronously on desktop
+	let viewport = use_resource(|| async {
+		if PROFILE_POPUP.read().is_none() {
+			return None;
+		}
+		let mut eval = document::eval(VIEWPORT);
+		eval.recv::<(f64, f64)>().await.ok()
+	});
+	let Some(anchor) = PROFILE_POPUP() else {
+		return rsx! {};
+	};
+	let viewport = viewport.cloned().flatten();
+
+	let guild_id = current_guild(&route);
+	let (left, top) = popup_position(&anchor, viewport.unwrap_or((f64::MAX, f64::MAX)));
+	// kept hidden until it's known where it fits
+	let visibility = if viewport.is_some() { "visible" } else { "hidden" };
+
+	rsx! {
+		div {
+			class: "profile-popup-overlay",
+			onclick: move |_| *PROFILE_POPUP.write() = None,
+			div {
+				class: "profile-popup",
+				style: "left: {left}px; top: {top}px; width: {POPUP_WIDTH}px; visibility: {visibility};",
+				onclick: move |event| event.stop_propagation(),
+				PopupContent {
+					key: "{anchor.user_id}-{guild_id:?}",
+					user_id: anchor.user_id,
+					guild_id,
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn PopupContent(
+	user_id: Snowflake,
+	guild_id: Option<Snowflake>,
+) -> Element {
+	let profile = use_profile(user_id, guild_id);
+
+	rsx! {
+		match profile() {
+			Some(profile) => rsx! {
+				ProfileCard { profile, guild_id }
+				button {
+					class: "profile-view-full",
+					onclick: move |_| open_profile_modal(user_id),
+					"View Full Profile"
+				}
+			},
+			None => rsx! {
+				div { class: "profile-loading", "Loading..." }
+			},
+		}
+	}
+}
+
+/// banner, names, badges, bio, roles, connections and note, shared by the popup, modal and dm sidebar
+#[component]
+pub fn ProfileCard(
+	profile: UserProfile,
+	guild_id: Option<Snowflake>,
+) -> Element {
+	let user = &profile.user;
+	let user_id = user.id;
+	let member = profile.guild_member.as_ref();
+	let name = member
+		.and_then(|m| m.nick.clone())
+		.or_else(|| user.global_name.clone())
+		.unwrap_or_else(|| user.username.clone());
+	let avatar = cdn::avatar(&user.clone().into(), 128);
+	let status = PRESENCES.read().status(user_id);
+
+	let banner_hash = profile
+		.user_profile
+		.as_ref()
+		.and_then(|p| p.banner.clone())
+		.or_else(|| user.banner.clone());
+	let banner_style = match (&banner_hash, profile.accent_color()) {
+		| (Some(hash), _) => format!("background-image: url({});", cdn::banner(user_id, hash, 600)),
+		| (None, Some(color)) => format!("background-color: #{:06x};", color),
+		| (None, None) => String::new(),
+	};
+
+	let bio = profile.bio().to_string();
+	let pronouns = profile.pronouns().to_string();
+	let badges = badges(user.public_flags);
+
+	let roles: Vec<Role> = match (guild_id, member) {
+		| (Some(guild_id), Some(member)) => {
+			let guilds = GUILDS.read();
+			let mut roles: Vec<Role> = member
+				.roles
+				.iter()
+				.filter_map(|&id| guilds.role(guild_id, id).cloned())
+				.collect();
+			roles.sort_by_key(|r| std::cmp::Reverse(r.position));
+			roles
+		},
+		| _ => Vec::new(),
+	};
+
+	let me = USERS.read().current().map(|u| u.id);
+	let relationship = RELATIONSHIPS.read().get(user_id).map(|r| r.r#type);
+	let can_add = !user.bot && relationship.is_none_or(|r| r != FRIEND && r != OUTGOING_REQUEST);
+
+	rsx! {
+		div {
+			class: "profile-card",
+			div { class: "profile-banner", style: banner_style }
+			div {
+				class: "profile-avatar",
+				img { src: avatar, alt: "" }
+				span { class: "status {status.as_str()}" }
+			}
+
+			div {
+				class: "profile-names",
+				h2 { "{name}" }
+				span { class: "profile-username", "{user.username}" }
+				if !pronouns.is_empty() {
+					span { class: "profile-pronouns", " • {pronouns}" }
+				}
+				if user.bot {
+					span { class: "bot-tag", "BOT" }
+				}
+			}
+
+			if !badges.is_empty() {
+				div {
+					class: "profile-badges",
+					for badge in badges {
+						span { class: "profile-badge", title: badge, "{badge}" }
+					}
+				}
+			}
+
+			if !bio.is_empty() {
+				section {
+					h3 { "About Me" }
+					Markdown { content: bio }
+				}
+			}
+
+			section {
+				h3 { "Member Since" }
+				span { {time::format(user_id.timestamp() as i64, 'D')} }
+			}
+
+			if !roles.is_empty() {
+				section {
+					h3 { if roles.len() == 1 { "Role" } else { "Roles" } }
+					div {
+						class: "profile-roles",
+						for role in roles {
+							span {
+								key: "{role.id}",
+								class: "profile-role",
+								span {
+									class: "role-color",
+									style: if role.color != 0 { format!("background-color: #{:06x};", role.color) },
+								}
+								"{role.name}"
+							}
+						}
+					}
+				}
+			}
+
+			if !profile.connected_accounts.is_empty() {
+				section {
+					h3 { "Connections" }
+					div {
+						class: "profile-connections",
+						for account in &profile.connected_accounts {
+							div {
+								key: "{account.r#type}-{account.id}",
+								class: "profile-connection",
+								span { class: "connection-type", "{account.r#type}" }
+								span { "{account.name}" }
+								if account.verified {
+									span { class: "connection-verified", title: "Verified", "✓" }
+								}
+							}
+						}
+					}
+				}
+			}
+
+			if me != Some(user_id) {
+				Note { key: "{user_id}", user_id }
+
+				div {
+					class: "profile-actions",
+					button {
+						onclick: move |_| {
+							*PROFILE_POPUP.write() = None;
+							open_private_channel(vec![user_id]);
+						},
+						"Message"
+					}
+					if can_add {
+						button { onclick: move |_| add_friend(user_id), "Add Friend" }
+					}
+				}
+			}
+		}
+	}
+}
+
+/// only visible to us, saved when the textarea loses focus
+#[component]
+fn Note(user_id: Snowflake) -> Element {
+	let mut note = use_signal(String::new);
+	let mut saved = use_signal(String::new);
+
+	use_hook(move || {
+		spawn(async move {
+			if let Ok(fetched) = get_note(user_id).await {
+				saved.set(fetched.note.clone());
+				note.set(fetched.note);
+			}
+		});
+	});
+
+	rsx! {
+		section {
+			h3 { "Note" }
+			textarea {
+				class: "profile-note",
+				placeholder: "Click to add a note",
+				value: "{note}",
+				oninput: move |event| note.set(event.value()),
+				onblur: move |_| {
+					let text = note();
+					if text == saved() {
+						return;
+					}
+
+					// blurring is usually what closes the popup, so the save can't be tied to it
+					spawn_forever(async move {
+						match update_note(user_id, text.clone()).await {
+							| Ok(()) => {
+								if let Ok(mut saved) = saved.try_write() {
+									*saved = text;
+								}
+							},
+							| Err(e) => platform::error(&format!("Failed to save note: {}", e)),
+						}
+					});
+				},
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn anchor(
+		left: f64,
+		top: f64,
+	) -> ProfileAnchor {
+		ProfileAnchor {
+			user_id: Snowflake::new(1),
+			left,
+			top,
+			right: left + 40.0,
+		}
+	}
+
+	#[test]
+	fn popup_stays_on_screen() {
+		let viewport = (1280.0, 720.0);
+
+		// room on the right
+		assert_eq!(popup_position(&anchor(100.0, 100.0), viewport), (148.0, 100.0));
+		// flips to the left of the anchor near the right edge
+		assert_eq!(popup_position(&anchor(1200.0, 100.0), viewport), (852.0, 100.0));
+		// shifted up near the bottom
+		assert_eq!(popup_position(&anchor(100.0, 700.0), viewport), (148.0, 192.0));
+	}
+}
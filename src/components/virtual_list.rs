@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use dioxus::prelude::*;
+
+// how close to either end (in px) before on_reach_start/on_reach_end fire
+const REACH_THRESHOLD: f64 = 200.0;
+// how close to the bottom still counts as being at the bottom when sticking to it
+const BOTTOM_THRESHOLD: f64 = 32.0;
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+// dioxus can only scroll things into view, so moving the scroll position goes through eval. null is the bottom
+const SET_SCROLL_TOP: &str = r#"
+	const [id, top] = await dioxus.recv();
+	const el = document.getElementById(id);
+	if (el) {
+		el.scrollTop = top ?? el.scrollHeight;
+	}
+"#;
+
+fn list_id(id: usize) -> String { format!("virtual-list-{}", id) }
+
+fn set_scroll_top(
+	id: usize,
+	top: Option<f64>,
+) {
+	let _ = document::eval(SET_SCROLL_TOP).send((list_id(id), top));
+}
+
+/// offset of the top of every row plus the total height as the last entry
+fn row_offsets(
+	keys: &[String],
+	heights: &HashMap<String, f64>,
+	estimated_height: f64,
+) -> Vec<f64> {
+	let mut offsets = Vec::with_capacity(keys.len() + 1);
+	let mut offset = 0.0;
+
+	for key in keys {
+		offsets.push(offset);
+		offset += heights.get(key).copied().unwrap_or(estimated_height);
+	}
+	offsets.push(offset);
+
+	offsets
+}
+
+/// rows overlapping the viewport, padded by `overscan` rows on each side
+fn visible_range(
+	offsets: &[f64],
+	scroll_top: f64,
+	viewport_height: f64,
+	overscan: usize,
+) -> (usize, usize) {
+	let count = offsets.len().saturating_sub(1);
+	if count == 0 {
+		return (0, 0);
+	}
+
+	// first row whose bottom is below the top of the viewport
+	let first = offsets[1..].partition_point(|&bottom| bottom <= scroll_top);
+	// first row whose top is below the bottom of the viewport
+	let last = offsets[..count].partition_point(|&top| top < scroll_top + viewport_height);
+
+	(
+		first.saturating_sub(overscan),
+		(last + overscan).min(count).max(first.min(count)),
+	)
+}
+
+/// only keeps the rows in view (plus some overscan) in the dom, row heights are measured once mounted.
+/// rows are identified by `keys` so measurements survive rows being inserted before them, and prepending rows
+/// keeps whatever was on screen in place.
+#[component]
+pub fn VirtualList(
+	keys: Vec<String>,
+	render_item: Callback<usize, Element>,
+	#[props(default = 48.0)] estimated_height: f64,
+	#[props(default = 8)] overscan: usize,
+	#[props(default)] class: String,
+	/// keeps the list scrolled to the bottom while it's already there, for chats
+	#[props(default)]
+	stick_to_bottom: bool,
+	/// key of a row to bring into view, applied whenever it changes
+	#[props(default)]
+	scroll_to: Option<String>,
+	#[props(default)] on_reach_start: EventHandler<()>,
+	#[props(default)] on_reach_end: EventHandler<()>,
+	/// whether the list is scrolled all the way down, whenever that changes
+	#[props(default)]
+	on_bottom_change: EventHandler<bool>,
+	/// rendered range, eg. for requesting lazy loaded rows
+	#[props(default)]
+	on_range_change: EventHandler<(usize, usize)>,
+) -> Element {
+	let id = use_hook(|| NEXT_ID.fetch_add(1, Ordering::Relaxed));
+	let mut container = use_signal(|| None::<Rc<MountedData>>);
+	let mut heights = use_signal(HashMap::<String, f64>::new);
+	let mut scroll_top = use_signal(|| 0.0);
+	let mut viewport_height = use_signal(|| 0.0);
+	let mut at_bottom = use_signal(|| stick_to_bottom);
+	let mut first_key = use_hook(|| CopyValue::new(None::<String>));
+	let mut applied_scroll_to = use_hook(|| CopyValue::new(None::<String>));
+	let mut last_range = use_hook(|| CopyValue::new((0, 0)));
+
+	let offsets = row_offsets(&keys, &heights.read(), estimated_height);
+	let total_height = offsets.last().copied().unwrap_or_default();
+	let mut top = scroll_top();
+	// where to move the scroll position to, worked out here so the right rows render straight away
+	let mut scroll = None;
+
+	// keep the rows that were on screen in place when rows get prepended.
+	// moving the scroll position fires onscroll, which catches scroll_top up afterwards
+	if keys.first() != first_key.peek().as_ref()
+		&& let Some(previous) = &*first_key.peek()
+		&& container.peek().is_some()
+		&& let Some(index) = keys.iter().position(|k| k == previous)
+		&& index > 0
+		&& !*at_bottom.peek()
+	{
+		top += offsets[index];
+		scroll = Some(top);
+	}
+
+	if scroll_to != *applied_scroll_to.peek()
+		&& let Some(target) = &scroll_to
+		&& container.peek().is_some()
+		&& let Some(index) = keys.iter().position(|k| k == target)
+	{
+		// a third of the way down looks less cramped than right at the top
+		top = (offsets[index] - *viewport_height.peek() / 3.0).max(0.0);
+		scroll = Some(top);
+	}
+
+	let (start, end) = visible_range(&offsets, top, viewport_height(), overscan);
+
+	// the scrolling and telling the parent wait until after render, once per change
+	let first = keys.first().cloned();
+	use_effect(use_reactive!(|(start, end, first, scroll_to, scroll)| {
+		if scroll.is_some() {
+			set_scroll_top(id, scroll);
+		}
+		first_key.set(first);
+		applied_scroll_to.set(scroll_to);
+
+		if (start, end) != *last_range.peek() {
+			last_range.set((start, end));
+			on_range_change.call((start, end));
+		}
+	}));
+
+	// runs after the rows above got rendered, so the scroll height is up to date
+	use_effect(move || {
+		let _ = heights.read();
+		if stick_to_bottom && at_bottom() && container.peek().is_some() {
+			set_scroll_top(id, None);
+		}
+	});
+
+	let mut measure = move |key: String, index: usize, height: f64| {
+		let previous = heights.peek().get(&key).copied();
+		if previous == Some(height) {
+			return;
+		}
+
+		// rows above the viewport changing height would push everything on screen around
+		let delta = height - previous.unwrap_or(estimated_height);
+		if !*at_bottom.peek() && index < start && container.peek().is_some() {
+			let top = *scroll_top.peek() + delta;
+			set_scroll_top(id, Some(top));
+			scroll_top.set(top);
+		}
+
+		heights.write().insert(key, height);
+	};
+
+	let onscroll = move |_| async move {
+		let Some(el) = container.peek().clone() else {
+			return;
+		};
+		let (Ok(offset), Ok(size), Ok(rect)) = (
+			el.get_scroll_offset().await,
+			el.get_scroll_size().await,
+			el.get_client_rect().await,
+		) else {
+			return;
+		};
+
+		let top = offset.y;
+		let distance_from_bottom = size.height - top - rect.height();
+
+		scroll_top.set(top);
+		let bottom = distance_from_bottom < BOTTOM_THRESHOLD;
+		if bottom != *at_bottom.peek() {
+			at_bottom.set(bottom);
+			on_bottom_change.call(bottom);
+		}
+
+		if top < REACH_THRESHOLD {
+			on_reach_start.call(());
+		}
+		if distance_from_bottom < REACH_THRESHOLD {
+			on_reach_end.call(());
+		}
+	};
+
+	rsx! {
+		div {
+			id: list_id(id),
+			class: "virtual-list {class}",
+			style: "overflow-y: auto;",
+			onmounted: move |event| async move {
+				let el = event.data();
+				container.set(Some(el.clone()));
+				if stick_to_bottom {
+					set_scroll_top(id, None);
+				}
+				if let Ok(rect) = el.get_client_rect().await {
+					viewport_height.set(rect.height());
+				}
+			},
+			onresize: move |event| {
+				if let Ok(size) = event.data().get_border_box_size() {
+					viewport_height.set(size.height);
+				}
+			},
+			onscroll,
+
+			div {
+				style: "position: relative; height: {total_height}px;",
+				div {
+					style: "position: absolute; top: {offsets[start]}px; left: 0; right: 0;",
+
+					for index in start..end {
+						div {
+							key: "{keys[index]}",
+							onmounted: {
+								let key = keys[index].clone();
+								move |event: MountedEvent| {
+									let key = key.clone();
+									async move {
+										if let Ok(rect) = event.data().get_client_rect().await {
+											measure(key, index, rect.height());
+										}
+									}
+								}
+							},
+							onresize: {
+								let key = keys[index].clone();
+								move |event: ResizeEvent| {
+									if let Ok(size) = event.data().get_border_box_size() {
+										measure(key.clone(), index, size.height);
+									}
+								}
+							},
+							{render_item.call(index)}
+						}
+					}
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn keys(count: usize) -> Vec<String> { (0..count).map(|i| i.to_string()).collect() }
+
+	#[test]
+	fn offsets_use_measured_heights() {
+		let heights = HashMap::from([("1".to_string(), 100.0)]);
+
+		assert_eq!(row_offsets(&keys(3), &heights, 50.0), vec![
+			0.0, 50.0, 150.0, 200.0
+		]);
+	}
+
+	#[test]
+	fn range_covers_viewport_and_overscan() {
+		let offsets = row_offsets(&keys(100), &HashMap::new(), 10.0);
+
+		assert_eq!(visible_range(&offsets, 0.0, 50.0, 0), (0, 5));
+		assert_eq!(visible_range(&offsets, 105.0, 50.0, 0), (10, 16));
+		assert_eq!(visible_range(&offsets, 105.0, 50.0, 2), (8, 18));
+		assert_eq!(visible_range(&offsets, 990.0, 50.0, 2), (97, 100));
+	}
+
+	#[test]
+	fn empty_list() {
+		let offsets = row_offsets(&[], &HashMap::new(), 10.0);
+
+		assert_eq!(visible_range(&offsets, 0.0, 50.0, 4), (0, 0));
+	}
+}
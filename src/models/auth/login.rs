@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use crate::models::mfa::{MfaMethod, MfaMethods};
 use crate::models::types::Snowflake;
 
 // https://docs.discord.sex/authentication#login-source
@@ -48,6 +49,33 @@ pub struct LoginResponse {
 	pub webauthn:         Option<String>,
 }
 
+impl LoginResponse {
+	/// what the account can use to finish logging in, in the order they're offered
+	pub fn mfa_methods(&self) -> Vec<MfaMethod> {
+		let method = |r#type, challenge| MfaMethod {
+			r#type,
+			challenge,
+			backup_codes_allowed: None,
+		};
+
+		let mut methods = Vec::new();
+		if let Some(challenge) = &self.webauthn {
+			methods.push(method(MfaMethods::webauthn, Some(challenge.clone())));
+		}
+		for (enabled, r#type) in [
+			(self.totp, MfaMethods::totp),
+			(self.sms, MfaMethods::sms),
+			(self.backup, MfaMethods::backup),
+		] {
+			if enabled.unwrap_or(false) {
+				methods.push(method(r#type, None));
+			}
+		}
+
+		methods
+	}
+}
+
 #[derive(Deserialize)]
 pub struct LoginSettings {
 	pub locale: String, // https://docs.discord.sex/reference#locales
@@ -1 +1,40 @@
+use serde::{Deserialize, Serialize};
 
+// https://docs.discord.sex/authentication#register-account
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RegisterRequest {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub fingerprint:              Option<String>,
+	pub email:                    String,
+	pub username:                 String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub global_name:              Option<String>,
+	pub password:                 String,
+	pub date_of_birth:            String, // YYYY-MM-DD
+	pub consent:                  bool,
+	pub promotional_email_opt_in: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RegisterResponse {
+	pub token:                  String,
+	#[serde(default)]
+	pub show_verification_form: bool,
+}
+
+// https://docs.discord.sex/resources/user#get-unique-username-attempt-unauthenticated
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct UsernameAttemptRequest {
+	pub username: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct UsernameAttemptResponse {
+	pub taken: bool,
+}
+
+// https://docs.discord.sex/topics/experiments#get-experiment-assignments, only used for the fingerprint
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Experiments {
+	pub fingerprint: Option<String>,
+}
@@ -0,0 +1,39 @@
+use iso8601_timestamp::Timestamp;
+use serde::{Deserialize, Serialize};
+
+// https://docs.discord.sex/authentication#get-auth-sessions
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct AuthSessions {
+	pub user_sessions: Vec<AuthSession>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct AuthSession {
+	pub id_hash:               String,
+	pub approx_last_used_time: Timestamp,
+	pub client_info:           ClientInfo,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ClientInfo {
+	pub os:       Option<String>,
+	pub platform: Option<String>,
+	pub location: Option<String>,
+}
+
+impl ClientInfo {
+	/// "Windows · Discord Client", or whichever half is known
+	pub fn describe(&self) -> String {
+		match (&self.os, &self.platform) {
+			| (Some(os), Some(platform)) => format!("{} · {}", os, platform),
+			| (Some(name), None) | (None, Some(name)) => name.clone(),
+			| (None, None) => "Unknown device".to_string(),
+		}
+	}
+}
+
+// https://docs.discord.sex/authentication#logout-auth-sessions
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LogoutSessionsRequest {
+	pub session_id_hashes: Vec<String>,
+}
@@ -3,70 +3,91 @@ use serde::{Deserialize, Serialize};
 
 use super::guild::GuildMember;
 use super::types::Snowflake;
-use super::user::user::{Nick, ThreadMember, User};
+use super::user::user::{Nick, PartialUser, ThreadMember};
 
-#[derive(Deserialize, Debug)]
-pub struct MessageHistoryResponse {
-	pub messages: Vec<Message>,
+// https://docs.discord.sex/resources/channel#create-private-channel, one recipient opens the dm with them
+#[derive(Serialize)]
+pub struct CreatePrivateChannelRequest {
+	pub recipients: Vec<Snowflake>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Default)]
 pub struct MessageHistoryRequest {
 	#[serde(skip_serializing_if = "Option::is_none")]
-	pub around: Option<String>,
+	pub around: Option<Snowflake>,
 	#[serde(skip_serializing_if = "Option::is_none")]
-	pub before: Option<String>,
+	pub before: Option<Snowflake>,
 	#[serde(skip_serializing_if = "Option::is_none")]
-	pub after:  Option<String>,
+	pub after:  Option<Snowflake>,
 	pub limit:  u8,
 }
 
-#[derive(Deserialize, Debug)]
+// https://docs.discord.sex/resources/channel#channel-structure
+// most fields only show up for certain channel types, so pretty much everything is optional
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Channel {
 	pub id:                                 Snowflake,
 	pub r#type:                             u8, // https://docs.discord.sex/resources/channel#channel-type
-	pub guild_id:                           Snowflake,
-	pub position:                           u16,
+	pub guild_id:                           Option<Snowflake>,
+	#[serde(default)]
+	pub position:                           i32,
+	#[serde(default)]
 	pub permission_overwrites:              Vec<PermissionOverwrite>,
-	pub names:                              Option<String>,
+	pub name:                               Option<String>,
 	pub topic:                              Option<String>,
+	#[serde(default)]
 	pub nsfw:                               bool,
 	pub last_message_id:                    Option<Snowflake>,
-	pub bitrate:                            u32,
-	pub user_limit:                         u8,
+	pub bitrate:                            Option<u32>,
+	pub user_limit:                         Option<u8>,
+	#[serde(default)]
 	pub rate_limit_per_user:                u16,
-	pub recipients:                         Vec<User>,
-	pub recipient_falgs:                    u8, // https://docs.discord.sex/resources/channel#recipient-flags
+	#[serde(default)]
+	pub recipients:                         Vec<PartialUser>,
+	#[serde(default)]
+	pub recipient_ids:                      Vec<Snowflake>, // sent instead of recipients in READY
+	#[serde(default)]
+	pub recipient_flags:                    u8, // https://docs.discord.sex/resources/channel#recipient-flags
 	pub icon:                               Option<String>, // TODO: specific types for cdn hashes https://docs.discord.sex/reference#cdn-formatting
+	#[serde(default)]
 	pub nicks:                              Vec<Nick>,
+	#[serde(default)]
 	pub managed:                            bool,
+	#[serde(default)]
 	pub blocked_user_warning_dismissed:     bool,
+	#[serde(default)]
 	pub safety_warnings:                    Vec<SafetyWarning>,
-	pub application_id:                     Snowflake,
-	pub owner_id:                           Snowflake,
+	pub application_id:                     Option<Snowflake>,
+	pub owner_id:                           Option<Snowflake>,
 	pub owner:                              Option<GuildMember>,
 	pub parent_id:                          Option<Snowflake>,
 	pub last_pin_timestamp:                 Option<Timestamp>,
 	pub rtc_region:                         Option<String>, // https://docs.discord.sex/resources/voice#voice-region-object
-	pub video_quality_mode:                 u8, // https://docs.discord.sex/resources/channel#video-quality-mode
-	pub total_message_sent:                 usize, // like message_count except it counts deleted messages and intial message
-	pub message_count:                      usize,
-	pub member_count:                       u8, // stops counting at 50, nice one discord
+	pub video_quality_mode:                 Option<u8>, // https://docs.discord.sex/resources/channel#video-quality-mode
+	pub total_message_sent:                 Option<usize>, // like message_count except it counts deleted messages and intial message
+	pub message_count:                      Option<usize>,
+	pub member_count:                       Option<u8>, // stops counting at 50, nice one discord
+	#[serde(default)]
 	pub member_ids_preview:                 Vec<Snowflake>,
-	pub thread_metadata:                    ThreadMetaData,
-	pub member:                             ThreadMember,
+	pub thread_metadata:                    Option<ThreadMetaData>,
+	pub member:                             Option<ThreadMember>,
 	pub default_auto_archive_duration:      Option<u16>,
-	pub default_thread_rate_limit_per_user: isize,
-	pub permissions:                        String,
-	pub flags:                              u8, // https://docs.discord.sex/resources/channel#channel-flags
+	pub default_thread_rate_limit_per_user: Option<isize>,
+	pub permissions:                        Option<String>,
+	#[serde(default)]
+	pub flags:                              u64, // https://docs.discord.sex/resources/channel#channel-flags
+	#[serde(default)]
 	pub available_tags:                     Vec<Tag>, // max 5
+	#[serde(default)]
 	pub applied_tags:                       Vec<Snowflake>,
 	pub default_reaction_emoji:             Option<DefaultReaction>,
-	pub default_forum_layout:               u8, // https://docs.discord.sex/resources/channel#forum-layout-type
+	pub default_forum_layout:               Option<u8>, // https://docs.discord.sex/resources/channel#forum-layout-type
 	pub default_sort_order:                 Option<u8>, // https://docs.discord.sex/resources/channel#sort-order-type
 	pub icon_emoji:                         Option<IconEmoji>,
+	#[serde(default)]
 	pub is_message_request:                 bool,
 	pub is_message_request_timestamp:       Option<Timestamp>,
+	#[serde(default)]
 	pub is_spam:                            bool,
 	pub theme_color:                        Option<u32>,
 	pub status:                             Option<String>, // max 500 characters
@@ -102,19 +123,19 @@ pub enum DmSpamFilterV2 {
 	FRIENDS_AND_NON_FRIENDS,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct DefaultReaction {
 	pub emoji_id:   Option<Snowflake>,
 	pub emoji_name: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct IconEmoji {
 	pub emoji_id:   Option<Snowflake>,
 	pub emoji_name: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Tag {
 	pub id:         Snowflake,
 	pub name:       String,
@@ -123,17 +144,18 @@ pub struct Tag {
 	pub emoji_name: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct ThreadMetaData {
 	pub archived:              bool,
 	pub auto_archive_duration: u16,
 	pub archive_timestamp:     Timestamp,
 	pub locked:                bool,
-	pub invitable:             bool,
+	#[serde(default)]
+	pub invitable:             bool, // only sent for private threads
 	pub create_timestamp:      Option<Timestamp>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct PermissionOverwrite {
 	pub id:     Snowflake,
 	pub r#type: u8, // https://docs.discord.sex/resources/channel#permission-overwrite-type
@@ -141,7 +163,7 @@ pub struct PermissionOverwrite {
 	pub deny:   String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct SafetyWarning {
 	pub id:                String,
 	pub r#type:            u8, // https://docs.discord.sex/resources/channel#safety-warning-type
@@ -149,16 +171,109 @@ pub struct SafetyWarning {
 	pub dismiss_timestamp: Option<Timestamp>,
 }
 
-#[derive(Deserialize, Debug)]
+// https://docs.discord.sex/resources/message#message-structure
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Message {
-	pub id:               Snowflake,
-	pub channel_id:       Snowflake,
-	pub author:           User,
-	pub content:          String,
-	pub timestamp:        Timestamp,
-	pub edited_timestamp: Option<Timestamp>,
-	pub tts:              bool,
-	pub mention_everyone: bool,
-	pub mentions:         Vec<User>,
-	pub mention_channels: Vec<Channel>,
+	pub id:                 Snowflake,
+	pub channel_id:         Snowflake,
+	pub guild_id:           Option<Snowflake>, // only sent over the gateway
+	#[serde(default)]
+	pub r#type:             u8, // https://docs.discord.sex/resources/message#message-type
+	pub author:             PartialUser,
+	pub member:             Option<GuildMember>, // only sent over the gateway, without user
+	#[serde(default)]
+	pub content:            String,
+	pub timestamp:          Timestamp,
+	pub edited_timestamp:   Option<Timestamp>,
+	#[serde(default)]
+	pub tts:                bool,
+	#[serde(default)]
+	pub mention_everyone:   bool,
+	#[serde(default)]
+	pub mentions:           Vec<PartialUser>,
+	#[serde(default)]
+	pub mention_roles:      Vec<Snowflake>,
+	#[serde(default)]
+	pub mention_channels:   Vec<ChannelMention>,
+	#[serde(default)]
+	pub pinned:             bool,
+	#[serde(default)]
+	pub flags:              u64, // https://docs.discord.sex/resources/message#message-flags
+	pub nonce:              Option<Nonce>,
+	pub message_reference:  Option<MessageReference>,
+	pub referenced_message: Option<Box<Message>>, // null if the replied to message got deleted
+}
+
+impl Message {
+	/// whether this pings the user, dms always count
+	pub fn mentions(
+		&self,
+		user_id: Snowflake,
+		roles: &[Snowflake],
+	) -> bool {
+		self.guild_id.is_none()
+			|| self.mention_everyone
+			|| self.mentions.iter().any(|u| u.id == user_id)
+			|| self.mention_roles.iter().any(|r| roles.contains(r))
+	}
+}
+
+// whatever the sending client used, we always send strings
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Nonce {
+	String(String),
+	Integer(i64),
+}
+
+impl Nonce {
+	pub fn as_snowflake(&self) -> Option<Snowflake> {
+		match self {
+			| Nonce::String(nonce) => nonce.parse().ok(),
+			| Nonce::Integer(nonce) => u64::try_from(*nonce).ok().map(Snowflake::new),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MessageReference {
+	#[serde(default)]
+	pub r#type:             u8, // https://docs.discord.sex/resources/message#message-reference-type
+	pub message_id:         Option<Snowflake>,
+	pub channel_id:         Option<Snowflake>,
+	pub guild_id:           Option<Snowflake>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub fail_if_not_exists: Option<bool>,
+}
+
+// https://docs.discord.sex/resources/message#create-message
+#[derive(Serialize, Default)]
+pub struct CreateMessageRequest {
+	pub content:           String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub nonce:             Option<String>,
+	pub enforce_nonce:     bool, // makes retries with the same nonce return the original message instead of a duplicate
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub message_reference: Option<MessageReference>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub allowed_mentions:  Option<AllowedMentions>,
+}
+
+#[derive(Serialize)]
+pub struct AllowedMentions {
+	pub parse:        Vec<String>, // "users", "roles" and/or "everyone"
+	pub replied_user: bool,
+}
+
+#[derive(Serialize)]
+pub struct EditMessageRequest {
+	pub content: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ChannelMention {
+	pub id:       Snowflake,
+	pub guild_id: Snowflake,
+	pub r#type:   u8,
+	pub name:     String,
 }
@@ -0,0 +1,34 @@
+use serde::Deserialize;
+
+use super::types::Snowflake;
+
+// https://docs.discord.sex/interactions/application-commands#get-application-command-index
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ApplicationCommandIndex {
+	#[serde(default)]
+	pub applications:         Vec<IndexApplication>,
+	#[serde(default)]
+	pub application_commands: Vec<ApplicationCommand>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct IndexApplication {
+	pub id:   Snowflake,
+	pub name: String,
+	pub icon: Option<String>,
+}
+
+// https://docs.discord.sex/interactions/application-commands#application-command-structure
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ApplicationCommand {
+	pub id:             Snowflake,
+	pub application_id: Snowflake,
+	#[serde(default = "default_type")]
+	pub r#type:         u8, // https://docs.discord.sex/interactions/application-commands#application-command-type
+	pub name:           String,
+	#[serde(default)]
+	pub description:    String,
+}
+
+// chat input, the only kind that shows up when typing /
+fn default_type() -> u8 { 1 }
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+// https://docs.discord.sex/reference#error-messages
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ErrorResponse {
+	pub code:    u32,
+	pub message: String,
+	pub errors:  Option<serde_json::Value>,
+}
+
+impl ErrorResponse {
+	/// the first message for every field that failed validation, nested fields are keyed by their top level name
+	pub fn field_errors(&self) -> HashMap<String, String> {
+		let Some(errors) = self.errors.as_ref().and_then(|e| e.as_object()) else {
+			return HashMap::new();
+		};
+
+		errors
+			.iter()
+			.filter_map(|(field, error)| Some((field.clone(), first_message(error)?)))
+			.collect()
+	}
+}
+
+fn first_message(error: &serde_json::Value) -> Option<String> {
+	if let Some(message) = error["_errors"][0]["message"].as_str() {
+		return Some(message.to_string());
+	}
+
+	error.as_object()?.values().find_map(first_message)
+}
+
+// https://docs.discord.sex/reference#captcha-handling
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct CaptchaRequired {
+	pub captcha_key:     Vec<String>,
+	pub captcha_sitekey: String,
+	pub captcha_service: String, // only "hcaptcha" in practice
+	pub captcha_rqdata:  Option<String>,
+	pub captcha_rqtoken: Option<String>,
+}
+
+// https://docs.discord.sex/topics/rate-limits#rate-limit-response-structure
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RateLimited {
+	pub message:     String,
+	pub retry_after: f64, // seconds
+	pub global:      bool,
+	pub code:        Option<u32>,
+}
@@ -1,29 +1,211 @@
 use std::collections::HashMap;
 
 use iso8601_timestamp::Timestamp;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use super::chat::{ChannelSettings, CustomNotifSoundConfig};
+use super::chat::{Channel, ChannelSettings, CustomNotifSoundConfig};
+use super::presence::Presence;
 use super::types::Snowflake;
-use super::user::user::{AvatarDecorationData, User};
+use super::user::user::{AvatarDecorationData, PartialUser};
+use super::voice::VoiceState;
 
-#[derive(Deserialize, Debug)]
+// https://docs.discord.sex/resources/guild#guild-structure
+// guilds in READY put most of the metadata under `properties` instead
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Guild {
+	pub id:           Snowflake,
+	pub name:         Option<String>,
+	pub icon:         Option<String>,
+	pub owner_id:     Option<Snowflake>,
+	pub properties:   Option<GuildProperties>,
+	#[serde(default)]
+	pub channels:     Vec<Channel>,
+	#[serde(default)]
+	pub threads:      Vec<Channel>,
+	#[serde(default)]
+	pub roles:        Vec<Role>,
+	#[serde(default)]
+	pub emojis:       Vec<Emoji>,
+	#[serde(default)]
+	pub voice_states: Vec<VoiceState>,
+	#[serde(default)]
+	pub unavailable:  bool,
+}
+
+impl Guild {
+	pub fn name(&self) -> &str {
+		self.name
+			.as_deref()
+			.or(self.properties.as_ref().map(|p| p.name.as_str()))
+			.unwrap_or_default()
+	}
+
+	pub fn icon(&self) -> Option<&str> {
+		self.icon
+			.as_deref()
+			.or(self.properties.as_ref().and_then(|p| p.icon.as_deref()))
+	}
+
+	pub fn owner(&self) -> Option<Snowflake> {
+		self.owner_id
+			.or(self.properties.as_ref().and_then(|p| p.owner_id))
+	}
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct GuildProperties {
+	pub name:     String,
+	pub icon:     Option<String>,
+	pub owner_id: Option<Snowflake>,
+}
+
+// https://docs.discord.sex/resources/guild#role-structure
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Role {
+	pub id:            Snowflake,
+	pub name:          String,
+	#[serde(default)]
+	pub color:         u32,
+	#[serde(default)]
+	pub hoist:         bool,
+	pub icon:          Option<String>,
+	pub unicode_emoji: Option<String>,
+	#[serde(default)]
+	pub position:      i32,
+	#[serde(default)]
+	pub permissions:   String,
+	#[serde(default)]
+	pub managed:       bool,
+	#[serde(default)]
+	pub mentionable:   bool,
+}
+
+// https://docs.discord.sex/resources/emoji#emoji-structure
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct Emoji {
+	pub id:        Option<Snowflake>,
+	pub name:      Option<String>,
+	#[serde(default)]
+	pub roles:     Vec<Snowflake>,
+	#[serde(default)]
+	pub animated:  bool,
+	#[serde(default = "default_true")]
+	pub available: bool,
+}
+
+fn default_true() -> bool { true }
+
+// https://docs.discord.sex/resources/guild#guild-member-structure
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct GuildMember {
-	pub user:                         User,
+	pub user:                         Option<PartialUser>, // missing in MESSAGE_CREATE and a few other places
 	pub nick:                         Option<String>,
 	pub avatar:                       Option<String>,
 	pub avatar_decoration_data:       Option<AvatarDecorationData>,
 	pub banner:                       Option<String>,
+	#[serde(default)]
 	pub roles:                        Vec<Snowflake>,
-	pub joined_at:                    Timestamp,
+	pub joined_at:                    Option<Timestamp>, // null for guest members
 	pub premium_since:                Option<Timestamp>,
+	#[serde(default)]
 	pub deaf:                         bool,
+	#[serde(default)]
 	pub mute:                         bool,
+	#[serde(default)]
 	pub pending:                      bool,
 	pub communication_disabled_until: Option<Timestamp>,
 	pub unusual_dm_activity_until:    Option<Timestamp>,
-	pub flags:                        u8, // https://docs.discord.sex/resources/guild#guild-member-flags
-	pub permissions:                  String,
+	#[serde(default)]
+	pub flags:                        u64, // https://docs.discord.sex/resources/guild#guild-member-flags
+	pub permissions:                  Option<String>,
+}
+
+// https://docs.discord.sex/topics/gateway-events#guild-members-chunk, the reply to op 8
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct GuildMembersChunk {
+	pub guild_id:    Snowflake,
+	pub members:     Vec<GuildMember>,
+	pub chunk_index: u32,
+	pub chunk_count: u32,
+	#[serde(default)]
+	pub not_found:   Vec<Snowflake>,
+	pub nonce:       Option<String>,
+}
+
+// https://docs.discord.sex/topics/gateway-events#guild-member-list-update
+#[derive(Deserialize, Debug)]
+pub struct MemberListUpdate {
+	pub id:           String, // derived from the channel's permissions, channels with the same perms share a list
+	pub guild_id:     Snowflake,
+	#[serde(default)]
+	pub member_count: u32,
+	#[serde(default)]
+	pub online_count: u32,
+	#[serde(default)]
+	pub groups:       Vec<MemberListGroup>,
+	#[serde(default)]
+	pub ops:          Vec<MemberListOp>,
+}
+
+impl MemberListUpdate {
+	/// every member carried by the ops, for caching
+	pub fn members(&self) -> impl Iterator<Item = &GuildMember> {
+		self.ops
+			.iter()
+			.flat_map(|op| match op {
+				| MemberListOp::Sync { items, .. } => items.iter().collect(),
+				| MemberListOp::Insert { item, .. } | MemberListOp::Update { item, .. } => vec![item],
+				| _ => Vec::new(),
+			})
+			.filter_map(|item| match item {
+				| MemberListItem::Member(member) => Some(&member.member),
+				| MemberListItem::Group(_) => None,
+			})
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct MemberListGroup {
+	pub id:    String, // a hoisted role id, "online" or "offline"
+	#[serde(default)]
+	pub count: u32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "op", rename_all = "UPPERCASE")]
+pub enum MemberListOp {
+	Sync {
+		range: (usize, usize),
+		items: Vec<MemberListItem>,
+	},
+	Insert {
+		index: usize,
+		item:  MemberListItem,
+	},
+	Update {
+		index: usize,
+		item:  MemberListItem,
+	},
+	Delete {
+		index: usize,
+	},
+	Invalidate {
+		range: (usize, usize),
+	},
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MemberListItem {
+	Group(MemberListGroup),
+	Member(Box<MemberListMember>),
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct MemberListMember {
+	#[serde(flatten)]
+	pub member:   GuildMember,
+	pub presence: Option<Presence>,
 }
 
 #[derive(Deserialize)]
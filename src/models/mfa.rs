@@ -1,21 +1,22 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 // json response when mfa is required for an action
-#[derive(Deserialize)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct MfaRequired {
 	pub message: String,
 	pub code:    u64,
 	pub mfa:     MfaObject,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct MfaObject {
 	pub ticket:  String,
-	pub methods: Vec<MfaMethods>,
+	pub methods: Vec<MfaMethod>,
 }
 
+// https://docs.discord.sex/authentication#authenticator-type
 #[allow(non_camel_case_types)]
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub enum MfaMethods {
 	totp,
 	sms,
@@ -24,9 +25,50 @@ pub enum MfaMethods {
 	password,
 }
 
-#[derive(Deserialize)]
+impl MfaMethods {
+	/// also the last path segment of the login mfa endpoints
+	pub fn as_str(self) -> &'static str {
+		match self {
+			| MfaMethods::totp => "totp",
+			| MfaMethods::sms => "sms",
+			| MfaMethods::backup => "backup",
+			| MfaMethods::webauthn => "webauthn",
+			| MfaMethods::password => "password",
+		}
+	}
+
+	pub fn label(self) -> &'static str {
+		match self {
+			| MfaMethods::totp => "Authenticator app",
+			| MfaMethods::sms => "SMS",
+			| MfaMethods::backup => "Backup code",
+			| MfaMethods::webauthn => "Security key",
+			| MfaMethods::password => "Password",
+		}
+	}
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct MfaMethod {
-	pub r#type:                String,
-	pub challenge:             Option<String>,
-	pub backupd_codes_allowed: Option<String>,
+	pub r#type:               MfaMethods,
+	pub challenge:            Option<String>, // webauthn only, the json credential request options
+	pub backup_codes_allowed: Option<bool>,
+}
+
+// https://docs.discord.sex/authentication#verify-mfa
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MfaFinishRequest {
+	pub ticket:   String,
+	pub mfa_type: MfaMethods,
+	pub data:     String, // the code, password or webauthn assertion
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct MfaFinishResponse {
+	pub token: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MfaSmsRequest {
+	pub ticket: String,
 }
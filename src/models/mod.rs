@@ -3,8 +3,18 @@
 pub mod app;
 pub mod auth;
 pub mod chat;
+pub mod command;
+pub mod error;
 pub mod guild;
 pub mod mfa;
+pub mod presence;
+pub mod read_state;
+pub mod relationship;
+pub mod remote_auth;
 pub mod types;
 pub mod user;
+pub mod voice;
 pub mod websocket;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,55 @@
+use serde::Deserialize;
+
+use super::types::Snowflake;
+
+// https://docs.discord.sex/resources/presence#presence-structure
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Presence {
+	pub user:       Option<PresenceUser>, // only the id is guaranteed
+	pub user_id:    Option<Snowflake>, // sent instead of user in READY's merged_presences
+	#[serde(default)]
+	pub status:     Status,
+	#[serde(default)]
+	pub activities: Vec<Activity>,
+}
+
+impl Presence {
+	pub fn user_id(&self) -> Option<Snowflake> { self.user_id.or(self.user.as_ref().map(|u| u.id)) }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct PresenceUser {
+	pub id: Snowflake,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+	Online,
+	Idle,
+	Dnd,
+	#[default]
+	Offline,
+	Invisible,
+}
+
+impl Status {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			| Status::Online => "online",
+			| Status::Idle => "idle",
+			| Status::Dnd => "dnd",
+			| Status::Offline => "offline",
+			| Status::Invisible => "invisible",
+		}
+	}
+}
+
+// https://docs.discord.sex/resources/presence#activity-structure
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Activity {
+	pub name:    String,
+	pub r#type:  u8, // https://docs.discord.sex/resources/presence#activity-type
+	pub state:   Option<String>,
+	pub details: Option<String>,
+}
@@ -0,0 +1,79 @@
+use iso8601_timestamp::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use super::types::Snowflake;
+
+// https://docs.discord.sex/topics/read-state#read-state-structure
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ReadState {
+	pub id:                 Snowflake, // the channel for channel read states
+	#[serde(default)]
+	pub read_state_type:    u8, // https://docs.discord.sex/topics/read-state#read-state-type
+	pub last_message_id:    Option<Snowflake>,
+	#[serde(default)]
+	pub mention_count:      u32,
+	#[serde(default)]
+	pub badge_count:        u32,
+	pub last_pin_timestamp: Option<Timestamp>,
+	#[serde(default)]
+	pub flags:              u32, // https://docs.discord.sex/topics/read-state#read-state-flags
+	pub last_viewed:        Option<u32>, // days since the discord epoch
+}
+
+// versioned with the VERSIONED_READ_STATES capability, a plain list without it
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum ReadStates {
+	Versioned {
+		entries: Vec<ReadState>,
+		#[serde(default)]
+		partial: bool,
+		#[serde(default)]
+		version: u64,
+	},
+	Legacy(Vec<ReadState>),
+}
+
+impl ReadStates {
+	pub fn entries(self) -> Vec<ReadState> {
+		match self {
+			| ReadStates::Versioned { entries, .. } | ReadStates::Legacy(entries) => entries,
+		}
+	}
+}
+
+// https://docs.discord.sex/topics/read-state#acknowledge-message
+#[derive(Serialize)]
+pub struct AckRequest {
+	pub token: Option<String>, // whatever the last ack returned, discord uses it to tell acks from the client apart from automated ones
+}
+
+#[derive(Deserialize)]
+pub struct AckResponse {
+	pub token: Option<String>,
+}
+
+// https://docs.discord.sex/topics/read-state#acknowledge-messages
+#[derive(Serialize)]
+pub struct BulkAckRequest {
+	pub read_states: Vec<BulkAckEntry>,
+}
+
+#[derive(Serialize)]
+pub struct BulkAckEntry {
+	pub channel_id:      Snowflake,
+	pub message_id:      Snowflake,
+	pub read_state_type: u8,
+}
+
+// https://docs.discord.sex/topics/gateway-events#message-ack, sent when any of our sessions acks something
+#[derive(Deserialize, Debug)]
+pub struct MessageAck {
+	pub channel_id:    Snowflake,
+	pub message_id:    Snowflake,
+	#[serde(default)]
+	pub mention_count: u32,
+	pub ack_type:      Option<u8>,
+	#[serde(default)]
+	pub version:       u64,
+}
@@ -0,0 +1,43 @@
+use iso8601_timestamp::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use super::types::Snowflake;
+use super::user::user::PartialUser;
+
+// https://docs.discord.sex/resources/relationships#relationship-type
+pub const FRIEND: u8 = 1;
+pub const BLOCKED: u8 = 2;
+pub const INCOMING_REQUEST: u8 = 3;
+pub const OUTGOING_REQUEST: u8 = 4;
+
+// https://docs.discord.sex/resources/relationships#relationship-structure
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Relationship {
+	pub id:              Snowflake, // the other user's id
+	pub r#type:          u8,
+	pub user:            Option<PartialUser>, // missing in RELATIONSHIP_REMOVE/UPDATE
+	pub nickname:        Option<String>,
+	pub since:           Option<Timestamp>,
+	#[serde(default)]
+	pub is_spam_request: bool,
+}
+
+// https://docs.discord.sex/resources/relationships#send-friend-request
+#[derive(Serialize)]
+pub struct FriendRequest {
+	pub username:      String,
+	pub discriminator: Option<u16>, // only for legacy usernames
+}
+
+// https://docs.discord.sex/resources/relationships#create-relationship, no type accepts a request or sends one
+#[derive(Serialize, Default)]
+pub struct CreateRelationshipRequest {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub r#type: Option<u8>,
+}
+
+// https://docs.discord.sex/resources/relationships#modify-relationship
+#[derive(Serialize)]
+pub struct ModifyRelationshipRequest {
+	pub nickname: Option<String>, // null clears it
+}
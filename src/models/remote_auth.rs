@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::types::Snowflake;
+use crate::models::user::user::PartialUser;
+
+// https://docs.discord.sex/remote-authentication/desktop#gateway-events
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum RemoteAuthServerMessage {
+	Hello {
+		heartbeat_interval: u32,
+		timeout_ms:         u32,
+	},
+	NonceProof {
+		encrypted_nonce: String,
+	},
+	PendingRemoteInit {
+		fingerprint: String,
+	},
+	PendingTicket {
+		encrypted_user_payload: String,
+	},
+	PendingLogin {
+		ticket: String,
+	},
+	Cancel,
+	HeartbeatAck,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum RemoteAuthClientMessage {
+	Init { encoded_public_key: String },
+	NonceProof { proof: String },
+	Heartbeat,
+}
+
+// https://docs.discord.sex/remote-authentication/desktop#exchange-remote-auth-ticket
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RemoteAuthLoginRequest {
+	pub ticket: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct RemoteAuthLoginResponse {
+	pub encrypted_token: String,
+}
+
+/// whoever scanned the code, from the decrypted `user_id:discriminator:avatar:username` payload
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingUser {
+	pub id:            Snowflake,
+	pub discriminator: String,
+	pub avatar:        Option<String>,
+	pub username:      String,
+}
+
+impl PendingUser {
+	pub fn parse(payload: &str) -> Option<Self> {
+		// usernames can't contain colons, but splitn keeps this from breaking if they ever can
+		let mut parts = payload.splitn(4, ':');
+		let id = parts.next()?.parse().ok()?;
+		let discriminator = parts.next()?.to_string();
+		let avatar = Some(parts.next()?.to_string()).filter(|a| !a.is_empty() && a != "0");
+		let username = parts.next()?.to_string();
+
+		Some(PendingUser {
+			id,
+			discriminator,
+			avatar,
+			username,
+		})
+	}
+}
+
+impl From<PendingUser> for PartialUser {
+	fn from(user: PendingUser) -> Self {
+		PartialUser {
+			id:                     user.id,
+			username:               user.username,
+			discriminator:          Some(user.discriminator),
+			global_name:            None,
+			avatar:                 user.avatar,
+			avatar_decoration_data: None,
+			primary_guild:          None,
+			bot:                    false,
+			system:                 false,
+			public_flags:           0,
+			banner:                 None,
+			accent_color:           None,
+		}
+	}
+}
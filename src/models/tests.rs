@@ -0,0 +1,226 @@
+// real-world payload shapes for every endpoint/event the client decodes, ids and hashes swapped for fake ones
+
+use serde::de::DeserializeOwned;
+
+use super::auth::login::LoginResponse;
+use super::auth::sessions::AuthSessions;
+use super::chat::{Channel, Message};
+use super::error::{CaptchaRequired, ErrorResponse, RateLimited};
+use super::guild::GuildMember;
+use super::mfa::{MfaMethods, MfaRequired};
+use super::types::Snowflake;
+use super::user::profile::{UserProfile, badges};
+use super::user::settings::{GuildFolder, GuildFolders, PartialUserSettingsProto};
+use super::user::user::PrivateUser;
+use super::websocket::{GatewayRecieveEvent, Ready};
+
+fn decode<T: DeserializeOwned>(json: &str) -> T {
+	serde_json::from_str(json).unwrap_or_else(|e| panic!("failed to decode fixture: {}", e))
+}
+
+#[test]
+fn message_create() {
+	let message: Message = decode(include_str!("fixtures/message_create.json"));
+
+	assert_eq!(message.author.display_name(), "someone");
+	assert_eq!(message.guild_id, Some(Snowflake::new(1146545410358431855)));
+	assert!(message.member.as_ref().unwrap().user.is_none());
+	assert_eq!(message.mentions[0].display_name(), "cow");
+}
+
+#[test]
+fn message_history() {
+	let messages: Vec<Message> = decode(include_str!("fixtures/message_history.json"));
+
+	assert_eq!(messages.len(), 3);
+	assert!(messages[0].edited_timestamp.is_some());
+	assert_eq!(messages[1].r#type, 7);
+	assert!(messages[2].author.bot);
+}
+
+#[test]
+fn guild_channels() {
+	let channels: Vec<Channel> = decode(include_str!("fixtures/channels.json"));
+
+	assert_eq!(channels[0].r#type, 4);
+	assert_eq!(channels[1].name.as_deref(), Some("general"));
+	assert_eq!(channels[1].parent_id, Some(channels[0].id));
+	assert_eq!(channels[2].bitrate, Some(64000));
+	assert_eq!(channels[3].available_tags.len(), 1);
+}
+
+#[test]
+fn thread() {
+	let thread: Channel = decode(include_str!("fixtures/thread.json"));
+
+	assert!(!thread.thread_metadata.unwrap().archived);
+	assert_eq!(thread.member.unwrap().flags, 1);
+}
+
+#[test]
+fn private_channels() {
+	let channels: Vec<Channel> = decode(include_str!("fixtures/private_channels.json"));
+
+	assert_eq!(channels[0].recipients.len(), 1);
+	assert_eq!(channels[1].nicks[0].nick, "some1");
+	assert!(channels[2].recipients.is_empty());
+	assert_eq!(channels[2].recipient_ids.len(), 1);
+}
+
+#[test]
+fn guild_member() {
+	let member: GuildMember = decode(include_str!("fixtures/guild_member.json"));
+
+	assert_eq!(member.user.unwrap().username, "cowfr");
+	assert_eq!(member.roles.len(), 1);
+}
+
+#[test]
+fn current_user() {
+	let user: PrivateUser = decode(include_str!("fixtures/user_me.json"));
+
+	assert_eq!(user.user.bio, "moo");
+	assert!(user.mfa_enabled);
+	assert!(user.personal_connection_id.is_none());
+}
+
+#[test]
+fn login() {
+	let mfa: LoginResponse = decode(include_str!("fixtures/login_mfa.json"));
+	assert_eq!(mfa.mfa, Some(true));
+	assert!(mfa.token.is_none());
+	let methods: Vec<MfaMethods> = mfa.mfa_methods().into_iter().map(|m| m.r#type).collect();
+	assert_eq!(methods, [MfaMethods::totp, MfaMethods::backup]);
+
+	let success: LoginResponse = decode(include_str!("fixtures/login_success.json"));
+	assert_eq!(success.token.as_deref(), Some("mock.token.value"));
+}
+
+#[test]
+fn mfa_required() {
+	let required: MfaRequired = decode(include_str!("fixtures/mfa_required.json"));
+	let methods: Vec<MfaMethods> = required.mfa.methods.iter().map(|m| m.r#type).collect();
+
+	assert_eq!(required.code, 60003);
+	assert_eq!(methods, [MfaMethods::password, MfaMethods::totp, MfaMethods::webauthn]);
+	assert_eq!(required.mfa.methods[1].backup_codes_allowed, Some(true));
+	assert!(required.mfa.methods[2].challenge.is_some());
+}
+
+#[test]
+fn user_profile() {
+	let profile: UserProfile = decode(include_str!("fixtures/user_profile.json"));
+
+	// the guild bio wins, the empty guild pronouns fall back to the user ones
+	assert_eq!(profile.bio(), "moo but in a guild");
+	assert_eq!(profile.pronouns(), "it/its");
+	assert_eq!(profile.accent_color(), Some(0xff0000));
+	assert_eq!(profile.guild_member.unwrap().nick.as_deref(), Some("cow in a server"));
+	assert_eq!(profile.mutual_guilds.unwrap().len(), 1);
+	assert_eq!(profile.connected_accounts[0].r#type, "github");
+	assert_eq!(badges(profile.user.public_flags), vec!["HypeSquad Bravery", "Active Developer"]);
+}
+
+#[test]
+fn register_field_errors() {
+	let error: ErrorResponse = decode(include_str!("fixtures/register_invalid.json"));
+	let fields = error.field_errors();
+
+	assert_eq!(error.code, 50035);
+	assert_eq!(fields["email"], "Email is already registered.");
+	assert_eq!(fields["date_of_birth"], "You need to be 13 or older in order to use Discord.");
+}
+
+#[test]
+fn captcha_required() {
+	let captcha: CaptchaRequired = decode(include_str!("fixtures/captcha_required.json"));
+
+	assert_eq!(captcha.captcha_service, "hcaptcha");
+	assert_eq!(captcha.captcha_rqtoken.as_deref(), Some("IlFkbVU2T2ciLg"));
+	// not an error response, so this is how the two get told apart
+	assert!(serde_json::from_str::<ErrorResponse>(include_str!("fixtures/captcha_required.json")).is_err());
+}
+
+#[test]
+fn rate_limited() {
+	let limited: RateLimited = decode(include_str!("fixtures/rate_limited.json"));
+
+	assert_eq!(limited.retry_after, 0.05);
+	assert!(!limited.global);
+	assert_eq!(limited.code, None);
+}
+
+#[test]
+fn gateway_hello() {
+	let hello: GatewayRecieveEvent = decode(include_str!("fixtures/hello.json"));
+
+	assert_eq!(hello.op, 10);
+	assert_eq!(hello.d["heartbeat_interval"], 41250);
+}
+
+#[test]
+fn gateway_ready() {
+	let event: GatewayRecieveEvent = decode(include_str!("fixtures/ready.json"));
+	let ready: Ready = serde_json::from_value(event.d).unwrap();
+
+	assert_eq!(ready.user.user.username, "cowfr");
+	assert_eq!(ready.guilds[0].name(), "cowcord dev");
+	assert_eq!(ready.guilds[0].channels.len(), 2);
+	assert!(ready.guilds[1].unavailable);
+	assert_eq!(ready.private_channels[0].recipient_ids.len(), 1);
+	assert_eq!(ready.auth_session_id_hash.as_deref(), Some("bW9ja19oYXNo"));
+}
+
+#[test]
+fn auth_sessions() {
+	let sessions: AuthSessions = decode(include_str!("fixtures/auth_sessions.json"));
+
+	assert_eq!(sessions.user_sessions.len(), 2);
+	assert_eq!(sessions.user_sessions[0].client_info.describe(), "Linux · Firefox");
+	assert_eq!(sessions.user_sessions[1].client_info.describe(), "Android");
+	assert_eq!(sessions.user_sessions[1].client_info.location, None);
+}
+
+#[test]
+fn guild_folders_proto_round_trip() {
+	let folders = vec![
+		GuildFolder {
+			color:     None,
+			guild_ids: vec![Snowflake::new(1)],
+			id:        None,
+			name:      None,
+		},
+		GuildFolder {
+			color:     Some(0x5865f2),
+			guild_ids: vec![Snowflake::new(2), Snowflake::new(3)],
+			id:        Some(7),
+			name:      Some("cows".to_string()),
+		},
+	];
+	let settings = PartialUserSettingsProto {
+		guild_folders: Some(GuildFolders {
+			folders:         folders.iter().cloned().map(Into::into).collect(),
+			guild_positions: Vec::new(),
+		}),
+	};
+
+	let decoded = PartialUserSettingsProto::from_base64(&settings.to_base64()).unwrap();
+	let decoded: Vec<GuildFolder> = decoded.guild_folders.unwrap().folders.into_iter().map(Into::into).collect();
+
+	assert_eq!(decoded, folders);
+}
+
+#[test]
+fn offline_cache_round_trip() {
+	fn round_trip<T: serde::Serialize + DeserializeOwned + PartialEq + std::fmt::Debug>(value: T) {
+		let json = serde_json::to_string(&value).unwrap();
+		assert_eq!(serde_json::from_str::<T>(&json).unwrap(), value);
+	}
+
+	let event: GatewayRecieveEvent = decode(include_str!("fixtures/ready.json"));
+	let ready: Ready = serde_json::from_value(event.d).unwrap();
+	round_trip(ready.guilds);
+	round_trip(ready.private_channels);
+	round_trip::<Vec<Message>>(decode(include_str!("fixtures/message_history.json")));
+	round_trip::<Channel>(decode(include_str!("fixtures/thread.json")));
+}
@@ -1,15 +1,16 @@
 use std::num::ParseIntError;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicU16, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use serde::{Deserialize, Deserializer, Serialize};
 
 const DISCORD_EPOCH: u64 = 1420070400000;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(transparent)]
-pub struct Snowflake(#[serde(deserialize_with = "deserialize_snowflake_from_string")] u64);
+pub struct Snowflake(#[serde(deserialize_with = "deserialize_snowflake")] u64);
 
 impl Snowflake {
 	pub fn new(id: u64) -> Self { Snowflake(id) }
@@ -19,11 +20,7 @@ impl Snowflake {
 		process_id: u8,
 		increment: &AtomicU16,
 	) -> Self {
-		let timestamp = SystemTime::now()
-			.duration_since(UNIX_EPOCH)
-			.unwrap()
-			.as_millis() as u64
-			- DISCORD_EPOCH;
+		let timestamp = now_ms() - DISCORD_EPOCH;
 
 		let worker_id = worker_id & 0b11111;
 		let process_id = process_id & 0b11111;
@@ -48,6 +45,18 @@ impl Snowflake {
 	pub fn raw(&self) -> u64 { self.0 }
 }
 
+// std's clock panics on wasm32-unknown-unknown
+#[cfg(target_arch = "wasm32")]
+pub fn now_ms() -> u64 { js_sys::Date::now() as u64 }
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now_ms() -> u64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap()
+		.as_millis() as u64
+}
+
 impl From<Snowflake> for u64 {
 	fn from(snowflake: Snowflake) -> Self { snowflake.0 }
 }
@@ -74,10 +83,20 @@ impl std::fmt::Display for Snowflake {
 	}
 }
 
-fn deserialize_snowflake_from_string<'de, D>(deserializer: D) -> Result<u64, D::Error>
+// almost always a string, but a few places (eg. non channel read states) send plain integers
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StringOrInteger {
+	String(String),
+	Integer(u64),
+}
+
+fn deserialize_snowflake<'de, D>(deserializer: D) -> Result<u64, D::Error>
 where
 	D: Deserializer<'de>,
 {
-	let s = String::deserialize(deserializer)?;
-	s.parse::<u64>().map_err(serde::de::Error::custom)
+	match StringOrInteger::deserialize(deserializer)? {
+		| StringOrInteger::String(s) => s.parse::<u64>().map_err(serde::de::Error::custom),
+		| StringOrInteger::Integer(id) => Ok(id),
+	}
 }
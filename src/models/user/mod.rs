@@ -1,2 +1,4 @@
+pub mod profile;
 pub mod settings;
+#[allow(clippy::module_inception)]
 pub mod user;
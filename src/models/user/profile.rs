@@ -0,0 +1,124 @@
+use iso8601_timestamp::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::models::guild::GuildMember;
+use crate::models::types::Snowflake;
+use crate::models::user::user::{PartialUser, User};
+
+// https://docs.discord.sex/resources/user#get-user-profile
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct UserProfileRequest {
+	pub with_mutual_guilds:  bool,
+	pub with_mutual_friends: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub guild_id:            Option<Snowflake>,
+}
+
+// https://docs.discord.sex/resources/user#profile-structure
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct UserProfile {
+	pub user:                 User,
+	pub user_profile:         Option<ProfileMetadata>,
+	pub guild_member:         Option<GuildMember>,
+	pub guild_member_profile: Option<ProfileMetadata>,
+	#[serde(default)]
+	pub connected_accounts:   Vec<ConnectedAccount>,
+	pub premium_since:        Option<Timestamp>,
+	pub mutual_guilds:        Option<Vec<MutualGuild>>, // only with with_mutual_guilds
+	pub mutual_friends:       Option<Vec<PartialUser>>, // only with with_mutual_friends
+}
+
+impl UserProfile {
+	/// the guild profile overrides the user one field by field
+	pub fn bio(&self) -> &str {
+		[&self.guild_member_profile, &self.user_profile]
+			.into_iter()
+			.flatten()
+			.map(|p| p.bio.as_str())
+			.find(|bio| !bio.is_empty())
+			.unwrap_or(&self.user.bio)
+	}
+
+	pub fn pronouns(&self) -> &str {
+		[&self.guild_member_profile, &self.user_profile]
+			.into_iter()
+			.flatten()
+			.map(|p| p.pronouns.as_str())
+			.find(|pronouns| !pronouns.is_empty())
+			.unwrap_or(&self.user.pronouns)
+	}
+
+	pub fn accent_color(&self) -> Option<u32> {
+		[&self.guild_member_profile, &self.user_profile]
+			.into_iter()
+			.flatten()
+			.find_map(|p| p.accent_color)
+			.or(self.user.accent_color)
+	}
+}
+
+// https://docs.discord.sex/resources/user#profile-metadata-object
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ProfileMetadata {
+	pub guild_id:     Option<Snowflake>,
+	#[serde(default)]
+	pub pronouns:     String,
+	#[serde(default)]
+	pub bio:          String,
+	pub banner:       Option<String>,
+	pub accent_color: Option<u32>,
+	pub theme_colors: Option<Vec<u32>>,
+}
+
+// https://docs.discord.sex/resources/user#profile-connected-account-structure
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ConnectedAccount {
+	pub r#type:   String,
+	pub id:       String,
+	pub name:     String,
+	#[serde(default)]
+	pub verified: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct MutualGuild {
+	pub id:   Snowflake,
+	pub nick: Option<String>,
+}
+
+// https://docs.discord.sex/resources/user#user-note-object
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct UserNote {
+	pub note: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct UpdateNoteRequest {
+	pub note: String,
+}
+
+// https://docs.discord.sex/resources/user#user-flags, only the ones that show up as badges
+const BADGES: [(u64, &str); 13] = [
+	(1 << 0, "Discord Staff"),
+	(1 << 1, "Partnered Server Owner"),
+	(1 << 2, "HypeSquad Events"),
+	(1 << 3, "Discord Bug Hunter"),
+	(1 << 6, "HypeSquad Bravery"),
+	(1 << 7, "HypeSquad Brilliance"),
+	(1 << 8, "HypeSquad Balance"),
+	(1 << 9, "Early Supporter"),
+	(1 << 14, "Discord Bug Hunter"),
+	(1 << 17, "Early Verified Bot Developer"),
+	(1 << 18, "Moderator Programs Alumni"),
+	(1 << 22, "Active Developer"),
+	(1 << 16, "Verified Bot"),
+];
+
+/// badge names for `public_flags`, in the order discord shows them
+pub fn badges(public_flags: u64) -> Vec<&'static str> {
+	BADGES
+		.iter()
+		.filter(|(flag, _)| public_flags & flag != 0)
+		.map(|(_, name)| *name)
+		.collect()
+}
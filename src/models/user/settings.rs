@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
 use iso8601_timestamp::Timestamp;
-use serde::Deserialize;
+use prost::Message;
+use serde::{Deserialize, Serialize};
 
 use super::user::CustomStatusProto;
 use crate::models::app::Versions;
@@ -9,7 +12,9 @@ use crate::models::chat::DmSpamFilterV2;
 use crate::models::guild::AllGuildSettings;
 use crate::models::types::Snowflake;
 
-#[derive(Deserialize)]
+// sent as `{}` or partially filled in READY depending on capabilities, so default everything
+#[derive(Deserialize, Default)]
+#[serde(default)]
 pub struct UserSettings {
 	pub activity_restricted_guild_ids:              Vec<Snowflake>,
 	pub activity_joining_restricted_guild_ids:      Vec<Snowflake>,
@@ -51,22 +56,49 @@ pub struct UserSettings {
 	pub view_nsfw_guilds:                           bool,
 }
 
-#[derive(Deserialize)]
+// a folder without an id is a single guild outside of any folder
+#[derive(Deserialize, Serialize, Default, Debug, Clone, PartialEq)]
 pub struct GuildFolder {
 	pub color:     Option<u32>,
 	pub guild_ids: Vec<Snowflake>,
-	pub id:        Option<u32>,
+	pub id:        Option<i64>,
 	pub name:      Option<String>,
 }
 
-#[derive(Deserialize)]
+impl GuildFolder {
+	pub fn is_folder(&self) -> bool { self.id.is_some() }
+}
+
+impl From<GuildFolderProto> for GuildFolder {
+	fn from(folder: GuildFolderProto) -> Self {
+		GuildFolder {
+			color:     folder.color.map(|c| c.value as u32),
+			guild_ids: folder.guild_ids.into_iter().map(Snowflake::new).collect(),
+			id:        folder.id.map(|id| id.value),
+			name:      folder.name.map(|n| n.value),
+		}
+	}
+}
+
+impl From<GuildFolder> for GuildFolderProto {
+	fn from(folder: GuildFolder) -> Self {
+		GuildFolderProto {
+			guild_ids: folder.guild_ids.into_iter().map(u64::from).collect(),
+			id:        folder.id.map(|value| Int64Value { value }),
+			name:      folder.name.map(|value| StringValue { value }),
+			color:     folder.color.map(|c| UInt64Value { value: c as u64 }),
+		}
+	}
+}
+
+#[derive(Deserialize, Default)]
 pub struct FriendSourceFlags {
 	pub all:            bool,
 	pub mutual_friends: bool,
 	pub mutual_guilds:  bool,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 pub struct CustomStatus {
 	pub text:       Option<String>,
 	pub emoji_id:   Option<Snowflake>,
@@ -204,19 +236,66 @@ pub enum FavoriteChannelType {
 	CATEGORY,
 }
 
-#[derive(Deserialize)]
+// the settings proto with only the fields we read, prost skips over everything else.
+// also what gets sent when updating, the api merges in whichever fields are set
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PartialUserSettingsProto {
+	#[prost(message, optional, tag = "14")]
+	pub guild_folders: Option<GuildFolders>,
+}
+
+impl PartialUserSettingsProto {
+	pub fn from_base64(encoded: &str) -> Option<Self> {
+		let bytes = BASE64_STANDARD.decode(encoded).ok()?;
+		Self::decode(bytes.as_slice()).ok()
+	}
+
+	pub fn to_base64(&self) -> String { BASE64_STANDARD.encode(self.encode_to_vec()) }
+}
+
+// https://docs.discord.sex/resources/user-settings#update-user-settings-proto
+#[derive(Serialize)]
+pub struct UpdateSettingsProtoRequest {
+	pub settings: String, // base 64 encoded
+}
+
+#[derive(Deserialize, Clone, PartialEq, prost::Message)]
 pub struct GuildFolders {
+	#[prost(message, repeated, tag = "1")]
 	pub folders:         Vec<GuildFolderProto>,
-	#[deprecated]
-	pub guild_positions: Vec<u64>,
+	#[prost(fixed64, repeated, tag = "2")]
+	pub guild_positions: Vec<u64>, // deprecated
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone, PartialEq, prost::Message)]
 pub struct GuildFolderProto {
+	#[prost(fixed64, repeated, tag = "1")]
 	pub guild_ids: Vec<u64>,
-	pub id:        Option<i64>,
-	pub name:      Option<String>,
-	pub color:     Option<u64>,
+	#[prost(message, optional, tag = "2")]
+	pub id:        Option<Int64Value>,
+	#[prost(message, optional, tag = "3")]
+	pub name:      Option<StringValue>,
+	#[prost(message, optional, tag = "4")]
+	pub color:     Option<UInt64Value>,
+}
+
+// google.protobuf wrapper types
+#[derive(Deserialize, Clone, PartialEq, prost::Message)]
+pub struct Int64Value {
+	#[prost(int64, tag = "1")]
+	pub value: i64,
+}
+
+#[derive(Deserialize, Clone, PartialEq, prost::Message)]
+pub struct UInt64Value {
+	#[prost(uint64, tag = "1")]
+	pub value: u64,
+}
+
+#[derive(Deserialize, Clone, PartialEq, prost::Message)]
+pub struct StringValue {
+	#[prost(string, tag = "1")]
+	pub value: String,
 }
 
 #[derive(Deserialize)]
@@ -372,6 +451,67 @@ pub struct NotificationSettings {
 	pub flags: u8, // https://docs.discord.sex/resources/user-settings#notification-settings-flags
 }
 
+// https://docs.discord.sex/resources/user-settings#user-guild-settings-structure
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct UserGuildSettings {
+	pub guild_id:          Option<Snowflake>, // null for the dm settings
+	#[serde(default)]
+	pub muted:             bool,
+	pub mute_config:       Option<MuteConfig>,
+	#[serde(default)]
+	pub channel_overrides: Vec<ChannelOverride>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ChannelOverride {
+	pub channel_id:  Snowflake,
+	#[serde(default)]
+	pub muted:       bool,
+	pub mute_config: Option<MuteConfig>,
+	#[serde(default)]
+	pub collapsed:   bool,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct MuteConfig {
+	pub end_time:             Option<Timestamp>, // null mutes forever
+	pub selected_time_window: Option<i64>,
+}
+
+/// mutes with an end time stop counting once it's passed, discord doesn't send an update for that
+pub fn is_muted(
+	muted: bool,
+	mute_config: Option<&MuteConfig>,
+	now_ms: i64,
+) -> bool {
+	muted
+		&& mute_config
+			.and_then(|c| c.end_time)
+			.is_none_or(|end| end.duration_since(Timestamp::UNIX_EPOCH).whole_milliseconds() > now_ms as i128)
+}
+
+// same versioning as read states
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum UserGuildSettingsEntries {
+	Versioned {
+		entries: Vec<UserGuildSettings>,
+		#[serde(default)]
+		partial: bool,
+		#[serde(default)]
+		version: u64,
+	},
+	Legacy(Vec<UserGuildSettings>),
+}
+
+impl UserGuildSettingsEntries {
+	pub fn entries(self) -> Vec<UserGuildSettings> {
+		match self {
+			| UserGuildSettingsEntries::Versioned { entries, .. } | UserGuildSettingsEntries::Legacy(entries) => entries,
+		}
+	}
+}
+
 #[derive(Deserialize)]
 pub struct NotificationSettingsProto {
 	pub show_in_app_notifications:           Option<bool>,
@@ -1,51 +1,122 @@
 use iso8601_timestamp::Timestamp;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::models::guild::GuildMember;
 use crate::models::types::Snowflake;
 
-#[derive(Deserialize, Debug)]
+// https://docs.discord.sex/resources/user#partial-user-structure
+// what you get for message authors, mentions, guild members, dm recipients etc.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct PartialUser {
+	pub id:                     Snowflake,
+	pub username:               String,
+	pub discriminator:          Option<String>,
+	pub global_name:            Option<String>,
+	pub avatar:                 Option<String>,
+	pub avatar_decoration_data: Option<AvatarDecorationData>,
+	pub primary_guild:          Option<PrimaryGuild>,
+	#[serde(default)]
+	pub bot:                    bool,
+	#[serde(default)]
+	pub system:                 bool,
+	#[serde(default)]
+	pub public_flags:           u64,
+	pub banner:                 Option<String>,
+	pub accent_color:           Option<u32>,
+}
+
+impl PartialUser {
+	pub fn display_name(&self) -> &str { self.global_name.as_deref().unwrap_or(&self.username) }
+}
+
+// full user object, eg. from /users/{user.id}
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct User {
-	pub id:                      Snowflake,
-	pub username:                String,
-	pub global_name:             Option<String>,
-	pub avatar:                  Option<String>,
-	pub avatar_decoration_data:  Option<AvatarDecorationData>,
-	pub primary_guild:           Option<PrimaryGuild>,
-	pub linked_users:            Vec<LinkedUser>,
-	pub bot:                     bool,
-	pub system:                  bool,
+	pub id:                     Snowflake,
+	pub username:               String,
+	pub discriminator:          Option<String>,
+	pub global_name:            Option<String>,
+	pub avatar:                 Option<String>,
+	pub avatar_decoration_data: Option<AvatarDecorationData>,
+	pub primary_guild:          Option<PrimaryGuild>,
+	#[serde(default)]
+	pub linked_users:           Vec<LinkedUser>,
+	#[serde(default)]
+	pub bot:                    bool,
+	#[serde(default)]
+	pub system:                 bool,
+	#[serde(default)]
+	pub pronouns:               String,
+	#[serde(default)]
+	pub bio:                    String,
+	pub banner:                 Option<String>,
+	pub accent_color:           Option<u32>,
+	#[serde(default)]
+	pub public_flags:           u64,
+	#[serde(default)]
+	pub premium_type:           u8, // https://docs.discord.sex/resources/user#premium-type
+}
+
+impl From<User> for PartialUser {
+	fn from(user: User) -> Self {
+		PartialUser {
+			id:                     user.id,
+			username:               user.username,
+			discriminator:          user.discriminator,
+			global_name:            user.global_name,
+			avatar:                 user.avatar,
+			avatar_decoration_data: user.avatar_decoration_data,
+			primary_guild:          user.primary_guild,
+			bot:                    user.bot,
+			system:                 user.system,
+			public_flags:           user.public_flags,
+			banner:                 user.banner,
+			accent_color:           user.accent_color,
+		}
+	}
+}
+
+// the current user, eg. from /users/@me or the READY event
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct PrivateUser {
+	#[serde(flatten)]
+	pub user:                    User,
+	#[serde(default)]
 	pub mfa_enabled:             bool,
 	pub nsfw_allowed:            Option<bool>,
+	#[serde(default)]
 	pub age_verification_status: u8, // https://docs.discord.sex/resources/user#age-verification-status
-	pub pronouns:                String,
-	pub bio:                     String,
-	pub banner:                  Option<String>,
-	pub accent_color:            Option<u32>,
+	#[serde(default)]
 	pub locale:                  String, // https://docs.discord.sex/reference#locales
+	#[serde(default)]
 	pub verified:                bool,
 	pub email:                   Option<String>,
 	pub phone:                   Option<String>,
-	pub premium_type:            u8, // https://docs.discord.sex/resources/user#premium-type
-	pub personal_connection_id:  Snowflake,
+	pub personal_connection_id:  Option<Snowflake>,
+	#[serde(default)]
 	pub flags:                   u64, // https://docs.discord.sex/resources/user#user-flags
-	pub public_flags:            u64,
+	#[serde(default)]
 	pub purchased_flags:         u8, // https://docs.discord.sex/resources/user#purchased-flags
+	#[serde(default)]
 	pub premium_flags:           u8, // https://docs.discord.sex/resources/user#premium-usage-flags
+	#[serde(default)]
 	pub desktop:                 bool,
+	#[serde(default)]
 	pub mobile:                  bool,
+	#[serde(default)]
 	pub has_bounced_email:       bool,
+	#[serde(default)]
 	pub authenticator_types:     Vec<u8>, // https://docs.discord.sex/resources/user#authenticator-type
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct AvatarDecorationData {
 	pub asset:      String,
-	pub sku_id:     String,
+	pub sku_id:     Option<String>,
 	pub expires_at: Option<usize>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct PrimaryGuild {
 	pub identity_enabled:  Option<bool>,
 	pub identity_guild_id: Option<String>,
@@ -53,7 +124,7 @@ pub struct PrimaryGuild {
 	pub badge:             Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct LinkedUser {
 	pub created_at:   Timestamp,
 	pub updated_at:   Timestamp,
@@ -63,24 +134,26 @@ pub struct LinkedUser {
 	pub user_id:      String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct Nick {
 	pub id:   Snowflake,
 	pub nick: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct ThreadMember {
-	pub id:             Snowflake,
-	pub user_id:        Snowflake,
+	pub id:             Option<Snowflake>,
+	pub user_id:        Option<Snowflake>,
 	pub join_timestamp: Timestamp,
+	#[serde(default)]
 	pub flags:          u8, // https://docs.discord.sex/resources/channel#thread-member-flags
+	#[serde(default)]
 	pub muted:          bool,
-	pub mute_config:    MuteConfig,
-	pub member:         GuildMember,
+	pub mute_config:    Option<MuteConfig>,
+	pub member:         Option<Box<GuildMember>>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct MuteConfig {
 	pub end_time:             Option<Timestamp>,
 	pub selected_time_window: isize,
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+use super::guild::GuildMember;
+use super::types::Snowflake;
+
+// https://docs.discord.sex/resources/voice#voice-state-structure
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct VoiceState {
+	pub guild_id:    Option<Snowflake>, // missing in the guild's own voice_states
+	pub channel_id:  Option<Snowflake>, // null when leaving
+	pub user_id:     Snowflake,
+	pub member:      Option<GuildMember>,
+	pub session_id:  Option<String>,
+	#[serde(default)]
+	pub deaf:        bool,
+	#[serde(default)]
+	pub mute:        bool,
+	#[serde(default)]
+	pub self_deaf:   bool,
+	#[serde(default)]
+	pub self_mute:   bool,
+	#[serde(default)]
+	pub self_video:  bool,
+	#[serde(default)]
+	pub self_stream: bool,
+}
@@ -1,7 +1,12 @@
 use serde::Deserialize;
 
-use super::user::settings::{NotificationSettings, UserSettings};
-use super::user::user::User;
+use super::chat::Channel;
+use super::guild::{Guild, GuildMember};
+use super::presence::Presence;
+use super::read_state::ReadStates;
+use super::relationship::Relationship;
+use super::user::settings::{NotificationSettings, UserGuildSettingsEntries, UserSettings};
+use super::user::user::{PartialUser, PrivateUser};
 
 #[derive(Deserialize)]
 pub struct Gateway {
@@ -16,14 +21,40 @@ pub struct GatewayRecieveEvent {
 	pub t:  Option<String>,
 }
 
+// https://docs.discord.sex/topics/gateway-events#ready-structure
 #[derive(Deserialize)]
 pub struct Ready {
-	_trace:                Vec<String>,
-	v:                     u8,
-	user:                  User,
+	#[serde(default)]
+	pub _trace:                Vec<String>,
+	pub v:                     u8,
+	pub user:                  PrivateUser,
+	pub session_id:            String,
+	pub auth_session_id_hash:  Option<String>,
+	pub resume_gateway_url:    Option<String>,
+	#[serde(default)]
+	pub users:                 Vec<PartialUser>,
+	#[serde(default)]
+	pub guilds:                Vec<Guild>,
+	#[serde(default)]
+	pub private_channels:      Vec<Channel>,
 	#[deprecated]
-	user_settings:         UserSettings,
-	user_settings_proto:   String, // base 64 encoded, todo: parsing (i already tried it please kill me that was awful)
-	notification_settings: NotificationSettings,
-	// todoL finish https://docs.discord.sex/topics/gateway-events#ready-structure
+	pub user_settings:         Option<UserSettings>,
+	pub user_settings_proto:   Option<String>, // base 64 encoded, todo: parsing (i already tried it please kill me that was awful)
+	pub notification_settings: Option<NotificationSettings>,
+	pub read_state:            Option<ReadStates>,
+	pub user_guild_settings:   Option<UserGuildSettingsEntries>,
+	#[serde(default)]
+	pub merged_members:        Vec<Vec<GuildMember>>, // our own member in each guild, same order as `guilds`
+	#[serde(default)]
+	pub relationships:         Vec<Relationship>,
+	pub merged_presences:      Option<MergedPresences>,
+	// todo: finish https://docs.discord.sex/topics/gateway-events#ready-structure
+}
+
+#[derive(Deserialize)]
+pub struct MergedPresences {
+	#[serde(default)]
+	pub friends: Vec<Presence>,
+	#[serde(default)]
+	pub guilds:  Vec<Vec<Presence>>, // same order as `guilds`
 }
@@ -0,0 +1,201 @@
+// what the last session knew, saved per account so the client has something to show before the gateway is up.
+// READY and the first history fetch of a channel replace all of it
+
+use std::collections::HashSet;
+use std::error::Error;
+use std::sync::LazyLock;
+
+use dioxus::prelude::*;
+use futures_util::lock::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::models::chat::{Channel, Message};
+use crate::models::guild::Guild;
+use crate::models::types::Snowflake;
+use crate::models::user::user::PartialUser;
+use crate::store::{GUILDS, MESSAGES, PRIVATE_CHANNELS, USERS};
+use crate::utils::platform;
+use crate::utils::storage::{self, Storage};
+
+const STATE_KEY: &str = "cache:state";
+// channels with cached messages, most recently saved first
+const CHANNELS_KEY: &str = "cache:channels";
+
+const MESSAGES_PER_CHANNEL: usize = 50;
+const MAX_CHANNELS: usize = 50;
+const MAX_USERS: usize = 500;
+
+// held for every write, two saves reading the index at once would each drop the other's channel from it
+static WRITING: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+#[derive(Serialize, Deserialize, Default)]
+struct CachedState {
+	me:               Option<PartialUser>,
+	guilds:           Vec<Guild>,
+	private_channels: Vec<Channel>,
+	users:            Vec<PartialUser>, // dm recipients, everyone else comes with their messages
+}
+
+fn messages_key(channel_id: Snowflake) -> String { format!("cache:messages:{}", channel_id) }
+
+/// moves a channel to the front of the index, returns whatever fell off the end
+fn touch(
+	index: &mut Vec<Snowflake>,
+	channel_id: Snowflake,
+	max: usize,
+) -> Vec<Snowflake> {
+	index.retain(|&id| id != channel_id);
+	index.insert(0, channel_id);
+	index.split_off(index.len().min(max))
+}
+
+/// fills the stores from the cache, before connecting so READY can replace it
+pub async fn hydrate() {
+	let storage = storage::account();
+	let Some(state) = storage.load::<CachedState>(STATE_KEY).await else {
+		return;
+	};
+
+	{
+		let mut users = USERS.write();
+		users.extend(state.users);
+		if let Some(me) = state.me {
+			users.set_current(me);
+		}
+	}
+	GUILDS.write().set(state.guilds);
+	PRIVATE_CHANNELS.write().set(state.private_channels);
+
+	let index = storage.load::<Vec<Snowflake>>(CHANNELS_KEY).await.unwrap_or_default();
+	let keys: Vec<String> = index.iter().copied().map(messages_key).collect();
+	let cached = storage.load_many::<Vec<Message>>(&keys).await;
+
+	for (channel_id, messages) in index.into_iter().zip(cached) {
+		if let Some(messages) = messages {
+			USERS.write().extend_from_messages(&messages);
+			MESSAGES.write().set_cached(channel_id, messages);
+		}
+	}
+}
+
+/// saves guilds, dms and their users, after READY
+pub async fn save_state() {
+	let state = {
+		let users = USERS.peek();
+		let private_channels = PRIVATE_CHANNELS.peek();
+		let mut seen = HashSet::new();
+		let recipients: Vec<Snowflake> = private_channels
+			.sorted()
+			.into_iter()
+			.flat_map(|c| c.recipient_ids.iter().copied())
+			.filter(|&id| seen.insert(id))
+			.collect();
+
+		CachedState {
+			me:               users.current().cloned(),
+			guilds:           GUILDS.peek().guilds().to_vec(),
+			private_channels: private_channels.sorted().into_iter().cloned().collect(),
+			users:            recipients
+				.into_iter()
+				.filter_map(|id| users.user(id).cloned())
+				.take(MAX_USERS)
+				.collect(),
+		}
+	};
+
+	let _writing = WRITING.lock().await;
+	if let Err(e) = storage::account().store(STATE_KEY, &state).await {
+		platform::error(&format!("Failed to cache state: {}", e));
+	}
+}
+
+/// saves the bottom of a channel, evicting the least recently saved channel if there's too many
+pub async fn save_channel(channel_id: Snowflake) {
+	let Some(messages) = MESSAGES
+		.peek()
+		.latest(channel_id, MESSAGES_PER_CHANNEL)
+		.map(<[Message]>::to_vec)
+		.filter(|m| !m.is_empty())
+	else {
+		return;
+	};
+
+	if let Err(e) = store_channel(channel_id, messages).await {
+		platform::error(&format!("Failed to cache messages: {}", e));
+	}
+}
+
+async fn store_channel(
+	channel_id: Snowflake,
+	messages: Vec<Message>,
+) -> Result<(), Box<dyn Error>> {
+	let _writing = WRITING.lock().await;
+	let storage = storage::account();
+	storage.store(&messages_key(channel_id), &messages).await?;
+
+	let mut index = storage.load::<Vec<Snowflake>>(CHANNELS_KEY).await.unwrap_or_default();
+	let evicted = touch(&mut index, channel_id, MAX_CHANNELS);
+	storage.store(CHANNELS_KEY, &index).await?;
+	for channel_id in evicted {
+		storage.delete(&messages_key(channel_id)).await?;
+	}
+
+	Ok(())
+}
+
+/// forgets everything cached for the current account
+pub async fn clear() {
+	let _writing = WRITING.lock().await;
+	let storage = storage::account();
+	let index = storage.load::<Vec<Snowflake>>(CHANNELS_KEY).await.unwrap_or_default();
+
+	let keys = index
+		.into_iter()
+		.map(messages_key)
+		.chain([CHANNELS_KEY.to_string(), STATE_KEY.to_string()]);
+	for key in keys {
+		if let Err(e) = storage.delete(&key).await {
+			platform::error(&format!("Failed to clear cache: {}", e));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn least_recently_saved_channels_get_evicted() {
+		let mut index = Vec::new();
+		for id in 1..=3 {
+			assert!(touch(&mut index, Snowflake::new(id), 3).is_empty());
+		}
+
+		// saving one again moves it back to the front
+		assert!(touch(&mut index, Snowflake::new(1), 3).is_empty());
+		assert_eq!(index, [1, 3, 2].map(Snowflake::new));
+
+		assert_eq!(touch(&mut index, Snowflake::new(4), 3), vec![Snowflake::new(2)]);
+		assert_eq!(index, [4, 1, 3].map(Snowflake::new));
+	}
+
+	#[tokio::test]
+	async fn saved_channels_get_hydrated() {
+		let message: Message = serde_json::from_str(include_str!("../models/fixtures/message_create.json")).unwrap();
+		let channels = [1, 2, 3].map(Snowflake::new);
+
+		crate::testing::in_runtime(async {
+			storage::account().store(STATE_KEY, &CachedState::default()).await.unwrap();
+			// saved at the same time, none of them should go missing from the index
+			futures_util::future::join_all(channels.map(|id| store_channel(id, vec![message.clone()]))).await;
+			let index = storage::account().load::<Vec<Snowflake>>(CHANNELS_KEY).await.unwrap();
+			assert_eq!(index.len(), channels.len());
+
+			hydrate().await;
+			for id in channels {
+				assert!(MESSAGES.peek().channel(id).is_some_and(|c| c.cached));
+			}
+		})
+		.await;
+	}
+}
@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use crate::models::guild::GuildMember;
+use crate::models::types::Snowflake;
+
+// members we've seen per guild, from member list syncs, op 8 chunks and messages.
+// never the full member list of a big guild, so lookups that miss should fall back to op 8
+#[derive(Default)]
+pub struct GuildMemberStore {
+	guilds: HashMap<Snowflake, HashMap<Snowflake, GuildMember>>,
+}
+
+impl GuildMemberStore {
+	pub fn member(
+		&self,
+		guild_id: Snowflake,
+		user_id: Snowflake,
+	) -> Option<&GuildMember> {
+		self.guilds.get(&guild_id)?.get(&user_id)
+	}
+
+	/// members without a user can't be keyed and are dropped
+	pub fn insert(
+		&mut self,
+		guild_id: Snowflake,
+		member: GuildMember,
+	) {
+		if let Some(user_id) = member.user.as_ref().map(|u| u.id) {
+			self.guilds.entry(guild_id).or_default().insert(user_id, member);
+		}
+	}
+
+	pub fn extend(
+		&mut self,
+		guild_id: Snowflake,
+		members: impl IntoIterator<Item = GuildMember>,
+	) {
+		for member in members {
+			self.insert(guild_id, member);
+		}
+	}
+
+	/// members whose nick, display name or username starts with `query`, case insensitive
+	pub fn search(
+		&self,
+		guild_id: Snowflake,
+		query: &str,
+		limit: usize,
+	) -> Vec<&GuildMember> {
+		let query = query.to_lowercase();
+		let Some(members) = self.guilds.get(&guild_id) else {
+			return Vec::new();
+		};
+
+		let mut found: Vec<&GuildMember> = members
+			.values()
+			.filter(|member| {
+				let Some(user) = &member.user else {
+					return false;
+				};
+
+				[Some(user.username.as_str()), user.global_name.as_deref(), member.nick.as_deref()]
+					.into_iter()
+					.flatten()
+					.any(|name| name.to_lowercase().starts_with(&query))
+			})
+			.collect();
+
+		found.sort_by_key(|m| m.user.as_ref().map(|u| u.username.clone()));
+		found.truncate(limit);
+		found
+	}
+}
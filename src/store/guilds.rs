@@ -0,0 +1,123 @@
+use crate::models::chat::Channel;
+use crate::models::guild::{Guild, Role};
+use crate::models::types::Snowflake;
+use crate::models::voice::VoiceState;
+
+// https://docs.discord.sex/resources/channel#channel-type
+const THREADS: [u8; 3] = [10, 11, 12];
+
+#[derive(Default)]
+pub struct GuildStore {
+	guilds: Vec<Guild>,
+}
+
+impl GuildStore {
+	pub fn set(
+		&mut self,
+		guilds: Vec<Guild>,
+	) {
+		self.guilds = guilds;
+	}
+
+	pub fn guilds(&self) -> &[Guild] { &self.guilds }
+
+	pub fn guild(
+		&self,
+		guild_id: Snowflake,
+	) -> Option<&Guild> {
+		self.guilds.iter().find(|g| g.id == guild_id)
+	}
+
+	pub fn role(
+		&self,
+		guild_id: Snowflake,
+		role_id: Snowflake,
+	) -> Option<&Role> {
+		self.guild(guild_id)?.roles.iter().find(|r| r.id == role_id)
+	}
+
+	/// role ids are unique across guilds, so mentions don't need to know which guild they're in
+	pub fn find_role(
+		&self,
+		role_id: Snowflake,
+	) -> Option<&Role> {
+		self.guilds.iter().flat_map(|g| &g.roles).find(|r| r.id == role_id)
+	}
+
+	/// any channel or thread in any guild, along with the guild it's in
+	pub fn find_channel(
+		&self,
+		channel_id: Snowflake,
+	) -> Option<(&Guild, &Channel)> {
+		self.guilds.iter().find_map(|g| {
+			g.channels
+				.iter()
+				.chain(&g.threads)
+				.find(|c| c.id == channel_id)
+				.map(|c| (g, c))
+		})
+	}
+
+	/// CHANNEL_CREATE/UPDATE and THREAD_CREATE/UPDATE, threads live in their own list
+	pub fn upsert_channel(
+		&mut self,
+		channel: Channel,
+	) {
+		let Some(guild) = channel.guild_id.and_then(|id| self.guilds.iter_mut().find(|g| g.id == id)) else {
+			return;
+		};
+		let channels = if THREADS.contains(&channel.r#type) {
+			&mut guild.threads
+		} else {
+			&mut guild.channels
+		};
+
+		match channels.iter_mut().find(|c| c.id == channel.id) {
+			| Some(existing) => *existing = channel,
+			| None => channels.push(channel),
+		}
+	}
+
+	pub fn remove_channel(
+		&mut self,
+		guild_id: Snowflake,
+		channel_id: Snowflake,
+	) {
+		if let Some(guild) = self.guilds.iter_mut().find(|g| g.id == guild_id) {
+			guild.channels.retain(|c| c.id != channel_id);
+			guild.threads.retain(|c| c.id != channel_id);
+		}
+	}
+
+	/// a user is in at most one voice channel per guild, no channel means they left
+	pub fn update_voice_state(
+		&mut self,
+		state: VoiceState,
+	) {
+		let Some(guild) = state.guild_id.and_then(|id| self.guilds.iter_mut().find(|g| g.id == id)) else {
+			return;
+		};
+
+		guild.voice_states.retain(|s| s.user_id != state.user_id);
+		if state.channel_id.is_some() {
+			guild.voice_states.push(state);
+		}
+	}
+
+	/// keeps last_message_id current, unread state gets worked out from it
+	pub fn set_last_message(
+		&mut self,
+		channel_id: Snowflake,
+		message_id: Snowflake,
+	) {
+		let channel = self
+			.guilds
+			.iter_mut()
+			.flat_map(|g| g.channels.iter_mut().chain(g.threads.iter_mut()))
+			.find(|c| c.id == channel_id);
+
+		if let Some(channel) = channel {
+			channel.last_message_id = Some(message_id);
+		}
+	}
+}
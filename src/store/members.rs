@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use crate::models::guild::{MemberListGroup, MemberListItem, MemberListOp, MemberListUpdate};
+use crate::models::types::Snowflake;
+
+// we only ever look at one channel per guild, so one list per guild is enough
+#[derive(Default)]
+pub struct MemberListStore {
+	lists: HashMap<Snowflake, MemberList>,
+}
+
+#[derive(Default, Clone, PartialEq)]
+pub struct MemberList {
+	pub id:           String,
+	pub member_count: u32,
+	pub online_count: u32,
+	pub groups:       Vec<MemberListGroup>,
+	pub items:        Vec<Option<MemberListItem>>, // None until the range gets synced
+}
+
+impl MemberListStore {
+	pub fn list(
+		&self,
+		guild_id: Snowflake,
+	) -> Option<&MemberList> {
+		self.lists.get(&guild_id)
+	}
+
+	/// GUILD_MEMBER_LIST_UPDATE
+	pub fn apply(
+		&mut self,
+		update: MemberListUpdate,
+	) {
+		let list = self.lists.entry(update.guild_id).or_default();
+		if list.id != update.id {
+			*list = MemberList {
+				id: update.id,
+				..Default::default()
+			};
+		}
+
+		list.member_count = update.member_count;
+		list.online_count = update.online_count;
+
+		for op in update.ops {
+			match op {
+				| MemberListOp::Sync { range, items } => {
+					let end = range.0 + items.len();
+					if list.items.len() < end {
+						list.items.resize(end, None);
+					}
+					for (index, item) in (range.0..).zip(items) {
+						list.items[index] = Some(item);
+					}
+				},
+				| MemberListOp::Insert { index, item } => {
+					list.items.insert(index.min(list.items.len()), Some(item));
+				},
+				| MemberListOp::Update { index, item } => {
+					if let Some(slot) = list.items.get_mut(index) {
+						*slot = Some(item);
+					}
+				},
+				| MemberListOp::Delete { index } => {
+					if index < list.items.len() {
+						list.items.remove(index);
+					}
+				},
+				| MemberListOp::Invalidate { range } => {
+					for slot in list.items.iter_mut().take(range.1 + 1).skip(range.0) {
+						*slot = None;
+					}
+				},
+			}
+		}
+
+		// every group header is a row too
+		let total = update
+			.groups
+			.iter()
+			.map(|g| g.count as usize + 1)
+			.sum::<usize>();
+		list.items.resize(total, None);
+		list.groups = update.groups;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	fn update(ops: serde_json::Value) -> MemberListUpdate {
+		serde_json::from_value(json!({
+			"id": "everyone",
+			"guild_id": "1",
+			"member_count": 2,
+			"online_count": 1,
+			"groups": [{ "id": "online", "count": 1 }, { "id": "offline", "count": 1 }],
+			"ops": ops,
+		}))
+		.unwrap()
+	}
+
+	fn member(id: &str) -> serde_json::Value {
+		json!({ "member": { "user": { "id": id, "username": id }, "roles": [], "presence": { "status": "online" } } })
+	}
+
+	#[test]
+	fn sync_then_update() {
+		let mut store = MemberListStore::default();
+		let guild = Snowflake::new(1);
+
+		store.apply(update(json!([{
+			"op": "SYNC",
+			"range": [0, 99],
+			"items": [{ "group": { "id": "online", "count": 1 } }, member("2"), { "group": { "id": "offline", "count": 1 } }],
+		}])));
+
+		let list = store.list(guild).unwrap();
+		assert_eq!(list.items.len(), 4);
+		assert!(list.items[3].is_none());
+
+		store.apply(update(json!([
+			{ "op": "DELETE", "index": 1 },
+			{ "op": "INSERT", "index": 2, "item": member("3") },
+			{ "op": "INVALIDATE", "range": [0, 0] },
+		])));
+
+		let list = store.list(guild).unwrap();
+		assert!(list.items[0].is_none());
+		assert!(matches!(&list.items[2], Some(MemberListItem::Member(m)) if m.member.user.as_ref().unwrap().username == "3"));
+	}
+}
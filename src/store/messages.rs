@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+
+use crate::models::chat::Message;
+use crate::models::types::Snowflake;
+
+pub const PAGE_SIZE: u8 = 50;
+
+#[derive(Default)]
+pub struct MessageStore {
+	channels: HashMap<Snowflake, ChannelMessages>,
+}
+
+#[derive(Default, Clone, PartialEq)]
+pub struct ChannelMessages {
+	pub messages:        Vec<Message>, // oldest first
+	pub pending:         Vec<PendingMessage>, // local echoes of what we're sending, in the order they were sent
+	pub has_more_before: bool,
+	pub has_more_after:  bool, // only after jumping to a message, live messages are ignored until we're back at the bottom
+	pub cached:          bool, // from the offline cache, replaced by the first fetch
+}
+
+#[derive(Clone, PartialEq)]
+pub struct PendingMessage {
+	pub nonce:    Snowflake,
+	pub content:  String,
+	pub reply_to: Option<Reply>,
+	pub state:    PendingState,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Reply {
+	pub message_id: Snowflake,
+	pub mention:    bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PendingState {
+	Queued,
+	Sending,
+	Failed,
+}
+
+impl ChannelMessages {
+	pub fn oldest(&self) -> Option<Snowflake> { self.messages.first().map(|m| m.id) }
+
+	pub fn newest(&self) -> Option<Snowflake> { self.messages.last().map(|m| m.id) }
+
+	fn merge(
+		&mut self,
+		page: Vec<Message>,
+	) {
+		for message in page {
+			match self.messages.binary_search_by_key(&message.id, |m| m.id) {
+				| Ok(index) => self.messages[index] = message,
+				| Err(index) => self.messages.insert(index, message),
+			}
+		}
+	}
+}
+
+impl MessageStore {
+	pub fn channel(
+		&self,
+		channel_id: Snowflake,
+	) -> Option<&ChannelMessages> {
+		self.channels.get(&channel_id)
+	}
+
+	/// the newest page of a channel, replaces whatever was loaded before
+	pub fn set_latest(
+		&mut self,
+		channel_id: Snowflake,
+		page: Vec<Message>,
+	) {
+		let mut channel = ChannelMessages {
+			has_more_before: page.len() >= PAGE_SIZE as usize,
+			pending: self.take_pending(channel_id),
+			..Default::default()
+		};
+		channel.merge(page);
+		self.channels.insert(channel_id, channel);
+	}
+
+	/// what the offline cache had for a channel, never replaces anything already loaded
+	pub fn set_cached(
+		&mut self,
+		channel_id: Snowflake,
+		page: Vec<Message>,
+	) {
+		if self.channels.contains_key(&channel_id) {
+			return;
+		}
+
+		let mut channel = ChannelMessages {
+			has_more_before: true,
+			cached: true,
+			..Default::default()
+		};
+		channel.merge(page);
+		self.channels.insert(channel_id, channel);
+	}
+
+	/// the newest messages at the bottom of a channel, nothing if we've jumped away from it
+	pub fn latest(
+		&self,
+		channel_id: Snowflake,
+		limit: usize,
+	) -> Option<&[Message]> {
+		let channel = self.channels.get(&channel_id).filter(|c| !c.has_more_after)?;
+		Some(&channel.messages[channel.messages.len().saturating_sub(limit)..])
+	}
+
+	/// a page centered on a message, we can't tell which side ran out so assume neither did
+	pub fn set_around(
+		&mut self,
+		channel_id: Snowflake,
+		page: Vec<Message>,
+	) {
+		let mut channel = ChannelMessages {
+			has_more_before: true,
+			has_more_after: true,
+			pending: self.take_pending(channel_id),
+			..Default::default()
+		};
+		channel.merge(page);
+		self.channels.insert(channel_id, channel);
+	}
+
+	/// a page fetched with `before`
+	pub fn prepend(
+		&mut self,
+		channel_id: Snowflake,
+		page: Vec<Message>,
+	) {
+		let channel = self.channels.entry(channel_id).or_default();
+		channel.has_more_before = page.len() >= PAGE_SIZE as usize;
+		channel.merge(page);
+	}
+
+	/// a page fetched with `after`
+	pub fn append(
+		&mut self,
+		channel_id: Snowflake,
+		page: Vec<Message>,
+	) {
+		let channel = self.channels.entry(channel_id).or_default();
+		channel.has_more_after = page.len() >= PAGE_SIZE as usize;
+		channel.merge(page);
+	}
+
+	/// MESSAGE_CREATE, also replaces the local echo of our own messages
+	pub fn insert(
+		&mut self,
+		message: Message,
+	) {
+		let Some(channel) = self.channels.get_mut(&message.channel_id) else {
+			return;
+		};
+
+		if let Some(nonce) = message.nonce.as_ref().and_then(|n| n.as_snowflake()) {
+			channel.pending.retain(|p| p.nonce != nonce);
+		}
+		if !channel.has_more_after {
+			channel.merge(vec![message]);
+		}
+	}
+
+	/// local echoes survive reloading the channel
+	fn take_pending(
+		&mut self,
+		channel_id: Snowflake,
+	) -> Vec<PendingMessage> {
+		self.channels
+			.remove(&channel_id)
+			.map(|c| c.pending)
+			.unwrap_or_default()
+	}
+
+	/// an edited message we already have, unlike `insert` this never adds it
+	pub fn replace(
+		&mut self,
+		message: Message,
+	) {
+		if let Some(existing) = self
+			.channels
+			.get_mut(&message.channel_id)
+			.and_then(|c| c.messages.iter_mut().find(|m| m.id == message.id))
+		{
+			*existing = message;
+		}
+	}
+
+	pub fn add_pending(
+		&mut self,
+		channel_id: Snowflake,
+		message: PendingMessage,
+	) {
+		self.channels.entry(channel_id).or_default().pending.push(message);
+	}
+
+	/// claims the next queued message for sending. sends go out one at a time so they arrive in order,
+	/// so nothing gets claimed while another one is still in flight
+	pub fn next_pending(
+		&mut self,
+		channel_id: Snowflake,
+	) -> Option<PendingMessage> {
+		let pending = &mut self.channels.get_mut(&channel_id)?.pending;
+		if pending.iter().any(|p| p.state == PendingState::Sending) {
+			return None;
+		}
+
+		let next = pending.iter_mut().find(|p| p.state == PendingState::Queued)?;
+		next.state = PendingState::Sending;
+		Some(next.clone())
+	}
+
+	/// hands a claimed message back to the queue, for when its send was dropped before it finished
+	pub fn unclaim_pending(
+		&mut self,
+		channel_id: Snowflake,
+		nonce: Snowflake,
+	) {
+		let pending = self
+			.channels
+			.get_mut(&channel_id)
+			.and_then(|c| c.pending.iter_mut().find(|p| p.nonce == nonce));
+		if let Some(pending) = pending.filter(|p| p.state == PendingState::Sending) {
+			pending.state = PendingState::Queued;
+		}
+	}
+
+	/// everything queued after a failed message fails too, otherwise it would overtake it
+	pub fn fail_pending(
+		&mut self,
+		channel_id: Snowflake,
+		nonce: Snowflake,
+	) {
+		self.set_pending_state(channel_id, nonce, PendingState::Failed);
+	}
+
+	/// requeues a failed message and the ones that failed after it, keeping their original order
+	pub fn retry_pending(
+		&mut self,
+		channel_id: Snowflake,
+		nonce: Snowflake,
+	) {
+		self.set_pending_state(channel_id, nonce, PendingState::Queued);
+	}
+
+	fn set_pending_state(
+		&mut self,
+		channel_id: Snowflake,
+		nonce: Snowflake,
+		state: PendingState,
+	) {
+		let Some(channel) = self.channels.get_mut(&channel_id) else {
+			return;
+		};
+
+		let Some(start) = channel.pending.iter().position(|p| p.nonce == nonce) else {
+			return;
+		};
+		for pending in &mut channel.pending[start..] {
+			if pending.nonce == nonce || pending.state != PendingState::Sending {
+				pending.state = state;
+			}
+		}
+	}
+
+	pub fn remove_pending(
+		&mut self,
+		channel_id: Snowflake,
+		nonce: Snowflake,
+	) {
+		if let Some(channel) = self.channels.get_mut(&channel_id) {
+			channel.pending.retain(|p| p.nonce != nonce);
+		}
+	}
+
+	/// MESSAGE_UPDATE, which can be partial (eg. embeds resolving) so only known fields get patched then
+	pub fn update(
+		&mut self,
+		data: &serde_json::Value,
+	) {
+		let (Some(channel_id), Some(id)) = (
+			snowflake_field(data, "channel_id"),
+			snowflake_field(data, "id"),
+		) else {
+			return;
+		};
+
+		let Some(message) = self
+			.channels
+			.get_mut(&channel_id)
+			.and_then(|c| c.messages.iter_mut().find(|m| m.id == id))
+		else {
+			return;
+		};
+
+		if let Ok(updated) = serde_json::from_value::<Message>(data.clone()) {
+			*message = updated;
+			return;
+		}
+
+		if let Some(content) = data["content"].as_str() {
+			message.content = content.to_string();
+		}
+		if let Ok(Some(edited)) = serde_json::from_value(data["edited_timestamp"].clone()) {
+			message.edited_timestamp = Some(edited);
+		}
+		if let Some(pinned) = data["pinned"].as_bool() {
+			message.pinned = pinned;
+		}
+		if let Some(flags) = data["flags"].as_u64() {
+			message.flags = flags;
+		}
+	}
+
+	/// MESSAGE_DELETE and MESSAGE_DELETE_BULK
+	pub fn delete(
+		&mut self,
+		channel_id: Snowflake,
+		ids: &[Snowflake],
+	) {
+		if let Some(channel) = self.channels.get_mut(&channel_id) {
+			channel.messages.retain(|m| !ids.contains(&m.id));
+		}
+	}
+}
+
+fn snowflake_field(
+	data: &serde_json::Value,
+	key: &str,
+) -> Option<Snowflake> {
+	data[key].as_str().and_then(|s| s.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+	use crate::models::chat::Nonce;
+
+	fn message(id: u64) -> Message {
+		serde_json::from_value(json!({
+			"id": id.to_string(),
+			"channel_id": "1",
+			"author": { "id": "2", "username": "cow" },
+			"content": format!("message {}", id),
+			"timestamp": "2025-04-10T18:21:33.146000+00:00",
+		}))
+		.unwrap()
+	}
+
+	fn ids(store: &MessageStore) -> Vec<u64> {
+		store
+			.channel(Snowflake::new(1))
+			.unwrap()
+			.messages
+			.iter()
+			.map(|m| m.id.raw())
+			.collect()
+	}
+
+	#[test]
+	fn pages_are_kept_in_order() {
+		let mut store = MessageStore::default();
+		let channel = Snowflake::new(1);
+
+		// the api returns newest first
+		store.set_latest(channel, vec![message(30), message(20)]);
+		store.prepend(channel, vec![message(10), message(5)]);
+
+		assert_eq!(ids(&store), vec![5, 10, 20, 30]);
+		assert!(!store.channel(channel).unwrap().has_more_before);
+	}
+
+	#[test]
+	fn live_messages_wait_until_caught_up() {
+		let mut store = MessageStore::default();
+		let channel = Snowflake::new(1);
+
+		store.set_around(channel, vec![message(20), message(10)]);
+		store.insert(message(40));
+		assert_eq!(ids(&store), vec![10, 20]);
+
+		store.append(channel, vec![message(30)]);
+		store.insert(message(40));
+		store.insert(message(40));
+		assert_eq!(ids(&store), vec![10, 20, 30, 40]);
+	}
+
+	#[test]
+	fn cached_messages_give_way_to_fetched_ones() {
+		let mut store = MessageStore::default();
+		let channel = Snowflake::new(1);
+
+		store.set_cached(channel, vec![message(10), message(20), message(30)]);
+		assert!(store.channel(channel).unwrap().cached);
+		let latest: Vec<u64> = store.latest(channel, 2).unwrap().iter().map(|m| m.id.raw()).collect();
+		assert_eq!(latest, vec![20, 30]);
+
+		store.set_latest(channel, vec![message(40), message(30)]);
+		store.set_cached(channel, vec![message(10)]);
+		assert!(!store.channel(channel).unwrap().cached);
+		assert_eq!(ids(&store), vec![30, 40]);
+	}
+
+	fn pending(nonce: u64) -> PendingMessage {
+		PendingMessage {
+			nonce:    Snowflake::new(nonce),
+			content:  format!("pending {}", nonce),
+			reply_to: None,
+			state:    PendingState::Queued,
+		}
+	}
+
+	fn states(store: &MessageStore) -> Vec<(u64, PendingState)> {
+		store
+			.channel(Snowflake::new(1))
+			.unwrap()
+			.pending
+			.iter()
+			.map(|p| (p.nonce.raw(), p.state))
+			.collect()
+	}
+
+	#[test]
+	fn pending_messages_send_in_order_and_reconcile() {
+		let mut store = MessageStore::default();
+		let channel = Snowflake::new(1);
+		store.set_latest(channel, vec![message(10)]);
+		store.add_pending(channel, pending(100));
+		store.add_pending(channel, pending(101));
+
+		assert_eq!(store.next_pending(channel).unwrap().nonce.raw(), 100);
+		assert!(store.next_pending(channel).is_none());
+
+		let mut sent = message(20);
+		sent.nonce = Some(Nonce::String("100".to_string()));
+		store.insert(sent);
+
+		assert_eq!(ids(&store), vec![10, 20]);
+		assert_eq!(states(&store), vec![(101, PendingState::Queued)]);
+		assert_eq!(store.next_pending(channel).unwrap().nonce.raw(), 101);
+	}
+
+	#[test]
+	fn failed_messages_retry_in_order() {
+		let mut store = MessageStore::default();
+		let channel = Snowflake::new(1);
+		store.add_pending(channel, pending(100));
+		store.add_pending(channel, pending(101));
+		store.add_pending(channel, pending(102));
+
+		store.next_pending(channel);
+		store.fail_pending(channel, Snowflake::new(100));
+		assert_eq!(states(&store), vec![
+			(100, PendingState::Failed),
+			(101, PendingState::Failed),
+			(102, PendingState::Failed),
+		]);
+		assert!(store.next_pending(channel).is_none());
+
+		store.remove_pending(channel, Snowflake::new(101));
+		store.retry_pending(channel, Snowflake::new(100));
+		assert_eq!(store.next_pending(channel).unwrap().nonce.raw(), 100);
+		assert_eq!(states(&store), vec![
+			(100, PendingState::Sending),
+			(102, PendingState::Queued),
+		]);
+	}
+
+	#[test]
+	fn partial_updates_and_deletes() {
+		let mut store = MessageStore::default();
+		let channel = Snowflake::new(1);
+		store.set_latest(channel, vec![message(20), message(10)]);
+
+		store.update(&json!({ "id": "10", "channel_id": "1", "content": "edited" }));
+		store.delete(channel, &[Snowflake::new(20)]);
+
+		let messages = &store.channel(channel).unwrap().messages;
+		assert_eq!(messages.len(), 1);
+		assert_eq!(messages[0].content, "edited");
+	}
+}
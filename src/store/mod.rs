@@ -0,0 +1,54 @@
+// client side state that outlives any one component, filled in by the gateway and rest calls
+
+pub mod cache;
+pub mod guild_members;
+pub mod guilds;
+pub mod members;
+pub mod messages;
+pub mod presences;
+pub mod private_channels;
+pub mod read_states;
+pub mod relationships;
+pub mod sessions;
+pub mod settings;
+pub mod users;
+
+use dioxus::prelude::*;
+use guild_members::GuildMemberStore;
+use guilds::GuildStore;
+use members::MemberListStore;
+use messages::MessageStore;
+use presences::PresenceStore;
+use private_channels::PrivateChannelStore;
+use read_states::ReadStateStore;
+use relationships::RelationshipStore;
+use sessions::SessionStore;
+use settings::SettingsStore;
+use users::UserStore;
+
+pub static GUILD_MEMBERS: GlobalSignal<GuildMemberStore> = Signal::global(GuildMemberStore::default);
+pub static GUILDS: GlobalSignal<GuildStore> = Signal::global(GuildStore::default);
+pub static MEMBER_LISTS: GlobalSignal<MemberListStore> = Signal::global(MemberListStore::default);
+pub static MESSAGES: GlobalSignal<MessageStore> = Signal::global(MessageStore::default);
+pub static PRESENCES: GlobalSignal<PresenceStore> = Signal::global(PresenceStore::default);
+pub static PRIVATE_CHANNELS: GlobalSignal<PrivateChannelStore> = Signal::global(PrivateChannelStore::default);
+pub static READ_STATES: GlobalSignal<ReadStateStore> = Signal::global(ReadStateStore::default);
+pub static RELATIONSHIPS: GlobalSignal<RelationshipStore> = Signal::global(RelationshipStore::default);
+pub static SESSIONS: GlobalSignal<SessionStore> = Signal::global(SessionStore::default);
+pub static SETTINGS: GlobalSignal<SettingsStore> = Signal::global(SettingsStore::default);
+pub static USERS: GlobalSignal<UserStore> = Signal::global(UserStore::default);
+
+/// forgets everything the account filled in, for logging out
+pub fn clear() {
+	*GUILD_MEMBERS.write() = GuildMemberStore::default();
+	*GUILDS.write() = GuildStore::default();
+	*MEMBER_LISTS.write() = MemberListStore::default();
+	*MESSAGES.write() = MessageStore::default();
+	*PRESENCES.write() = PresenceStore::default();
+	*PRIVATE_CHANNELS.write() = PrivateChannelStore::default();
+	*READ_STATES.write() = ReadStateStore::default();
+	*RELATIONSHIPS.write() = RelationshipStore::default();
+	*SESSIONS.write() = SessionStore::default();
+	*SETTINGS.write() = SettingsStore::default();
+	*USERS.write() = UserStore::default();
+}
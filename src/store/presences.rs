@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use crate::models::presence::{Presence, Status};
+use crate::models::types::Snowflake;
+
+// the latest presence of everyone we get them for, status is the same in every guild so one per user does
+#[derive(Default)]
+pub struct PresenceStore {
+	users: HashMap<Snowflake, Presence>,
+}
+
+impl PresenceStore {
+	pub fn insert(
+		&mut self,
+		presence: Presence,
+	) {
+		if let Some(user_id) = presence.user_id() {
+			self.users.insert(user_id, presence);
+		}
+	}
+
+	pub fn extend(
+		&mut self,
+		presences: impl IntoIterator<Item = Presence>,
+	) {
+		for presence in presences {
+			self.insert(presence);
+		}
+	}
+
+	pub fn get(
+		&self,
+		user_id: Snowflake,
+	) -> Option<&Presence> {
+		self.users.get(&user_id)
+	}
+
+	pub fn status(
+		&self,
+		user_id: Snowflake,
+	) -> Status {
+		self.get(user_id).map(|p| p.status).unwrap_or_default()
+	}
+}
@@ -0,0 +1,171 @@
+use crate::models::chat::Channel;
+use crate::models::types::Snowflake;
+use crate::models::user::user::PartialUser;
+use crate::store::users::UserStore;
+
+// https://docs.discord.sex/resources/channel#channel-type
+const DM: u8 = 1;
+
+// dms and group dms. recipients are only kept as ids, the users themselves go in the user store
+#[derive(Default)]
+pub struct PrivateChannelStore {
+	channels: Vec<Channel>,
+}
+
+/// READY only sends `recipient_ids`, everything else sends full `recipients`
+fn normalize(mut channel: Channel) -> Channel {
+	if channel.recipient_ids.is_empty() {
+		channel.recipient_ids = channel.recipients.iter().map(|u| u.id).collect();
+	}
+	channel.recipients.clear();
+	channel
+}
+
+impl PrivateChannelStore {
+	pub fn set(
+		&mut self,
+		channels: Vec<Channel>,
+	) {
+		self.channels = channels.into_iter().map(normalize).collect();
+	}
+
+	pub fn channel(
+		&self,
+		channel_id: Snowflake,
+	) -> Option<&Channel> {
+		self.channels.iter().find(|c| c.id == channel_id)
+	}
+
+	/// most recently active first, channels without messages go by when they were made
+	pub fn sorted(&self) -> Vec<&Channel> {
+		let mut channels: Vec<&Channel> = self.channels.iter().collect();
+		channels.sort_by_key(|c| std::cmp::Reverse(c.last_message_id.unwrap_or(c.id)));
+		channels
+	}
+
+	/// the 1 on 1 dm with a user, if it's been opened before
+	pub fn dm_with(
+		&self,
+		user_id: Snowflake,
+	) -> Option<&Channel> {
+		self.channels
+			.iter()
+			.find(|c| c.r#type == DM && c.recipient_ids == [user_id])
+	}
+
+	pub fn upsert(
+		&mut self,
+		channel: Channel,
+	) {
+		let channel = normalize(channel);
+
+		match self.channels.iter_mut().find(|c| c.id == channel.id) {
+			| Some(existing) => *existing = channel,
+			| None => self.channels.push(channel),
+		}
+	}
+
+	pub fn remove(
+		&mut self,
+		channel_id: Snowflake,
+	) {
+		self.channels.retain(|c| c.id != channel_id);
+	}
+
+	pub fn set_last_message(
+		&mut self,
+		channel_id: Snowflake,
+		message_id: Snowflake,
+	) {
+		if let Some(channel) = self.channels.iter_mut().find(|c| c.id == channel_id) {
+			channel.last_message_id = Some(message_id);
+		}
+	}
+
+	pub fn add_recipient(
+		&mut self,
+		channel_id: Snowflake,
+		user_id: Snowflake,
+	) {
+		if let Some(channel) = self.channels.iter_mut().find(|c| c.id == channel_id)
+			&& !channel.recipient_ids.contains(&user_id)
+		{
+			channel.recipient_ids.push(user_id);
+		}
+	}
+
+	pub fn remove_recipient(
+		&mut self,
+		channel_id: Snowflake,
+		user_id: Snowflake,
+	) {
+		if let Some(channel) = self.channels.iter_mut().find(|c| c.id == channel_id) {
+			channel.recipient_ids.retain(|&id| id != user_id);
+			channel.nicks.retain(|n| n.id != user_id);
+		}
+	}
+}
+
+pub fn recipients(
+	channel: &Channel,
+	users: &UserStore,
+) -> Vec<PartialUser> {
+	channel
+		.recipient_ids
+		.iter()
+		.filter_map(|&id| users.user(id).cloned())
+		.collect()
+}
+
+/// group dms without a name get named after everyone in them, using their nicks in the group
+pub fn display_name(
+	channel: &Channel,
+	recipients: &[PartialUser],
+) -> String {
+	if let Some(name) = channel.name.as_deref().filter(|n| !n.is_empty()) {
+		return name.to_string();
+	}
+	if recipients.is_empty() {
+		return "Unnamed".to_string();
+	}
+
+	recipients
+		.iter()
+		.map(|user| {
+			channel
+				.nicks
+				.iter()
+				.find(|n| n.id == user.id)
+				.map_or(user.display_name(), |n| n.nick.as_str())
+		})
+		.collect::<Vec<_>>()
+		.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn channels() -> Vec<Channel> {
+		serde_json::from_str(include_str!("../models/fixtures/private_channels.json")).unwrap()
+	}
+
+	#[test]
+	fn sorted_by_last_message() {
+		let mut store = PrivateChannelStore::default();
+		store.set(channels());
+
+		let ids: Vec<u64> = store.sorted().iter().map(|c| c.id.raw()).collect();
+		assert_eq!(ids, vec![1002003004005006009, 1002003004005006007, 1002003004005006008]);
+		assert!(store.dm_with(Snowflake::new(1147068862474711091)).is_some());
+	}
+
+	#[test]
+	fn group_names_use_nicks() {
+		let channels = channels();
+		let group = &channels[1];
+
+		assert_eq!(display_name(group, &group.recipients), "some1, Friend");
+		assert_eq!(display_name(group, &[]), "Unnamed");
+	}
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use crate::models::chat::Channel;
+use crate::models::read_state::ReadState;
+use crate::models::types::Snowflake;
+
+// https://docs.discord.sex/resources/channel#channel-type
+const TEXT_CHANNELS: [u8; 5] = [0, 1, 3, 5, 15];
+
+fn empty(channel_id: Snowflake) -> ReadState {
+	ReadState {
+		id:                 channel_id,
+		read_state_type:    0,
+		last_message_id:    None,
+		mention_count:      0,
+		badge_count:        0,
+		last_pin_timestamp: None,
+		flags:              0,
+		last_viewed:        None,
+	}
+}
+
+#[derive(Default)]
+pub struct ReadStateStore {
+	channels:      HashMap<Snowflake, ReadState>,
+	pub ack_token: Option<String>, // from the last ack we sent, goes along with the next one
+}
+
+impl ReadStateStore {
+	pub fn set(
+		&mut self,
+		entries: Vec<ReadState>,
+	) {
+		self.channels = entries
+			.into_iter()
+			.filter(|e| e.read_state_type == 0)
+			.map(|e| (e.id, e))
+			.collect();
+	}
+
+	pub fn last_acked(
+		&self,
+		channel_id: Snowflake,
+	) -> Option<Snowflake> {
+		self.channels.get(&channel_id)?.last_message_id
+	}
+
+	/// acks never go backwards, an older ack from another session arriving late is ignored
+	pub fn ack(
+		&mut self,
+		channel_id: Snowflake,
+		message_id: Snowflake,
+		mention_count: u32,
+	) {
+		let read_state = self.channels.entry(channel_id).or_insert_with(|| empty(channel_id));
+
+		if read_state.last_message_id.is_none_or(|acked| message_id >= acked) {
+			read_state.last_message_id = Some(message_id);
+			read_state.mention_count = mention_count;
+		}
+	}
+
+	/// a channel we've never opened gets a read state with nothing acked, so it shows up as unread
+	pub fn add_mention(
+		&mut self,
+		channel_id: Snowflake,
+	) {
+		self.channels
+			.entry(channel_id)
+			.or_insert_with(|| empty(channel_id))
+			.mention_count += 1;
+	}
+
+	pub fn mention_count(
+		&self,
+		channel_id: Snowflake,
+	) -> u32 {
+		self.channels.get(&channel_id).map_or(0, |r| r.mention_count)
+	}
+
+	/// channels we've never opened have no read state, those count as read like they do on discord
+	pub fn is_unread(
+		&self,
+		channel: &Channel,
+	) -> bool {
+		if !TEXT_CHANNELS.contains(&channel.r#type) {
+			return false;
+		}
+
+		match (channel.last_message_id, self.channels.get(&channel.id)) {
+			| (Some(last), Some(read)) => read.last_message_id.is_none_or(|acked| last > acked),
+			| _ => false,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn id(id: u64) -> Snowflake { Snowflake::new(id) }
+
+	#[test]
+	fn acks_only_move_forward() {
+		let mut store = ReadStateStore::default();
+		store.add_mention(id(1));
+		store.add_mention(id(1));
+		assert_eq!(store.mention_count(id(1)), 2);
+		assert_eq!(store.last_acked(id(1)), None);
+
+		store.ack(id(1), id(20), 0);
+		store.ack(id(1), id(10), 3);
+		assert_eq!(store.last_acked(id(1)), Some(id(20)));
+		assert_eq!(store.mention_count(id(1)), 0);
+	}
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use crate::models::relationship::{BLOCKED, Relationship};
+use crate::models::types::Snowflake;
+
+// friends, blocks and pending requests keyed by the other user, their users go in the user store
+#[derive(Default)]
+pub struct RelationshipStore {
+	relationships: HashMap<Snowflake, Relationship>,
+}
+
+impl RelationshipStore {
+	pub fn set(
+		&mut self,
+		relationships: Vec<Relationship>,
+	) {
+		self.relationships = relationships.into_iter().map(|r| (r.id, r)).collect();
+	}
+
+	pub fn get(
+		&self,
+		user_id: Snowflake,
+	) -> Option<&Relationship> {
+		self.relationships.get(&user_id)
+	}
+
+	pub fn of_type(
+		&self,
+		r#type: u8,
+	) -> Vec<&Relationship> {
+		self.relationships.values().filter(|r| r.r#type == r#type).collect()
+	}
+
+	/// RELATIONSHIP_ADD and RELATIONSHIP_UPDATE, updates don't carry the user so keep the one we have
+	pub fn upsert(
+		&mut self,
+		relationship: Relationship,
+	) {
+		match self.relationships.get_mut(&relationship.id) {
+			| Some(existing) => {
+				existing.r#type = relationship.r#type;
+				existing.nickname = relationship.nickname;
+				existing.since = relationship.since.or(existing.since);
+				if relationship.user.is_some() {
+					existing.user = relationship.user;
+				}
+			},
+			| None => {
+				self.relationships.insert(relationship.id, relationship);
+			},
+		}
+	}
+
+	pub fn remove(
+		&mut self,
+		user_id: Snowflake,
+	) {
+		self.relationships.remove(&user_id);
+	}
+
+	pub fn is_blocked(
+		&self,
+		user_id: Snowflake,
+	) -> bool {
+		self.get(user_id).is_some_and(|r| r.r#type == BLOCKED)
+	}
+
+	pub fn nickname(
+		&self,
+		user_id: Snowflake,
+	) -> Option<&str> {
+		self.get(user_id)?.nickname.as_deref()
+	}
+}
@@ -0,0 +1,11 @@
+#[derive(Default)]
+pub struct SessionStore {
+	/// `auth_session_id_hash` from READY, which of the auth sessions is this client
+	pub current:      Option<String>,
+	/// bumped on every SESSIONS_REPLACE, so the sessions page knows to refetch
+	pub replacements: u32,
+}
+
+impl SessionStore {
+	pub fn replaced(&mut self) { self.replacements = self.replacements.wrapping_add(1); }
+}
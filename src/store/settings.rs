@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use crate::models::chat::Channel;
+use crate::models::types::{Snowflake, now_ms};
+use crate::models::user::settings::{GuildFolder, UserGuildSettings, is_muted};
+
+#[derive(Default)]
+pub struct SettingsStore {
+	pub guild_folders:  Vec<GuildFolder>,
+	pub from_proto:     bool, // which endpoint changes have to go to
+	pub guild_settings: HashMap<Snowflake, UserGuildSettings>,
+}
+
+impl SettingsStore {
+	pub fn set_guild_settings(
+		&mut self,
+		settings: UserGuildSettings,
+	) {
+		if let Some(guild_id) = settings.guild_id {
+			self.guild_settings.insert(guild_id, settings);
+		}
+	}
+
+	/// muting a category mutes everything in it, same for a thread's parent
+	pub fn is_channel_muted(
+		&self,
+		guild_id: Snowflake,
+		channel: &Channel,
+	) -> bool {
+		let Some(settings) = self.guild_settings.get(&guild_id) else {
+			return false;
+		};
+		let now = now_ms() as i64;
+
+		settings
+			.channel_overrides
+			.iter()
+			.filter(|o| o.channel_id == channel.id || Some(o.channel_id) == channel.parent_id)
+			.any(|o| is_muted(o.muted, o.mute_config.as_ref(), now))
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FolderDrop {
+	Before(Snowflake),      // right before a guild, inside its folder if it has one
+	BeforeEntry(Snowflake), // before the folder (or lone guild) containing this guild
+	Into(Snowflake),        // at the end of the folder containing this guild, makes one if it's a lone guild
+	End,
+}
+
+/// the folders as stored in settings don't have to match the guilds we're in,
+/// new guilds go on top like on discord and guilds we left get dropped
+pub fn layout(
+	folders: &[GuildFolder],
+	guild_ids: &[Snowflake],
+) -> Vec<GuildFolder> {
+	let mut layout: Vec<GuildFolder> = guild_ids
+		.iter()
+		.filter(|id| !folders.iter().any(|f| f.guild_ids.contains(id)))
+		.map(|&id| GuildFolder {
+			guild_ids: vec![id],
+			..Default::default()
+		})
+		.collect();
+
+	for folder in folders {
+		let guild_ids: Vec<Snowflake> = folder
+			.guild_ids
+			.iter()
+			.filter(|id| guild_ids.contains(id))
+			.copied()
+			.collect();
+
+		if !guild_ids.is_empty() {
+			layout.push(GuildFolder {
+				guild_ids,
+				..folder.clone()
+			});
+		}
+	}
+
+	layout
+}
+
+/// moves a guild within the layout, returns false if nothing changed
+pub fn move_guild(
+	folders: &mut Vec<GuildFolder>,
+	guild_id: Snowflake,
+	target: FolderDrop,
+) -> bool {
+	match target {
+		| FolderDrop::Before(id) | FolderDrop::BeforeEntry(id) | FolderDrop::Into(id) if id == guild_id => {
+			return false;
+		},
+		| _ => {},
+	}
+
+	let Some(from) = folders.iter().position(|f| f.guild_ids.contains(&guild_id)) else {
+		return false;
+	};
+	folders[from].guild_ids.retain(|&id| id != guild_id);
+	if folders[from].guild_ids.is_empty() {
+		folders.remove(from);
+	}
+
+	let entry_of = |folders: &[GuildFolder], id: Snowflake| folders.iter().position(|f| f.guild_ids.contains(&id));
+	let alone = |guild_id| GuildFolder {
+		guild_ids: vec![guild_id],
+		..Default::default()
+	};
+
+	match target {
+		| FolderDrop::Before(id) => {
+			let Some(index) = entry_of(folders, id) else {
+				folders.push(alone(guild_id));
+				return true;
+			};
+
+			if folders[index].is_folder() {
+				let folder = &mut folders[index].guild_ids;
+				let position = folder.iter().position(|&g| g == id).unwrap();
+				folder.insert(position, guild_id);
+			} else {
+				folders.insert(index, alone(guild_id));
+			}
+		},
+		| FolderDrop::BeforeEntry(id) => {
+			let index = entry_of(folders, id).unwrap_or(folders.len());
+			folders.insert(index, alone(guild_id));
+		},
+		| FolderDrop::Into(id) => {
+			let Some(index) = entry_of(folders, id) else {
+				folders.push(alone(guild_id));
+				return true;
+			};
+
+			if !folders[index].is_folder() {
+				let next_id = folders.iter().filter_map(|f| f.id).max().unwrap_or(0) + 1;
+				folders[index].id = Some(next_id);
+			}
+			folders[index].guild_ids.push(guild_id);
+		},
+		| FolderDrop::End => folders.push(alone(guild_id)),
+	}
+
+	true
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn id(id: u64) -> Snowflake { Snowflake::new(id) }
+
+	fn folder(
+		folder_id: Option<i64>,
+		guilds: &[u64],
+	) -> GuildFolder {
+		GuildFolder {
+			id: folder_id,
+			guild_ids: guilds.iter().map(|&g| id(g)).collect(),
+			..Default::default()
+		}
+	}
+
+	fn shape(folders: &[GuildFolder]) -> Vec<(Option<i64>, Vec<u64>)> {
+		folders
+			.iter()
+			.map(|f| (f.id, f.guild_ids.iter().map(|g| g.raw()).collect()))
+			.collect()
+	}
+
+	#[test]
+	fn layout_adds_new_guilds_on_top_and_drops_left_ones() {
+		let folders = vec![folder(None, &[1]), folder(Some(7), &[2, 3])];
+
+		assert_eq!(shape(&layout(&folders, &[id(1), id(3), id(4)])), vec![
+			(None, vec![4]),
+			(None, vec![1]),
+			(Some(7), vec![3]),
+		]);
+	}
+
+	#[test]
+	fn moving_into_and_out_of_folders() {
+		let mut folders = vec![folder(None, &[1]), folder(Some(7), &[2, 3]), folder(None, &[4])];
+
+		assert!(move_guild(&mut folders, id(1), FolderDrop::Before(id(3))));
+		assert_eq!(shape(&folders), vec![(Some(7), vec![2, 1, 3]), (None, vec![4])]);
+
+		assert!(move_guild(&mut folders, id(2), FolderDrop::End));
+		assert_eq!(shape(&folders), vec![(Some(7), vec![1, 3]), (None, vec![4]), (None, vec![2])]);
+
+		assert!(move_guild(&mut folders, id(4), FolderDrop::BeforeEntry(id(1))));
+		assert_eq!(shape(&folders), vec![(None, vec![4]), (Some(7), vec![1, 3]), (None, vec![2])]);
+	}
+
+	#[test]
+	fn dropping_onto_a_lone_guild_makes_a_folder() {
+		let mut folders = vec![folder(Some(7), &[1]), folder(None, &[2]), folder(None, &[3])];
+
+		assert!(move_guild(&mut folders, id(3), FolderDrop::Into(id(2))));
+		assert_eq!(shape(&folders), vec![(Some(7), vec![1]), (Some(8), vec![2, 3])]);
+		assert!(!move_guild(&mut folders, id(2), FolderDrop::Into(id(2))));
+	}
+}
@@ -0,0 +1,59 @@
+use std::collections::HashMap;
+
+use crate::models::chat::Message;
+use crate::models::types::Snowflake;
+use crate::models::user::user::PartialUser;
+
+// every user we've come across, for resolving mentions and the like
+#[derive(Default)]
+pub struct UserStore {
+	users:   HashMap<Snowflake, PartialUser>,
+	current: Option<Snowflake>,
+}
+
+impl UserStore {
+	/// whoever we're logged in as
+	pub fn current(&self) -> Option<&PartialUser> { self.user(self.current?) }
+
+	pub fn set_current(
+		&mut self,
+		user: PartialUser,
+	) {
+		self.current = Some(user.id);
+		self.insert(user);
+	}
+
+	pub fn user(
+		&self,
+		user_id: Snowflake,
+	) -> Option<&PartialUser> {
+		self.users.get(&user_id)
+	}
+
+	pub fn insert(
+		&mut self,
+		user: PartialUser,
+	) {
+		self.users.insert(user.id, user);
+	}
+
+	pub fn extend(
+		&mut self,
+		users: impl IntoIterator<Item = PartialUser>,
+	) {
+		for user in users {
+			self.insert(user);
+		}
+	}
+
+	/// authors and mentioned users
+	pub fn extend_from_messages(
+		&mut self,
+		messages: &[Message],
+	) {
+		for message in messages {
+			self.insert(message.author.clone());
+			self.extend(message.mentions.iter().cloned());
+		}
+	}
+}
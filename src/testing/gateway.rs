@@ -0,0 +1,179 @@
+// plays a script to whoever connects, one list of steps per connection. heartbeats are acked whenever the
+// mock is waiting on the client, and everything the client sends is kept for asserting on later
+
+use std::sync::{Arc, Mutex};
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::WebSocketStream;
+use tokio_tungstenite::tungstenite::Message as Frame;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+
+use crate::utils::recorder::Recording;
+
+pub const SESSION_ID: &str = "mock-session";
+pub const HEARTBEAT_INTERVAL_MS: u32 = 50;
+
+pub enum Step {
+	Hello,
+	/// waits for op 2
+	ExpectIdentify,
+	/// waits for op 6
+	ExpectResume,
+	/// waits for the next heartbeat
+	ExpectHeartbeat,
+	/// the READY fixture, with resumes pointed back at the mock
+	Ready,
+	Dispatch(&'static str, Value),
+	/// a raw frame, eg. from a recording
+	Frame(String),
+	/// op 7
+	Reconnect,
+	/// op 9, whether the session can be resumed
+	InvalidSession(bool),
+	Close,
+	/// a close frame with a code, eg. 4004 for a bad token
+	CloseWith(u16),
+}
+
+pub struct MockGateway {
+	pub url:  String,
+	received: Arc<Mutex<Vec<Vec<Value>>>>,
+}
+
+impl MockGateway {
+	pub async fn start(script: Vec<Vec<Step>>) -> Self {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let url = format!("ws://{}", listener.local_addr().unwrap());
+		let received = Arc::new(Mutex::new(Vec::new()));
+
+		tokio::spawn(serve(listener, url.clone(), script, received.clone()));
+		MockGateway { url, received }
+	}
+
+	/// what the client sent, per connection
+	pub fn received(&self) -> Vec<Vec<Value>> { self.received.lock().unwrap().clone() }
+}
+
+/// a single connection that identifies and then gets the recording's dispatches
+pub fn recorded(recording: &Recording) -> Vec<Vec<Step>> {
+	let dispatches = recording
+		.frames()
+		.filter(|frame| serde_json::from_str::<Value>(frame).is_ok_and(|payload| payload["op"] == 0))
+		.map(|frame| Step::Frame(frame.to_string()));
+	vec![[Step::Hello, Step::ExpectIdentify].into_iter().chain(dispatches).collect()]
+}
+
+async fn serve(
+	listener: TcpListener,
+	url: String,
+	script: Vec<Vec<Step>>,
+	received: Arc<Mutex<Vec<Vec<Value>>>>,
+) {
+	// the sequence carries on across connections, like a resumed session's would
+	let mut sequence = 0;
+
+	for steps in script {
+		let Ok((stream, _)) = listener.accept().await else {
+			return;
+		};
+		let Ok(mut socket) = tokio_tungstenite::accept_async(stream).await else {
+			return;
+		};
+		received.lock().unwrap().push(Vec::new());
+		let mut connection = Connection { socket: &mut socket, received: &received };
+
+		for step in steps {
+			let sent = match step {
+				| Step::Hello => {
+					connection
+						.send(json!({ "op": 10, "d": { "heartbeat_interval": HEARTBEAT_INTERVAL_MS } }))
+						.await
+				},
+				| Step::ExpectIdentify => connection.expect(2).await,
+				| Step::ExpectResume => connection.expect(6).await,
+				| Step::ExpectHeartbeat => connection.expect(1).await,
+				| Step::Ready => {
+					sequence += 1;
+					let mut ready: Value = serde_json::from_str(include_str!("../models/fixtures/ready.json")).unwrap();
+					ready["s"] = json!(sequence);
+					ready["d"]["session_id"] = json!(SESSION_ID);
+					ready["d"]["resume_gateway_url"] = json!(url);
+					connection.send(ready).await
+				},
+				| Step::Dispatch(t, d) => {
+					sequence += 1;
+					connection.send(json!({ "op": 0, "s": sequence, "t": t, "d": d })).await
+				},
+				| Step::Frame(frame) => connection.socket.send(Frame::Text(frame)).await.is_ok(),
+				| Step::Reconnect => connection.send(json!({ "op": 7, "d": null })).await,
+				| Step::InvalidSession(resumable) => connection.send(json!({ "op": 9, "d": resumable })).await,
+				| Step::Close => {
+					let _ = connection.socket.close(None).await;
+					false
+				},
+				| Step::CloseWith(code) => {
+					let frame = CloseFrame {
+						code:   code.into(),
+						reason: "".into(),
+					};
+					let _ = connection.socket.close(Some(frame)).await;
+					false
+				},
+			};
+
+			if !sent {
+				break;
+			}
+		}
+
+		// keeps acking until the client hangs up
+		while connection.receive().await.is_some() {}
+	}
+}
+
+struct Connection<'a> {
+	socket:   &'a mut WebSocketStream<TcpStream>,
+	received: &'a Mutex<Vec<Vec<Value>>>,
+}
+
+impl Connection<'_> {
+	async fn send(
+		&mut self,
+		payload: Value,
+	) -> bool {
+		self.socket.send(Frame::Text(payload.to_string())).await.is_ok()
+	}
+
+	/// the next payload from the client, acking it if it's a heartbeat
+	async fn receive(&mut self) -> Option<Value> {
+		loop {
+			match self.socket.next().await? {
+				| Ok(Frame::Text(text)) => {
+					let payload: Value = serde_json::from_str(&text).unwrap();
+					self.received.lock().unwrap().last_mut().unwrap().push(payload.clone());
+					if payload["op"] == 1 && !self.send(json!({ "op": 11 })).await {
+						return None;
+					}
+					return Some(payload);
+				},
+				| Ok(Frame::Close(_)) | Err(_) => return None,
+				| Ok(_) => {},
+			}
+		}
+	}
+
+	/// skips ahead to the client's next `op`
+	async fn expect(
+		&mut self,
+		op: u64,
+	) -> bool {
+		while let Some(payload) = self.receive().await {
+			if payload["op"] == op {
+				return true;
+			}
+		}
+		false
+	}
+}
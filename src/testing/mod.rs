@@ -0,0 +1,17 @@
+// local stand-ins for discord so scenarios can run end to end in tests, see `gateway` and `rest`
+
+pub mod gateway;
+pub mod rest;
+
+use std::future::{Future, poll_fn};
+use std::pin::pin;
+
+use dioxus::prelude::*;
+
+/// polls `future` inside a bare dioxus runtime, so whatever it touches can use global signals
+pub async fn in_runtime<F: Future>(future: F) -> F::Output {
+	let dom = VirtualDom::new(VNode::empty);
+	let mut future = pin!(future);
+	poll_fn(|cx| dom.in_runtime(|| future.as_mut().poll(cx))).await
+}
+
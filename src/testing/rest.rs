@@ -0,0 +1,205 @@
+// a bare http server answering from scripted routes. each route goes through its responses in order and
+// keeps repeating the last one, and every request is kept for asserting on later
+
+use std::sync::{Arc, Mutex};
+
+use reqwest::StatusCode;
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::utils::recorder::{Entry, Recording};
+
+pub struct Route {
+	method:    String,
+	path:      String, // `*` matches any one segment
+	responses: Vec<(u16, String)>,
+	answered:  usize,
+}
+
+impl Route {
+	pub fn new(
+		method: &str,
+		path: &str,
+	) -> Self {
+		Route {
+			method: method.to_string(),
+			path: path.to_string(),
+			responses: Vec::new(),
+			answered: 0,
+		}
+	}
+
+	pub fn respond(
+		mut self,
+		status: u16,
+		body: &str,
+	) -> Self {
+		self.responses.push((status, body.to_string()));
+		self
+	}
+
+	fn matches(
+		&self,
+		method: &str,
+		path: &str,
+	) -> bool {
+		let pattern: Vec<&str> = self.path.split('/').collect();
+		let segments: Vec<&str> = path.split('/').collect();
+		self.method == method
+			&& pattern.len() == segments.len()
+			&& pattern.iter().zip(&segments).all(|(p, s)| *p == "*" || p == s)
+	}
+
+	fn next(&mut self) -> (u16, String) {
+		let response = self.responses[self.answered.min(self.responses.len() - 1)].clone();
+		self.answered += 1;
+		response
+	}
+}
+
+/// answers everything the way it was answered while recording
+pub fn recorded(recording: &Recording) -> Vec<Route> {
+	let mut routes: Vec<Route> = Vec::new();
+	for entry in &recording.entries {
+		let Entry::Rest { method, path, status, body, .. } = entry else {
+			continue;
+		};
+		let path = path.split('?').next().unwrap_or_default();
+
+		match routes.iter().position(|r| r.method == *method && r.path == path) {
+			| Some(i) => routes[i].responses.push((*status, body.clone())),
+			| None => routes.push(Route::new(method, path).respond(*status, body)),
+		}
+	}
+	routes
+}
+
+/// login, mfa, messages and channels answering with the fixtures, login asking for mfa
+pub fn discord() -> Vec<Route> {
+	vec![
+		Route::new("POST", "/api/v9/auth/login").respond(200, include_str!("../models/fixtures/login_mfa.json")),
+		Route::new("POST", "/api/v9/auth/mfa/*").respond(200, include_str!("../models/fixtures/login_success.json")),
+		Route::new("GET", "/api/v9/channels/*/messages")
+			.respond(200, include_str!("../models/fixtures/message_history.json")),
+		Route::new("POST", "/api/v9/channels/*/messages")
+			.respond(200, include_str!("../models/fixtures/message_create.json")),
+		Route::new("GET", "/api/v9/guilds/*/channels").respond(200, include_str!("../models/fixtures/channels.json")),
+		Route::new("GET", "/api/v9/users/@me/channels")
+			.respond(200, include_str!("../models/fixtures/private_channels.json")),
+	]
+}
+
+#[derive(Clone, Debug)]
+pub struct Request {
+	pub method:  String,
+	pub path:    String,
+	pub headers: Vec<(String, String)>,
+	pub body:    String,
+}
+
+impl Request {
+	pub fn header(
+		&self,
+		name: &str,
+	) -> Option<&str> {
+		self.headers
+			.iter()
+			.find(|(n, _)| n.eq_ignore_ascii_case(name))
+			.map(|(_, v)| v.as_str())
+	}
+
+	pub fn json(&self) -> Value { serde_json::from_str(&self.body).unwrap() }
+}
+
+pub struct MockRest {
+	pub url:  String, // the api base, without the /api
+	requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockRest {
+	/// earlier routes win when more than one matches
+	pub async fn start(routes: Vec<Route>) -> Self {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let url = format!("http://{}", listener.local_addr().unwrap());
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let routes = Arc::new(Mutex::new(routes));
+
+		let recorded = requests.clone();
+		tokio::spawn(async move {
+			while let Ok((stream, _)) = listener.accept().await {
+				tokio::spawn(serve(stream, routes.clone(), recorded.clone()));
+			}
+		});
+		MockRest { url, requests }
+	}
+
+	pub fn requests(&self) -> Vec<Request> { self.requests.lock().unwrap().clone() }
+}
+
+async fn serve(
+	stream: TcpStream,
+	routes: Arc<Mutex<Vec<Route>>>,
+	requests: Arc<Mutex<Vec<Request>>>,
+) {
+	let mut stream = BufReader::new(stream);
+
+	while let Some(request) = read_request(&mut stream).await {
+		let (status, body) = routes
+			.lock()
+			.unwrap()
+			.iter_mut()
+			.find(|r| r.matches(&request.method, &request.path))
+			.map_or((404, r#"{"message": "404: Not Found", "code": 0}"#.to_string()), Route::next);
+		requests.lock().unwrap().push(request);
+
+		let reason = StatusCode::from_u16(status).ok().and_then(|s| s.canonical_reason()).unwrap_or("");
+		let response = format!(
+			"HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+			status,
+			reason,
+			body.len(),
+			body
+		);
+		if stream.write_all(response.as_bytes()).await.is_err() {
+			return;
+		}
+	}
+}
+
+/// `None` once the client's done with the connection
+async fn read_request(stream: &mut BufReader<TcpStream>) -> Option<Request> {
+	let mut line = String::new();
+	stream.read_line(&mut line).await.ok().filter(|&read| read > 0)?;
+	let mut parts = line.split_whitespace();
+	let method = parts.next()?.to_string();
+	let target = parts.next()?;
+	let path = target.split('?').next()?.to_string();
+
+	let mut headers = Vec::new();
+	loop {
+		let mut line = String::new();
+		stream.read_line(&mut line).await.ok()?;
+		let line = line.trim_end();
+		if line.is_empty() {
+			break;
+		}
+		let (name, value) = line.split_once(':')?;
+		headers.push((name.trim().to_string(), value.trim().to_string()));
+	}
+
+	let length = headers
+		.iter()
+		.find(|(n, _)| n.eq_ignore_ascii_case("content-length"))
+		.and_then(|(_, v)| v.parse().ok())
+		.unwrap_or(0);
+	let mut body = vec![0; length];
+	stream.read_exact(&mut body).await.ok()?;
+
+	Some(Request {
+		method,
+		path,
+		headers,
+		body: String::from_utf8(body).ok()?,
+	})
+}
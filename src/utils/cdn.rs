@@ -0,0 +1,66 @@
+// https://docs.discord.sex/reference#cdn-formatting
+
+use crate::models::types::Snowflake;
+use crate::models::user::user::PartialUser;
+use crate::utils::config;
+
+fn cdn() -> String { config::get().cdn_base }
+
+pub fn avatar(
+	user: &PartialUser,
+	size: u16,
+) -> String {
+	match &user.avatar {
+		| Some(hash) => format!("{}/avatars/{}/{}.webp?size={}", cdn(), user.id, hash, size),
+		| None => default_avatar(user.id, user.discriminator.as_deref()),
+	}
+}
+
+pub fn default_avatar(
+	user_id: Snowflake,
+	discriminator: Option<&str>,
+) -> String {
+	// legacy usernames still use the discriminator
+	let index = match discriminator.and_then(|d| d.parse::<u64>().ok()) {
+		| Some(discriminator) if discriminator != 0 => discriminator % 5,
+		| _ => (user_id.raw() >> 22) % 6,
+	};
+
+	format!("{}/embed/avatars/{}.png", cdn(), index)
+}
+
+pub fn emoji(
+	emoji_id: Snowflake,
+	animated: bool,
+	size: u16,
+) -> String {
+	let extension = if animated { "gif" } else { "webp" };
+
+	format!("{}/emojis/{}.{}?size={}", cdn(), emoji_id, extension, size)
+}
+
+pub fn guild_icon(
+	guild_id: Snowflake,
+	hash: &str,
+	size: u16,
+) -> String {
+	format!("{}/icons/{}/{}.webp?size={}", cdn(), guild_id, hash, size)
+}
+
+pub fn channel_icon(
+	channel_id: Snowflake,
+	hash: &str,
+	size: u16,
+) -> String {
+	format!("{}/channel-icons/{}/{}.webp?size={}", cdn(), channel_id, hash, size)
+}
+
+pub fn banner(
+	user_id: Snowflake,
+	hash: &str,
+	size: u16,
+) -> String {
+	let extension = if hash.starts_with("a_") { "gif" } else { "webp" };
+
+	format!("{}/banners/{}/{}.{}?size={}", cdn(), user_id, hash, extension, size)
+}
@@ -0,0 +1,131 @@
+// which discord-compatible backend the client talks to. picked on the login screen and saved with the other
+// local settings, tests point it at the mock servers
+
+use std::cell::RefCell;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::platform;
+use crate::utils::storage::{self, Storage};
+
+const CONFIG_KEY: &str = "config";
+// the qr code login only exists on discord, every instance points here
+const REMOTE_AUTH_URL: &str = "wss://remote-auth-gateway.discord.gg/?v=2";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Instance {
+	Discord,
+	Canary,
+	Ptb,
+	/// something serving the api locally, eg. a mock
+	Local,
+	/// https://spacebar.chat, a self-hosted reimplementation
+	Spacebar,
+}
+
+impl Instance {
+	pub const ALL: [Instance; 5] = [Instance::Discord, Instance::Canary, Instance::Ptb, Instance::Local, Instance::Spacebar];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			| Instance::Discord => "Discord",
+			| Instance::Canary => "Discord Canary",
+			| Instance::Ptb => "Discord PTB",
+			| Instance::Local => "Local",
+			| Instance::Spacebar => "Spacebar",
+		}
+	}
+
+	/// the ones that aren't discord live wherever they're hosted, so their urls can be changed
+	pub fn custom(self) -> bool { matches!(self, Instance::Local | Instance::Spacebar) }
+
+	/// where it usually is
+	pub fn config(self) -> Config {
+		let (api_base, cdn_base) = match self {
+			| Instance::Discord => ("https://discord.com", "https://cdn.discordapp.com"),
+			| Instance::Canary => ("https://canary.discord.com", "https://cdn.discordapp.com"),
+			| Instance::Ptb => ("https://ptb.discord.com", "https://cdn.discordapp.com"),
+			| Instance::Local => ("http://localhost:8080", "http://localhost:8080"),
+			// the default port of the all in one server
+			| Instance::Spacebar => ("http://localhost:3001", "http://localhost:3001"),
+		};
+
+		Config {
+			instance:    self,
+			api_base:    api_base.to_string(),
+			api_version: 9,
+			cdn_base:    cdn_base.to_string(),
+			gateway_url: None,
+
+			remote_auth_url: REMOTE_AUTH_URL.to_string(),
+		}
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Config {
+	pub instance:    Instance,
+	pub api_base:    String, // everything up to the /api
+	pub api_version: u8,
+	pub cdn_base:    String,
+	#[serde(skip)]
+	pub gateway_url: Option<String>, // asked for at /gateway once per session
+
+	// configs saved before it was here get discord's
+	#[serde(default = "remote_auth_url")]
+	pub remote_auth_url: String,
+}
+
+fn remote_auth_url() -> String { REMOTE_AUTH_URL.to_string() }
+
+impl Config {
+	/// discord's remote auth gateway turns away anything without discord.com as the origin, which a
+	/// browser won't fake, so the web build can only get there through a proxy set as `remote_auth_url`
+	pub fn remote_auth_reachable(&self) -> bool {
+		!cfg!(target_arch = "wasm32") || self.remote_auth_url != REMOTE_AUTH_URL
+	}
+}
+
+impl Default for Config {
+	fn default() -> Self { Instance::Discord.config() }
+}
+
+thread_local! {
+	static CONFIG: RefCell<Config> = RefCell::new(Config::default());
+}
+
+pub fn get() -> Config { CONFIG.with_borrow(Config::clone) }
+
+pub fn set(config: Config) {
+	if let Err(e) = storage::local().set(CONFIG_KEY, &config) {
+		platform::error(&format!("Failed to save config: {}", e));
+	}
+	CONFIG.set(config);
+}
+
+/// reads the saved config, has to happen before anything makes a request
+pub fn load() {
+	if let Some(config) = storage::local().get::<Config>(CONFIG_KEY) {
+		CONFIG.set(config);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn configs_survive_a_reload() {
+		let config = Config {
+			api_base: "https://spacebar.example".to_string(),
+			gateway_url: Some("wss://gateway.spacebar.example".to_string()),
+			..Instance::Spacebar.config()
+		};
+		set(config.clone());
+
+		CONFIG.set(Config::default());
+		load();
+		// the gateway gets asked for again
+		assert_eq!(get(), Config { gateway_url: None, ..config });
+	}
+}
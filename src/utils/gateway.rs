@@ -0,0 +1,741 @@
+use std::cell::RefCell;
+use std::pin::pin;
+
+use dioxus::prelude::*;
+use futures_channel::mpsc::UnboundedSender;
+use futures_util::future::{Either, Fuse, select};
+use futures_util::{FutureExt, Stream, StreamExt};
+use serde_json::json;
+
+use crate::models::chat::{Channel, Message};
+use crate::models::guild::{GuildMember, GuildMembersChunk, MemberListUpdate};
+use crate::models::presence::Presence;
+use crate::models::read_state::MessageAck;
+use crate::models::relationship::Relationship;
+use crate::models::types::Snowflake;
+use crate::models::user::settings::{GuildFolder, PartialUserSettingsProto, UserGuildSettings};
+use crate::models::user::user::PartialUser;
+use crate::models::voice::VoiceState;
+use crate::models::websocket::{Gateway, GatewayRecieveEvent, Ready};
+use crate::store::{
+	self,
+	cache,
+	GUILD_MEMBERS,
+	GUILDS,
+	MEMBER_LISTS,
+	MESSAGES,
+	PRESENCES,
+	PRIVATE_CHANNELS,
+	READ_STATES,
+	RELATIONSHIPS,
+	SESSIONS,
+	SETTINGS,
+	USERS,
+};
+use crate::utils::config::{self, Config};
+use crate::utils::platform::{self, Connection};
+use crate::utils::recorder;
+use crate::utils::request::RequestClient;
+use crate::utils::token;
+
+// the longest we wait between attempts when the gateway can't be reached
+const MAX_BACKOFF_MS: u32 = 30_000;
+
+thread_local! {
+	static SOCKET: RefCell<Option<UnboundedSender<String>>> = const { RefCell::new(None) };
+}
+
+/// sends an opcode over the current connection, dropped if there isn't one yet
+pub fn send(
+	op: u8,
+	d: serde_json::Value,
+) {
+	SOCKET.with_borrow(|socket| {
+		if let Some(outgoing) = socket {
+			let _ = outgoing.unbounded_send(json!({ "op": op, "d": d }).to_string());
+		}
+	});
+}
+
+/// closes the current connection for good, eg. when logging out
+pub fn disconnect() {
+	if let Some(outgoing) = SOCKET.take() {
+		outgoing.close_channel();
+	}
+}
+
+/// connects to the gateway for as long as the calling component is mounted, dispatch events get applied to the stores
+pub fn use_gateway() {
+	use_hook(|| {
+		let Some(token) = token::get() else {
+			return;
+		};
+		// whatever's left is from before, maybe another account, READY fills it all back in
+		store::clear();
+
+		spawn(async move {
+			// a recording stands in for discord while it's replayed
+			if let Some(recording) = recorder::replay() {
+				recorder::play(&recording, handle_dispatch).await;
+				return;
+			}
+
+			// something to show while connecting, or while offline
+			cache::hydrate().await;
+			let url = gateway_url().await;
+			keep_connected(&url, &token, handle_dispatch).await;
+		});
+	});
+
+	use_drop(disconnect);
+}
+
+/// asks the api where the gateway is until it answers, once per session
+async fn gateway_url() -> String {
+	let mut failures = 0;
+	loop {
+		let config = config::get();
+		if let Some(url) = config.gateway_url {
+			return url;
+		}
+
+		match RequestClient::new().get::<Gateway>("/gateway").await {
+			| Ok(gateway) => config::set(Config {
+				gateway_url: Some(gateway.url),
+				..config
+			}),
+			| Err(e) => {
+				platform::error(&format!("Failed to find the gateway: {}", e));
+				failures += 1;
+				platform::sleep(backoff(failures)).await;
+			},
+		}
+	}
+}
+
+/// what's needed to pick up where a dropped connection left off
+#[derive(Default, Debug)]
+pub struct Session {
+	pub id:         Option<String>,
+	pub sequence:   Option<i64>,
+	pub resume_url: Option<String>,
+}
+
+impl Session {
+	fn forget(&mut self) { *self = Session::default(); }
+}
+
+/// how a connection ended
+#[derive(Debug, PartialEq)]
+pub enum Closed {
+	/// discord asked us to, or stopped answering heartbeats. the session can be resumed
+	Reconnect,
+	/// the session's gone, the next connection has to identify from scratch
+	InvalidSession,
+	/// the socket closed, either end could've done it
+	Hangup,
+	/// closed with a code that connecting again won't fix, a bad token or a bug on our end
+	Fatal(u16),
+}
+
+impl Closed {
+	/// what discord closing with `code` means for the next connection.
+	/// https://docs.discord.sex/topics/opcodes-and-status-codes#gateway-close-event-codes
+	fn from_code(code: u16) -> Self {
+		match code {
+			// authentication failed, or sharding, the api version or intents are wrong
+			| 4004 | 4010..=4014 => Closed::Fatal(code),
+			// invalid sequence, session timed out
+			| 4007 | 4009 => Closed::InvalidSession,
+			| _ => Closed::Hangup,
+		}
+	}
+}
+
+/// reconnects whenever the connection drops until `disconnect` is called or discord closes it for good,
+/// resuming the session when discord allows it
+pub async fn keep_connected(
+	url: &str,
+	token: &str,
+	mut on_dispatch: impl FnMut(GatewayRecieveEvent),
+) {
+	let mut session = Session::default();
+	let mut failures = 0;
+
+	loop {
+		// the gateway only takes these as query params, resume urls come without them
+		let url = format!(
+			"{}/?encoding=json&v={}",
+			session.resume_url.as_deref().unwrap_or(url),
+			config::get().api_version
+		);
+		match platform::connect(&url, None).await {
+			| Ok(Connection {
+				incoming,
+				outgoing,
+				mut close_code,
+			}) => {
+				SOCKET.set(Some(outgoing.clone()));
+				let sequence = session.sequence;
+				let mut closed = run(incoming, &outgoing, token, &mut session, &mut on_dispatch).await;
+				// `disconnect` took it
+				if SOCKET.with_borrow(Option::is_none) {
+					return;
+				}
+				// finishes the close handshake when it was discord that hung up
+				outgoing.close_channel();
+				if closed == Closed::Hangup
+					&& let Ok(Some(code)) = close_code.try_recv()
+				{
+					closed = Closed::from_code(code);
+				}
+
+				platform::log(&format!("Gateway connection ended: {:?}", closed));
+				match closed {
+					| Closed::Fatal(code) => {
+						SOCKET.take();
+						// only a bad token needs them to log in again, the rest are our bugs
+						if code == 4004 {
+							token::expired();
+						} else {
+							platform::error(&format!("Gateway closed with {}, not reconnecting", code));
+						}
+						return;
+					},
+					| Closed::InvalidSession => session.forget(),
+					| _ => {},
+				}
+				// anything dispatched means it got going, however it ended
+				failures = if session.sequence != sequence { 0 } else { failures + 1 };
+			},
+			| Err(e) => {
+				platform::error(&format!("Failed to connect to the gateway: {}", e));
+				failures += 1;
+			},
+		}
+
+		if failures > 0 {
+			platform::sleep(backoff(failures)).await;
+		}
+	}
+}
+
+/// 1s, 2s, 4s... up to `MAX_BACKOFF_MS`
+fn backoff(failures: u32) -> u32 { 500u32.saturating_mul(1 << failures.min(16)).min(MAX_BACKOFF_MS) }
+
+fn identify(token: &str) -> serde_json::Value {
+	json!({
+		"op": 2,
+		"d": {
+			"token": token,
+			"intents": 3276799,
+			"properties": {
+				// TODO: get real properties
+				"os": "Windows",
+				"browser": "Chrome",
+				"device": ""
+			}
+		}
+	})
+}
+
+fn resume(
+	token: &str,
+	session_id: &str,
+	sequence: Option<i64>,
+) -> serde_json::Value {
+	json!({
+		"op": 6,
+		"d": {
+			"token": token,
+			"session_id": session_id,
+			"seq": sequence,
+		}
+	})
+}
+
+/// one connection from HELLO until it closes, heartbeating in between and keeping `session` up to
+/// date. dispatch events go to `on_dispatch`, everything else is handled here
+pub async fn run(
+	mut incoming: impl Stream<Item = String> + Unpin,
+	outgoing: &UnboundedSender<String>,
+	token: &str,
+	session: &mut Session,
+	mut on_dispatch: impl FnMut(GatewayRecieveEvent),
+) -> Closed {
+	let send = |payload: serde_json::Value| {
+		let _ = outgoing.unbounded_send(payload.to_string());
+	};
+	let mut interval_ms = 0;
+	let mut acked = true;
+	// doesn't start ticking until HELLO says how often
+	let mut heartbeat = pin!(Fuse::terminated());
+
+	loop {
+		let text = match select(incoming.next(), heartbeat.as_mut()).await {
+			| Either::Left((Some(text), _)) => text,
+			| Either::Left((None, _)) => return Closed::Hangup,
+			| Either::Right(_) => {
+				// the last one never got acked, the connection is probably dead
+				if !acked {
+					outgoing.close_channel();
+					return Closed::Reconnect;
+				}
+				send(json!({ "op": 1, "d": session.sequence }));
+				acked = false;
+				heartbeat.set(platform::sleep(interval_ms).fuse());
+				continue;
+			},
+		};
+
+		recorder::gateway(&text);
+		let data = match serde_json::from_str::<GatewayRecieveEvent>(&text) {
+			| Ok(data) => data,
+			| Err(e) => {
+				platform::error(&format!("Unparseable gateway message {}: {}", text, e));
+				continue;
+			},
+		};
+		if let Some(seq) = data.s {
+			session.sequence = Some(seq);
+		}
+
+		match data.op {
+			| 0 => {
+				// dispatch
+				if data.t.as_deref() == Some("READY") {
+					session.id = data.d["session_id"].as_str().map(str::to_string);
+					session.resume_url = data.d["resume_gateway_url"].as_str().map(str::to_string);
+				}
+				on_dispatch(data);
+			},
+			| 1 => send(json!({ "op": 1, "d": session.sequence })), // heartbeat request
+			| 7 => {
+				// reconnect
+				outgoing.close_channel();
+				return Closed::Reconnect;
+			},
+			| 9 => {
+				// invalid session, `d` says whether it can still be resumed
+				if data.d != true {
+					session.forget();
+				}
+				outgoing.close_channel();
+				return if data.d == true { Closed::Reconnect } else { Closed::InvalidSession };
+			},
+			| 10 => {
+				// hello
+				interval_ms = data.d["heartbeat_interval"].as_f64().unwrap_or(41250.0) as u32;
+				heartbeat.set(platform::sleep(interval_ms).fuse());
+				match &session.id {
+					| Some(id) => send(resume(token, id, session.sequence)),
+					| None => send(identify(token)),
+				}
+			},
+			| 11 => acked = true, // heartbeat ack
+			| _ => {
+				// this should never happen unless discord adds something
+				platform::error(&format!("Unhandled response, please report this: {:#?}", data));
+			},
+		}
+	}
+}
+
+fn guild_folders(proto: PartialUserSettingsProto) -> Vec<GuildFolder> {
+	proto
+		.guild_folders
+		.map(|f| f.folders.into_iter().map(GuildFolder::from).collect())
+		.unwrap_or_default()
+}
+
+fn handle_dispatch(data: GatewayRecieveEvent) {
+	if let Some(event) = data.t.as_deref() {
+		match event {
+			| "READY" => match serde_json::from_value::<Ready>(data.d) {
+				| Ok(ready) => {
+					let me: PartialUser = ready.user.user.into();
+					spawn(token::remember(me.clone()));
+					let mut guild_members = GUILD_MEMBERS.write();
+					for (guild, members) in ready.guilds.iter().zip(ready.merged_members) {
+						// merged members only have a user_id, which is always us
+						guild_members.extend(
+							guild.id,
+							members.into_iter().map(|m| GuildMember {
+								user: Some(me.clone()),
+								..m
+							}),
+						);
+					}
+
+					let mut users = USERS.write();
+					users.set_current(me);
+					users.extend(ready.users);
+					users.extend(ready.relationships.iter().filter_map(|r| r.user.clone()));
+					RELATIONSHIPS.write().set(ready.relationships);
+
+					if let Some(presences) = ready.merged_presences {
+						let mut store = PRESENCES.write();
+						store.extend(presences.friends);
+						store.extend(presences.guilds.into_iter().flatten());
+					}
+
+					SESSIONS.write().current = ready.auth_session_id_hash;
+					GUILDS.write().set(ready.guilds);
+					PRIVATE_CHANNELS.write().set(ready.private_channels);
+
+					if let Some(read_state) = ready.read_state {
+						READ_STATES.write().set(read_state.entries());
+					}
+
+					let mut settings = SETTINGS.write();
+					for guild_settings in ready.user_guild_settings.map(|s| s.entries()).unwrap_or_default() {
+						settings.set_guild_settings(guild_settings);
+					}
+					match ready
+						.user_settings_proto
+						.as_deref()
+						.and_then(PartialUserSettingsProto::from_base64)
+					{
+						| Some(proto) => {
+							settings.guild_folders = guild_folders(proto);
+							settings.from_proto = true;
+						},
+						| None => {
+							#[allow(deprecated)]
+							let legacy = ready.user_settings;
+							settings.guild_folders = legacy.map(|s| s.guild_folders).unwrap_or_default();
+						},
+					}
+					spawn(cache::save_state());
+				},
+				| Err(e) => platform::error(&format!("Bad READY: {}", e)),
+			},
+			// https://docs.discord.sex/topics/gateway-events#user-settings-proto-update, type 1 is the one with guild folders
+			| "USER_SETTINGS_PROTO_UPDATE" => {
+				let proto = data.d["settings"]["proto"]
+					.as_str()
+					.filter(|_| data.d["settings"]["type"] == 1)
+					.and_then(PartialUserSettingsProto::from_base64);
+
+				if let Some(proto) = proto
+					&& proto.guild_folders.is_some()
+				{
+					SETTINGS.write().guild_folders = guild_folders(proto);
+				}
+			},
+			| "USER_GUILD_SETTINGS_UPDATE" => match serde_json::from_value::<UserGuildSettings>(data.d) {
+				| Ok(settings) => SETTINGS.write().set_guild_settings(settings),
+				| Err(e) => platform::error(&format!("Bad USER_GUILD_SETTINGS_UPDATE: {}", e)),
+			},
+			| "CHANNEL_CREATE" | "CHANNEL_UPDATE" | "THREAD_CREATE" | "THREAD_UPDATE" => {
+				match serde_json::from_value::<Channel>(data.d) {
+					| Ok(channel) if channel.guild_id.is_some() => GUILDS.write().upsert_channel(channel),
+					| Ok(channel) => {
+						USERS.write().extend(channel.recipients.clone());
+						PRIVATE_CHANNELS.write().upsert(channel);
+					},
+					| Err(e) => platform::error(&format!("Bad {}: {}", event, e)),
+				}
+			},
+			| "CHANNEL_DELETE" | "THREAD_DELETE" => {
+				let guild_id = data.d["guild_id"].as_str().and_then(|s| s.parse().ok());
+				let channel_id = data.d["id"].as_str().and_then(|s| s.parse().ok());
+
+				match (guild_id, channel_id) {
+					| (Some(guild_id), Some(channel_id)) => GUILDS.write().remove_channel(guild_id, channel_id),
+					| (None, Some(channel_id)) => PRIVATE_CHANNELS.write().remove(channel_id),
+					| _ => {},
+				}
+			},
+			// https://docs.discord.sex/topics/gateway-events#channel-recipient-add
+			| "CHANNEL_RECIPIENT_ADD" | "CHANNEL_RECIPIENT_REMOVE" => {
+				let channel_id = data.d["channel_id"].as_str().and_then(|s| s.parse().ok());
+
+				match (channel_id, serde_json::from_value::<PartialUser>(data.d["user"].clone())) {
+					| (Some(channel_id), Ok(user)) => {
+						let user_id = user.id;
+						USERS.write().insert(user);
+
+						if event == "CHANNEL_RECIPIENT_ADD" {
+							PRIVATE_CHANNELS.write().add_recipient(channel_id, user_id);
+						} else {
+							PRIVATE_CHANNELS.write().remove_recipient(channel_id, user_id);
+						}
+					},
+					| (_, Err(e)) => platform::error(&format!("Bad {}: {}", event, e)),
+					| _ => {},
+				}
+			},
+			| "VOICE_STATE_UPDATE" => match serde_json::from_value::<VoiceState>(data.d) {
+				| Ok(state) => {
+					if let (Some(guild_id), Some(member)) = (state.guild_id, &state.member) {
+						GUILD_MEMBERS.write().insert(guild_id, member.clone());
+					}
+					GUILDS.write().update_voice_state(state);
+				},
+				| Err(e) => platform::error(&format!("Bad VOICE_STATE_UPDATE: {}", e)),
+			},
+			| "GUILD_MEMBER_UPDATE" => {
+				let guild_id = data.d["guild_id"].as_str().and_then(|s| s.parse().ok());
+
+				match (guild_id, serde_json::from_value::<GuildMember>(data.d)) {
+					| (Some(guild_id), Ok(member)) => GUILD_MEMBERS.write().insert(guild_id, member),
+					| (_, Err(e)) => platform::error(&format!("Bad GUILD_MEMBER_UPDATE: {}", e)),
+					| _ => {},
+				}
+			},
+			| "RELATIONSHIP_ADD" | "RELATIONSHIP_UPDATE" => match serde_json::from_value::<Relationship>(data.d) {
+				| Ok(relationship) => {
+					if let Some(user) = relationship.user.clone() {
+						USERS.write().insert(user);
+					}
+					RELATIONSHIPS.write().upsert(relationship);
+				},
+				| Err(e) => platform::error(&format!("Bad {}: {}", event, e)),
+			},
+			| "RELATIONSHIP_REMOVE" => {
+				if let Some(user_id) = data.d["id"].as_str().and_then(|s| s.parse().ok()) {
+					RELATIONSHIPS.write().remove(user_id);
+				}
+			},
+			// the gateway sessions changed, which usually means a login or logout somewhere
+			| "SESSIONS_REPLACE" => SESSIONS.write().replaced(),
+			| "PRESENCE_UPDATE" => match serde_json::from_value::<Presence>(data.d) {
+				| Ok(presence) => PRESENCES.write().insert(presence),
+				| Err(e) => platform::error(&format!("Bad PRESENCE_UPDATE: {}", e)),
+			},
+			| "GUILD_MEMBER_LIST_UPDATE" => match serde_json::from_value::<MemberListUpdate>(data.d) {
+				| Ok(update) => {
+					GUILD_MEMBERS
+						.write()
+						.extend(update.guild_id, update.members().cloned());
+					MEMBER_LISTS.write().apply(update);
+				},
+				| Err(e) => platform::error(&format!("Bad GUILD_MEMBER_LIST_UPDATE: {}", e)),
+			},
+			| "GUILD_MEMBERS_CHUNK" => match serde_json::from_value::<GuildMembersChunk>(data.d) {
+				| Ok(chunk) => GUILD_MEMBERS.write().extend(chunk.guild_id, chunk.members),
+				| Err(e) => platform::error(&format!("Bad GUILD_MEMBERS_CHUNK: {}", e)),
+			},
+			| "MESSAGE_CREATE" => match serde_json::from_value::<Message>(data.d) {
+				| Ok(message) => {
+					USERS.write().extend_from_messages(std::slice::from_ref(&message));
+					// the member comes without its user here
+					if let (Some(guild_id), Some(member)) = (message.guild_id, &message.member) {
+						GUILD_MEMBERS.write().insert(guild_id, GuildMember {
+							user: Some(message.author.clone()),
+							..member.clone()
+						});
+					}
+
+					match message.guild_id {
+						| Some(_) => GUILDS.write().set_last_message(message.channel_id, message.id),
+						| None => PRIVATE_CHANNELS.write().set_last_message(message.channel_id, message.id),
+					}
+					if let Some(me) = USERS.peek().current().map(|u| u.id) {
+						let roles = message
+							.guild_id
+							.and_then(|guild_id| GUILD_MEMBERS.peek().member(guild_id, me).map(|m| m.roles.clone()))
+							.unwrap_or_default();
+
+						// whatever we send counts as read
+						if message.author.id == me {
+							READ_STATES.write().ack(message.channel_id, message.id, 0);
+						} else if message.mentions(me, &roles) {
+							READ_STATES.write().add_mention(message.channel_id);
+						}
+					}
+
+					MESSAGES.write().insert(message);
+				},
+				| Err(e) => platform::error(&format!("Bad MESSAGE_CREATE: {}", e)),
+			},
+			| "MESSAGE_ACK" => match serde_json::from_value::<MessageAck>(data.d) {
+				| Ok(ack) => READ_STATES.write().ack(ack.channel_id, ack.message_id, ack.mention_count),
+				| Err(e) => platform::error(&format!("Bad MESSAGE_ACK: {}", e)),
+			},
+			| "MESSAGE_UPDATE" => MESSAGES.write().update(&data.d),
+			| "MESSAGE_DELETE" | "MESSAGE_DELETE_BULK" => {
+				let channel_id = data.d["channel_id"].as_str().and_then(|s| s.parse().ok());
+				let ids: Vec<Snowflake> = match data.d.get("ids") {
+					| Some(ids) => serde_json::from_value(ids.clone()).unwrap_or_default(),
+					| None => data.d["id"].as_str().and_then(|s| s.parse().ok()).into_iter().collect(),
+				};
+
+				if let Some(channel_id) = channel_id {
+					MESSAGES.write().delete(channel_id, &ids);
+				}
+			},
+			| _ => {},
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::pin::pin;
+	use std::time::Duration;
+
+	use futures_util::future::{Either, select};
+
+	use super::*;
+	use crate::testing::gateway::{self as mock, MockGateway, SESSION_ID, Step};
+	use crate::testing::in_runtime;
+	use crate::testing::rest::{MockRest, Route};
+
+	/// stays connected until `count` events have been dispatched, then hangs up
+	async fn dispatched(
+		url: &str,
+		count: usize,
+	) -> Vec<String> {
+		let (sender, mut receiver) = futures_channel::mpsc::unbounded();
+		let client = keep_connected(url, "mock-token", move |event| {
+			let _ = sender.unbounded_send(event.t.unwrap_or_default());
+		});
+		let events = receiver.by_ref().take(count).collect::<Vec<_>>();
+
+		let events = match tokio::time::timeout(Duration::from_secs(5), select(pin!(client), events)).await {
+			| Ok(Either::Right((events, _))) => events,
+			| Ok(Either::Left(_)) => panic!("the client gave up"),
+			| Err(_) => panic!("timed out waiting for {} events", count),
+		};
+		disconnect();
+		events
+	}
+
+	#[tokio::test]
+	async fn heartbeats_carry_the_sequence() {
+		let mock = MockGateway::start(vec![vec![
+			Step::Hello,
+			Step::ExpectIdentify,
+			Step::Ready,
+			Step::ExpectHeartbeat,
+			Step::Close,
+		]])
+		.await;
+
+		let Connection { incoming, outgoing, .. } = platform::connect(&mock.url, None).await.unwrap();
+		let mut session = Session::default();
+		let mut dispatched = Vec::new();
+		let closed = run(incoming, &outgoing, "mock-token", &mut session, |event| dispatched.push(event.t));
+		let closed = tokio::time::timeout(Duration::from_secs(5), closed)
+			.await
+			.expect("the session should end when the server hangs up");
+
+		assert_eq!(closed, Closed::Hangup);
+		assert_eq!(dispatched, [Some("READY".to_string())]);
+		assert_eq!(session.id.as_deref(), Some(SESSION_ID));
+
+		let received = &mock.received()[0];
+		assert_eq!(received[0]["op"], 2);
+		assert_eq!(received[0]["d"]["token"], "mock-token");
+		assert_eq!(received.last().unwrap(), &json!({ "op": 1, "d": 1 }));
+	}
+
+	#[tokio::test]
+	async fn reconnects_resume_the_session() {
+		let mock = MockGateway::start(vec![
+			vec![
+				Step::Hello,
+				Step::ExpectIdentify,
+				Step::Ready,
+				Step::Dispatch("TYPING_START", json!({})),
+				Step::Reconnect,
+			],
+			vec![Step::Hello, Step::ExpectResume, Step::Dispatch("RESUMED", json!({}))],
+		])
+		.await;
+
+		assert_eq!(dispatched(&mock.url, 3).await, ["READY", "TYPING_START", "RESUMED"]);
+
+		let received = mock.received();
+		let resume = received[1].iter().find(|p| p["op"] == 6).unwrap();
+		assert_eq!(resume["d"], json!({ "token": "mock-token", "session_id": SESSION_ID, "seq": 2 }));
+		assert!(received[1].iter().all(|p| p["op"] != 2), "a resumed session shouldn't identify");
+	}
+
+	#[tokio::test]
+	async fn invalid_sessions_identify_again() {
+		let mock = MockGateway::start(vec![
+			vec![Step::Hello, Step::ExpectIdentify, Step::Ready, Step::InvalidSession(false)],
+			vec![Step::Hello, Step::ExpectIdentify, Step::Ready],
+		])
+		.await;
+
+		assert_eq!(dispatched(&mock.url, 2).await, ["READY", "READY"]);
+
+		let received = mock.received();
+		assert!(received[1].iter().any(|p| p["op"] == 2));
+		assert!(received[1].iter().all(|p| p["op"] != 6), "an invalid session can't be resumed");
+	}
+
+	#[tokio::test]
+	async fn fatal_close_codes_stop_reconnecting() {
+		for code in [4004, 4010, 4011, 4012, 4013, 4014] {
+			let mock = MockGateway::start(vec![vec![Step::Hello, Step::ExpectIdentify, Step::Ready, Step::CloseWith(code)]])
+				.await;
+
+			in_runtime(async {
+				*token::SESSION_EXPIRED.write() = false;
+				let client = keep_connected(&mock.url, "mock-token", |_| {});
+				tokio::time::timeout(Duration::from_secs(5), client)
+					.await
+					.unwrap_or_else(|_| panic!("{} should've stopped the client", code));
+				assert_eq!(*token::SESSION_EXPIRED.peek(), code == 4004, "only 4004 means the token's bad");
+			})
+			.await;
+			assert_eq!(mock.received().len(), 1, "{} shouldn't reconnect", code);
+		}
+	}
+
+	#[tokio::test]
+	async fn expired_sessions_identify_again() {
+		for code in [4007, 4009] {
+			let mock = MockGateway::start(vec![
+				vec![Step::Hello, Step::ExpectIdentify, Step::Ready, Step::CloseWith(code)],
+				vec![Step::Hello, Step::ExpectIdentify, Step::Ready],
+			])
+			.await;
+
+			assert_eq!(dispatched(&mock.url, 2).await, ["READY", "READY"]);
+
+			let received = mock.received();
+			assert!(received[1].iter().any(|p| p["op"] == 2), "{} should identify again", code);
+			assert!(received[1].iter().all(|p| p["op"] != 6), "{} can't be resumed", code);
+		}
+	}
+
+	#[tokio::test]
+	async fn the_gateway_is_asked_for_once() {
+		let rest = MockRest::start(vec![
+			Route::new("GET", "/api/v9/gateway").respond(200, r#"{"url": "wss://gateway.spacebar.example"}"#),
+		])
+		.await;
+		config::set(Config {
+			api_base: rest.url.clone(),
+			..Config::default()
+		});
+
+		assert_eq!(in_runtime(gateway_url()).await, "wss://gateway.spacebar.example");
+		assert_eq!(in_runtime(gateway_url()).await, "wss://gateway.spacebar.example");
+		assert_eq!(rest.requests().len(), 1);
+	}
+
+	#[tokio::test]
+	async fn recordings_play_back_through_the_mock() {
+		let live = MockGateway::start(vec![vec![
+			Step::Hello,
+			Step::ExpectIdentify,
+			Step::Ready,
+			Step::Dispatch("TYPING_START", json!({ "channel_id": "1" })),
+		]])
+		.await;
+		recorder::start();
+		assert_eq!(dispatched(&live.url, 2).await, ["READY", "TYPING_START"]);
+		recorder::stop().await;
+
+		let recording = recorder::saved().await.unwrap();
+		let replayed = MockGateway::start(mock::recorded(&recording)).await;
+		assert_eq!(dispatched(&replayed.url, 2).await, ["READY", "TYPING_START"]);
+	}
+}
@@ -0,0 +1,936 @@
+// discord flavored markdown, mostly follows the rules of simple-markdown which is what the official client uses
+// https://docs.discord.sex/reference#message-formatting
+
+use crate::models::types::Snowflake;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+	Text(String),
+	Emoji(String), // unicode, kept apart so it can be sized like custom ones
+	CustomEmoji {
+		name:     String,
+		id:       Snowflake,
+		animated: bool,
+	},
+	Bold(Vec<Node>),
+	Italic(Vec<Node>),
+	Underline(Vec<Node>),
+	Strikethrough(Vec<Node>),
+	Spoiler(Vec<Node>),
+	InlineCode(String),
+	CodeBlock {
+		language: Option<String>,
+		code:     String,
+	},
+	BlockQuote(Vec<Node>),
+	Heading {
+		level:    u8,
+		children: Vec<Node>,
+	},
+	Subtext(Vec<Node>),
+	List {
+		start: Option<u32>, // Some for ordered lists
+		items: Vec<Vec<Node>>,
+	},
+	Link {
+		url:      String,
+		children: Vec<Node>,
+	},
+	Url(String),
+	UserMention(Snowflake),
+	RoleMention(Snowflake),
+	ChannelMention(Snowflake),
+	Everyone,
+	Here,
+	Timestamp {
+		unix:  i64,
+		style: char, // https://docs.discord.sex/reference#timestamp-styles
+	},
+}
+
+pub fn parse(src: &str) -> Vec<Node> {
+	parse_with(src, Context {
+		in_quote:    false,
+		inline_only: false,
+		in_link:     false,
+	})
+}
+
+/// whether the message should get big emojis, which discord does for up to 30 emojis and nothing else
+pub fn is_emoji_only(nodes: &[Node]) -> bool {
+	let mut count = 0;
+
+	for node in nodes {
+		match node {
+			| Node::Emoji(_) | Node::CustomEmoji { .. } => count += 1,
+			| Node::Text(text) if text.trim().is_empty() => {},
+			| _ => return false,
+		}
+	}
+
+	count > 0 && count <= 30
+}
+
+#[derive(Clone, Copy)]
+struct Context {
+	in_quote:    bool,
+	inline_only: bool, // inside formatting, headings etc. where block syntax doesn't apply
+	in_link:     bool, // masked links can't nest or contain urls
+}
+
+impl Context {
+	fn inline(self) -> Self {
+		Context {
+			inline_only: true,
+			..self
+		}
+	}
+}
+
+fn parse_with(
+	src: &str,
+	ctx: Context,
+) -> Vec<Node> {
+	let mut nodes = Vec::new();
+	let mut text = String::new();
+	let mut i = 0;
+
+	while i < src.len() {
+		let rest = &src[i..];
+		let line_start = i == 0 || src[..i].ends_with('\n');
+
+		if line_start
+			&& !ctx.inline_only
+			&& let Some((node, len)) = parse_block(rest, ctx)
+		{
+			flush(&mut nodes, &mut text);
+			nodes.push(node);
+			i += len;
+			continue;
+		}
+
+		let previous = src[..i].chars().next_back();
+		if let Some((node, len)) = parse_inline(rest, previous, ctx) {
+			flush(&mut nodes, &mut text);
+			nodes.push(node);
+			i += len;
+			continue;
+		}
+
+		let c = rest.chars().next().unwrap();
+
+		if c == '\\'
+			&& let Some(escaped) = rest[1..].chars().next()
+			&& escaped.is_ascii_punctuation()
+		{
+			text.push(escaped);
+			i += 1 + escaped.len_utf8();
+			continue;
+		}
+
+		if let Some(len) = emoji_len(rest) {
+			flush(&mut nodes, &mut text);
+			nodes.push(Node::Emoji(rest[..len].to_string()));
+			i += len;
+			continue;
+		}
+
+		text.push(c);
+		i += c.len_utf8();
+	}
+
+	flush(&mut nodes, &mut text);
+	nodes
+}
+
+fn flush(
+	nodes: &mut Vec<Node>,
+	text: &mut String,
+) {
+	if !text.is_empty() {
+		nodes.push(Node::Text(std::mem::take(text)));
+	}
+}
+
+/// the current line and the offset just past its newline
+fn line(src: &str) -> (&str, usize) {
+	match src.find('\n') {
+		| Some(end) => (&src[..end], end + 1),
+		| None => (src, src.len()),
+	}
+}
+
+fn parse_block(
+	src: &str,
+	ctx: Context,
+) -> Option<(Node, usize)> {
+	if !ctx.in_quote {
+		if let Some(rest) = src.strip_prefix(">>> ") {
+			let children = parse_with(rest, Context {
+				in_quote: true,
+				..ctx
+			});
+			return Some((Node::BlockQuote(children), src.len()));
+		}
+
+		if src.starts_with("> ") {
+			let mut content = Vec::new();
+			let mut len = 0;
+
+			while let Some(quoted) = src[len..].strip_prefix("> ") {
+				let (text, line_len) = line(quoted);
+				content.push(text);
+				len += 2 + line_len;
+			}
+
+			let children = parse_with(&content.join("\n"), Context {
+				in_quote: true,
+				..ctx
+			});
+			return Some((Node::BlockQuote(children), len));
+		}
+	}
+
+	let (text, len) = line(src);
+
+	let hashes = text.bytes().take_while(|&b| b == b'#').count();
+	if (1..=3).contains(&hashes)
+		&& let Some(content) = text[hashes..].strip_prefix(' ')
+		&& !content.trim().is_empty()
+	{
+		let children = parse_with(content.trim(), ctx.inline());
+		return Some((
+			Node::Heading {
+				level: hashes as u8,
+				children,
+			},
+			len,
+		));
+	}
+
+	if let Some(content) = text.strip_prefix("-# ")
+		&& !content.trim().is_empty()
+	{
+		return Some((Node::Subtext(parse_with(content.trim(), ctx.inline())), len));
+	}
+
+	if list_marker(text).is_some() {
+		return Some(parse_list(src, ctx));
+	}
+
+	None
+}
+
+/// indentation, the number for ordered lists and where the item content starts
+fn list_marker(line: &str) -> Option<(usize, Option<u32>, usize)> {
+	let indent = line.len() - line.trim_start_matches(' ').len();
+	let rest = &line[indent..];
+
+	let (start, marker_len) = if rest.starts_with("- ") || rest.starts_with("* ") {
+		(None, 1)
+	} else {
+		let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+		if digits == 0 || digits > 9 || rest.as_bytes().get(digits) != Some(&b'.') {
+			return None;
+		}
+		(rest[..digits].parse().ok(), digits + 1)
+	};
+
+	let content = rest[marker_len..].strip_prefix(' ')?;
+	if content.trim().is_empty() {
+		return None;
+	}
+
+	Some((indent, start, indent + marker_len + 1))
+}
+
+fn parse_list(
+	src: &str,
+	ctx: Context,
+) -> (Node, usize) {
+	let (indent, start, _) = list_marker(line(src).0).unwrap();
+	let mut items: Vec<String> = Vec::new();
+	let mut len = 0;
+
+	while len < src.len() {
+		let (text, line_len) = line(&src[len..]);
+
+		match list_marker(text) {
+			| Some((item_indent, _, content_start)) if item_indent <= indent => {
+				items.push(text[content_start..].to_string());
+			},
+			// anything indented further belongs to the previous item, as a nested list
+			| Some((item_indent, ..)) if !items.is_empty() => {
+				let last = items.last_mut().unwrap();
+				last.push('\n');
+				last.push_str(&text[(indent + 2).min(item_indent)..]);
+			},
+			| _ => break,
+		}
+
+		len += line_len;
+	}
+
+	let items = items
+		.iter()
+		.map(|item| {
+			let (first, rest) = match item.split_once('\n') {
+				| Some((first, rest)) => (first, Some(rest)),
+				| None => (item.as_str(), None),
+			};
+
+			let mut children = parse_with(first, ctx.inline());
+			if let Some(rest) = rest {
+				children.push(parse_list(rest, ctx).0);
+			}
+			children
+		})
+		.collect();
+
+	(Node::List { start, items }, len)
+}
+
+fn parse_inline(
+	src: &str,
+	previous: Option<char>,
+	ctx: Context,
+) -> Option<(Node, usize)> {
+	let inline = ctx.inline();
+
+	match src.as_bytes()[0] {
+		| b'`' => parse_code(src),
+		| b'*' => {
+			// `***a***` matches both, the longer one wins and italics win ties like in simple-markdown
+			match longest(italic(src, '*', previous), delimited(src, "**", Some('*'))) {
+				| Some((false, inner, len)) => Some((Node::Italic(parse_with(inner, inline)), len)),
+				| Some((true, inner, len)) => Some((Node::Bold(parse_with(inner, inline)), len)),
+				| None => None,
+			}
+		},
+		| b'_' => match longest(italic(src, '_', previous), delimited(src, "__", Some('_'))) {
+			| Some((false, inner, len)) => Some((Node::Italic(parse_with(inner, inline)), len)),
+			| Some((true, inner, len)) => Some((Node::Underline(parse_with(inner, inline)), len)),
+			| None => None,
+		},
+		| b'~' => delimited(src, "~~", None).map(|(inner, len)| (Node::Strikethrough(parse_with(inner, inline)), len)),
+		| b'|' => delimited(src, "||", None).map(|(inner, len)| (Node::Spoiler(parse_with(inner, inline)), len)),
+		| b'[' if !ctx.in_link => parse_masked_link(src, ctx),
+		| b'<' => parse_angle(src, ctx),
+		| b'@' => {
+			if src.starts_with("@everyone") {
+				Some((Node::Everyone, 9))
+			} else if src.starts_with("@here") {
+				Some((Node::Here, 5))
+			} else {
+				None
+			}
+		},
+		| b'h' if !ctx.in_link && !previous.is_some_and(|c| c.is_alphanumeric()) => {
+			let len = url_len(src)?;
+			Some((Node::Url(src[..len].to_string()), len))
+		},
+		| _ => None,
+	}
+}
+
+/// which of the two matches wins, true for `other`. only the winner gets parsed, parsing both would double
+/// the work for every level of nesting
+fn longest<'a>(
+	preferred: Option<(&'a str, usize)>,
+	other: Option<(&'a str, usize)>,
+) -> Option<(bool, &'a str, usize)> {
+	match (preferred, other) {
+		| (Some(a), Some(b)) if b.1 > a.1 => Some((true, b.0, b.1)),
+		| (Some(a), _) => Some((false, a.0, a.1)),
+		| (None, b) => b.map(|b| (true, b.0, b.1)),
+	}
+}
+
+/// `delim` content `delim`, where the closing one can't be followed by `not_followed_by`.
+/// returns the content and the length of the whole match
+fn delimited<'a>(
+	src: &'a str,
+	delim: &str,
+	not_followed_by: Option<char>,
+) -> Option<(&'a str, usize)> {
+	let body = src.strip_prefix(delim)?;
+	let mut i = 0;
+
+	while i < body.len() {
+		if let Some(escaped) = body[i..].strip_prefix('\\') {
+			i += 1 + escaped.chars().next().map_or(0, char::len_utf8);
+			continue;
+		}
+
+		if i > 0 && body[i..].starts_with(delim) {
+			let after = body[i + delim.len()..].chars().next();
+			if not_followed_by.is_none() || after != not_followed_by {
+				return Some((&body[..i], delim.len() * 2 + i));
+			}
+		}
+
+		i += body[i..].chars().next().unwrap().len_utf8();
+	}
+
+	None
+}
+
+fn italic(
+	src: &str,
+	marker: char,
+	previous: Option<char>,
+) -> Option<(&str, usize)> {
+	let body = &src[1..];
+	let first = body.chars().next()?;
+
+	if first.is_whitespace() {
+		return None;
+	}
+	// snake_case_names shouldn't turn into italics
+	if marker == '_' && previous.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+		return None;
+	}
+
+	let double = [marker, marker].iter().collect::<String>();
+	let mut i = 0;
+
+	while i < body.len() {
+		let rest = &body[i..];
+
+		if let Some(escaped) = rest.strip_prefix('\\') {
+			i += 1 + escaped.chars().next().map_or(0, char::len_utf8);
+			continue;
+		}
+
+		if rest.starts_with(marker) {
+			let before = body[..i].chars().next_back();
+			let after = rest[1..].chars().next();
+
+			let closes = i > 0
+				&& match marker {
+					| '_' => !after.is_some_and(|c| c.is_alphanumeric() || c == '_'),
+					| _ => !before.is_some_and(char::is_whitespace) && after != Some(marker),
+				};
+
+			if closes {
+				return Some((&body[..i], i + 2));
+			}
+
+			// doubled markers are nested bold/underline, a lone one means this isn't italics
+			if !rest.starts_with(&double) {
+				return None;
+			}
+			i += 2;
+			continue;
+		}
+
+		i += rest.chars().next().unwrap().len_utf8();
+	}
+
+	None
+}
+
+fn parse_code(src: &str) -> Option<(Node, usize)> {
+	if let Some(body) = src.strip_prefix("```") {
+		let end = body.find("```")?;
+		let content = &body[..end];
+
+		// the language is only a language if there's more on the lines after it
+		let (language, code) = match content.split_once('\n') {
+			| Some((first, rest))
+				if !first.is_empty()
+					&& first
+						.chars()
+						.all(|c| c.is_ascii_alphanumeric() || "_+-.#".contains(c)) =>
+			{
+				(Some(first.to_string()), rest)
+			},
+			| _ => (None, content),
+		};
+
+		let code = code.trim_matches('\n');
+		if code.is_empty() && language.is_none() {
+			return None;
+		}
+
+		return Some((
+			Node::CodeBlock {
+				language,
+				code: code.to_string(),
+			},
+			end + 6,
+		));
+	}
+
+	let ticks = src.bytes().take_while(|&b| b == b'`').count();
+	let delim = &src[..ticks];
+	let body = &src[ticks..];
+	let mut search = 0;
+
+	while let Some(found) = body[search..].find(delim) {
+		let at = search + found;
+		let run = body[at..].bytes().take_while(|&b| b == b'`').count();
+
+		if run == ticks && at > 0 {
+			return Some((Node::InlineCode(body[..at].to_string()), ticks * 2 + at));
+		}
+		search = at + run;
+	}
+
+	None
+}
+
+fn parse_masked_link(
+	src: &str,
+	ctx: Context,
+) -> Option<(Node, usize)> {
+	let mut depth = 0;
+	let mut close = None;
+
+	for (i, c) in src.char_indices() {
+		match c {
+			| '[' => depth += 1,
+			| ']' => {
+				depth -= 1;
+				if depth == 0 {
+					close = Some(i);
+					break;
+				}
+			},
+			| '\n' => return None,
+			| _ => {},
+		}
+	}
+
+	let close = close?;
+	let after = src[close + 1..].strip_prefix('(')?;
+	let end = after.find(')')?;
+	let target = after[..end].trim();
+	let url = target
+		.strip_prefix('<')
+		.and_then(|t| t.strip_suffix('>'))
+		.unwrap_or(target);
+
+	if !(url.starts_with("https://") || url.starts_with("http://")) || url.contains(char::is_whitespace) {
+		return None;
+	}
+
+	let label = &src[1..close];
+	if label.trim().is_empty() {
+		return None;
+	}
+
+	let children = parse_with(label, Context {
+		in_link: true,
+		..ctx.inline()
+	});
+
+	Some((
+		Node::Link {
+			url: url.to_string(),
+			children,
+		},
+		close + 2 + end + 1,
+	))
+}
+
+fn parse_angle(
+	src: &str,
+	ctx: Context,
+) -> Option<(Node, usize)> {
+	let end = src.find('>')?;
+	let inner = &src[1..end];
+	let len = end + 1;
+
+	let id = |s: &str| -> Option<Snowflake> {
+		if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+			return None;
+		}
+		s.parse().ok()
+	};
+
+	if let Some(rest) = inner.strip_prefix("@&") {
+		return Some((Node::RoleMention(id(rest)?), len));
+	}
+	if let Some(rest) = inner.strip_prefix('@') {
+		return Some((Node::UserMention(id(rest.strip_prefix('!').unwrap_or(rest))?), len));
+	}
+	if let Some(rest) = inner.strip_prefix('#') {
+		return Some((Node::ChannelMention(id(rest)?), len));
+	}
+	if let Some(rest) = inner.strip_prefix("t:") {
+		let (unix, style) = match rest.split_once(':') {
+			| Some((unix, style)) if style.len() == 1 && "tTdDfFR".contains(style) => {
+				(unix, style.chars().next().unwrap())
+			},
+			| Some(_) => return None,
+			| None => (rest, 'f'),
+		};
+		return Some((
+			Node::Timestamp {
+				unix: unix.parse().ok()?,
+				style,
+			},
+			len,
+		));
+	}
+
+	let (animated, emoji) = match inner.strip_prefix("a:") {
+		| Some(rest) => (true, Some(rest)),
+		| None => (false, inner.strip_prefix(':')),
+	};
+	if let Some((name, emoji_id)) = emoji.and_then(|e| e.split_once(':'))
+		&& !name.is_empty()
+		&& name.chars().all(|c| c.is_alphanumeric() || c == '_')
+	{
+		return Some((
+			Node::CustomEmoji {
+				name: name.to_string(),
+				id: id(emoji_id)?,
+				animated,
+			},
+			len,
+		));
+	}
+
+	// <https://...> is a link that doesn't embed
+	if !ctx.in_link && url_len(inner) == Some(inner.len()) {
+		return Some((Node::Url(inner.to_string()), len));
+	}
+
+	None
+}
+
+fn url_len(src: &str) -> Option<usize> {
+	let scheme = if src.starts_with("https://") {
+		8
+	} else if src.starts_with("http://") {
+		7
+	} else {
+		return None;
+	};
+
+	let mut len = src
+		.find(|c: char| c.is_whitespace() || c == '<')
+		.unwrap_or(src.len());
+
+	// trailing punctuation is almost always part of the sentence, and so are unbalanced parentheses
+	loop {
+		let url = &src[..len];
+		let Some(last) = url.chars().next_back() else {
+			break;
+		};
+
+		let unbalanced = last == ')' && url.matches('(').count() < url.matches(')').count();
+		if ".,:;!?\"'".contains(last) || unbalanced {
+			len -= last.len_utf8();
+		} else {
+			break;
+		}
+	}
+
+	(len > scheme).then_some(len)
+}
+
+fn is_emoji(c: char) -> bool {
+	matches!(c as u32,
+		0x1f000..=0x1faff // most emojis, including regional indicators and skin tones
+		| 0x2600..=0x27bf // misc symbols and dingbats
+		| 0x2300..=0x23ff // ⌚ ⏰ etc
+		| 0x2b00..=0x2bff // ⭐ ⬆ etc
+	)
+}
+
+/// length of the emoji sequence at the start of `src`, including zero width joiners, variation selectors and keycaps
+fn emoji_len(src: &str) -> Option<usize> {
+	let mut chars = src.char_indices().peekable();
+	let (_, first) = chars.next()?;
+
+	// keycaps like 1️⃣ start with a normal character
+	if first.is_ascii_digit() || first == '#' || first == '*' {
+		let rest = &src[1..];
+		let rest = rest.strip_prefix('\u{fe0f}').unwrap_or(rest);
+		return rest
+			.starts_with('\u{20e3}')
+			.then(|| src.len() - rest.len() + '\u{20e3}'.len_utf8());
+	}
+
+	if !is_emoji(first) {
+		return None;
+	}
+
+	let mut len = first.len_utf8();
+	let mut joined = false;
+
+	while let Some(&(i, c)) = chars.peek() {
+		let continues = match c as u32 {
+			| 0xfe0f | 0x20e3 | 0x1f3fb..=0x1f3ff | 0xe0020..=0xe007f => true,
+			| 0x200d => {
+				joined = true;
+				true
+			},
+			| _ if joined => {
+				joined = false;
+				is_emoji(c)
+			},
+			// flags are pairs of regional indicators
+			| 0x1f1e6..=0x1f1ff if (first as u32 & 0x1f1e0) == 0x1f1e0 && len == first.len_utf8() => true,
+			| _ => false,
+		};
+
+		if !continues {
+			break;
+		}
+		len = i + c.len_utf8();
+		chars.next();
+	}
+
+	Some(len)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::Node::*;
+	use super::*;
+
+	fn text(s: &str) -> Node { Text(s.to_string()) }
+
+	#[test]
+	fn deep_nesting_stays_fast() {
+		for marker in ["*", "_"] {
+			let src = format!("{}a{}", marker.repeat(200), marker.repeat(200));
+			let started = std::time::Instant::now();
+			parse(&src);
+			assert!(started.elapsed().as_millis() < 200, "{} took {:?}", marker, started.elapsed());
+		}
+	}
+
+	#[test]
+	fn plain_text() {
+		assert_eq!(parse("hello world"), vec![text("hello world")]);
+		assert_eq!(parse(""), vec![]);
+	}
+
+	#[test]
+	fn basic_formatting() {
+		assert_eq!(parse("**a**"), vec![Bold(vec![text("a")])]);
+		assert_eq!(parse("*a*"), vec![Italic(vec![text("a")])]);
+		assert_eq!(parse("_a_"), vec![Italic(vec![text("a")])]);
+		assert_eq!(parse("__a__"), vec![Underline(vec![text("a")])]);
+		assert_eq!(parse("~~a~~"), vec![Strikethrough(vec![text("a")])]);
+		assert_eq!(parse("||a||"), vec![Spoiler(vec![text("a")])]);
+	}
+
+	#[test]
+	fn triple_markers_nest() {
+		assert_eq!(parse("***a***"), vec![Italic(vec![Bold(vec![text("a")])])]);
+		assert_eq!(parse("___a___"), vec![Italic(vec![Underline(vec![text("a")])])]);
+		assert_eq!(parse("__*a*__"), vec![Underline(vec![Italic(vec![text("a")])])]);
+	}
+
+	#[test]
+	fn nested_formatting() {
+		assert_eq!(parse("*a **b** c*"), vec![Italic(vec![
+			text("a "),
+			Bold(vec![text("b")]),
+			text(" c")
+		])]);
+		assert_eq!(parse("||**a** ~~b~~||"), vec![Spoiler(vec![
+			Bold(vec![text("a")]),
+			text(" "),
+			Strikethrough(vec![text("b")]),
+		])]);
+	}
+
+	#[test]
+	fn unclosed_markers_are_text() {
+		assert_eq!(parse("**a"), vec![text("**a")]);
+		assert_eq!(parse("a * b * c"), vec![text("a * b * c")]);
+		assert_eq!(parse("||"), vec![text("||")]);
+		assert_eq!(parse("a *b *c"), vec![text("a *b *c")]);
+	}
+
+	#[test]
+	fn underscores_inside_words() {
+		assert_eq!(parse("snake_case_name"), vec![text("snake_case_name")]);
+		assert_eq!(parse("_a_b"), vec![text("_a_b")]);
+	}
+
+	#[test]
+	fn escapes() {
+		assert_eq!(parse(r"\*\*a\*\*"), vec![text("**a**")]);
+		assert_eq!(parse(r"**a\*\*b**"), vec![Bold(vec![text("a**b")])]);
+		assert_eq!(parse(r"\a"), vec![text(r"\a")]);
+	}
+
+	#[test]
+	fn inline_code_is_literal() {
+		assert_eq!(parse("`**a**`"), vec![InlineCode("**a**".to_string())]);
+		assert_eq!(parse("``a ` b``"), vec![InlineCode("a ` b".to_string())]);
+		assert_eq!(parse("**`a`**"), vec![Bold(vec![InlineCode("a".to_string())])]);
+	}
+
+	#[test]
+	fn code_blocks() {
+		assert_eq!(parse("```rust\nfn main() {}\n```"), vec![CodeBlock {
+			language: Some("rust".to_string()),
+			code:     "fn main() {}".to_string(),
+		}]);
+		assert_eq!(parse("```no language```"), vec![CodeBlock {
+			language: None,
+			code:     "no language".to_string(),
+		}]);
+		assert_eq!(parse("a ```\n> not a quote\n``` b"), vec![
+			text("a "),
+			CodeBlock {
+				language: None,
+				code:     "> not a quote".to_string(),
+			},
+			text(" b"),
+		]);
+	}
+
+	#[test]
+	fn block_quotes() {
+		assert_eq!(parse("> a\n> **b**\nc"), vec![
+			BlockQuote(vec![text("a\n"), Bold(vec![text("b")])]),
+			text("c"),
+		]);
+		assert_eq!(parse(">>> a\nb"), vec![BlockQuote(vec![text("a\nb")])]);
+		assert_eq!(parse("> > a"), vec![BlockQuote(vec![text("> a")])]);
+		assert_eq!(parse("a > b"), vec![text("a > b")]);
+	}
+
+	#[test]
+	fn headings_and_subtext() {
+		assert_eq!(parse("# a\nb"), vec![
+			Heading {
+				level:    1,
+				children: vec![text("a")],
+			},
+			text("b"),
+		]);
+		assert_eq!(parse("### *a*"), vec![Heading {
+			level:    3,
+			children: vec![Italic(vec![text("a")])],
+		}]);
+		assert_eq!(parse("#### a"), vec![text("#### a")]);
+		assert_eq!(parse("#a"), vec![text("#a")]);
+		assert_eq!(parse("-# small"), vec![Subtext(vec![text("small")])]);
+		assert_eq!(parse("> # a"), vec![BlockQuote(vec![Heading {
+			level:    1,
+			children: vec![text("a")],
+		}])]);
+	}
+
+	#[test]
+	fn lists() {
+		assert_eq!(parse("- a\n- b\nc"), vec![
+			List {
+				start: None,
+				items: vec![vec![text("a")], vec![text("b")]],
+			},
+			text("c"),
+		]);
+		assert_eq!(parse("3. a\n4. b"), vec![List {
+			start: Some(3),
+			items: vec![vec![text("a")], vec![text("b")]],
+		}]);
+		assert_eq!(parse("- a\n  - b"), vec![List {
+			start: None,
+			items: vec![vec![text("a"), List {
+				start: None,
+				items: vec![vec![text("b")]],
+			}]],
+		}]);
+		assert_eq!(parse("* a"), vec![List {
+			start: None,
+			items: vec![vec![text("a")]],
+		}]);
+		assert_eq!(parse("-a"), vec![text("-a")]);
+	}
+
+	#[test]
+	fn links() {
+		assert_eq!(parse("[**a**](https://a.com)"), vec![Link {
+			url:      "https://a.com".to_string(),
+			children: vec![Bold(vec![text("a")])],
+		}]);
+		assert_eq!(parse("[a](<https://a.com>)"), vec![Link {
+			url:      "https://a.com".to_string(),
+			children: vec![text("a")],
+		}]);
+		assert_eq!(parse("[a](javascript:alert(1))"), vec![text("[a](javascript:alert(1))")]);
+		assert_eq!(parse("[https://a.com](https://b.com)"), vec![Link {
+			url:      "https://b.com".to_string(),
+			children: vec![text("https://a.com")],
+		}]);
+	}
+
+	#[test]
+	fn urls() {
+		assert_eq!(parse("see https://a.com/b."), vec![
+			text("see "),
+			Url("https://a.com/b".to_string()),
+			text("."),
+		]);
+		assert_eq!(parse("(https://a.com/(b))"), vec![
+			text("("),
+			Url("https://a.com/(b)".to_string()),
+			text(")"),
+		]);
+		assert_eq!(parse("<https://a.com>"), vec![Url("https://a.com".to_string())]);
+		assert_eq!(parse("https://"), vec![text("https://")]);
+	}
+
+	#[test]
+	fn mentions() {
+		assert_eq!(parse("<@1> <@!2> <@&3> <#4>"), vec![
+			UserMention(Snowflake::new(1)),
+			text(" "),
+			UserMention(Snowflake::new(2)),
+			text(" "),
+			RoleMention(Snowflake::new(3)),
+			text(" "),
+			ChannelMention(Snowflake::new(4)),
+		]);
+		assert_eq!(parse("@everyone @here"), vec![Everyone, text(" "), Here]);
+		assert_eq!(parse("**<@1>**"), vec![Bold(vec![UserMention(Snowflake::new(1))])]);
+		assert_eq!(parse("<@abc>"), vec![text("<@abc>")]);
+	}
+
+	#[test]
+	fn emojis() {
+		assert_eq!(parse("<:cow:1> <a:moo:2>"), vec![
+			CustomEmoji {
+				name:     "cow".to_string(),
+				id:       Snowflake::new(1),
+				animated: false,
+			},
+			text(" "),
+			CustomEmoji {
+				name:     "moo".to_string(),
+				id:       Snowflake::new(2),
+				animated: true,
+			},
+		]);
+		assert_eq!(parse("hi 🐄"), vec![text("hi "), Emoji("🐄".to_string())]);
+		assert_eq!(parse("👩‍👩‍👧🇳🇱"), vec![Emoji("👩‍👩‍👧".to_string()), Emoji("🇳🇱".to_string())]);
+		assert_eq!(parse("👍🏽1️⃣"), vec![Emoji("👍🏽".to_string()), Emoji("1️⃣".to_string())]);
+		assert!(is_emoji_only(&parse("🐄 <:cow:1>")));
+		assert!(!is_emoji_only(&parse("🐄 moo")));
+	}
+
+	#[test]
+	fn timestamps() {
+		assert_eq!(parse("<t:1700000000:R>"), vec![Timestamp {
+			unix:  1700000000,
+			style: 'R',
+		}]);
+		assert_eq!(parse("<t:1700000000>"), vec![Timestamp {
+			unix:  1700000000,
+			style: 'f',
+		}]);
+		assert_eq!(parse("<t:1700000000:x>"), vec![text("<t:1700000000:x>")]);
+	}
+}
@@ -1,4 +1,15 @@
 // TODO: jsut make some build script that autogenerates this file
 
-pub mod local_storage;
+pub mod cdn;
+pub mod config;
+pub mod gateway;
+pub mod markdown;
+pub mod permissions;
+pub mod platform;
+pub mod recorder;
+pub mod remote_auth;
 pub mod request;
+pub mod storage;
+pub mod sudo;
+pub mod time;
+pub mod token;
@@ -0,0 +1,145 @@
+use crate::models::chat::Channel;
+use crate::models::guild::Guild;
+use crate::models::types::Snowflake;
+
+// https://docs.discord.sex/topics/permissions#permissions-bitwise-values
+pub const ADMINISTRATOR: u64 = 1 << 3;
+pub const VIEW_CHANNEL: u64 = 1 << 10;
+
+// https://docs.discord.sex/resources/channel#channel-type
+const THREADS: [u8; 3] = [10, 11, 12];
+
+// permissions are sent as stringified integers
+fn bits(value: &str) -> u64 { value.parse().unwrap_or(0) }
+
+/// guild wide permissions from the member's roles, the @everyone role shares its id with the guild
+pub fn guild_permissions(
+	guild: &Guild,
+	user_id: Snowflake,
+	roles: &[Snowflake],
+) -> u64 {
+	if guild.owner() == Some(user_id) {
+		return u64::MAX;
+	}
+
+	let permissions = guild
+		.roles
+		.iter()
+		.filter(|r| r.id == guild.id || roles.contains(&r.id))
+		.fold(0, |permissions, role| permissions | bits(&role.permissions));
+
+	if permissions & ADMINISTRATOR != 0 {
+		return u64::MAX;
+	}
+
+	permissions
+}
+
+/// https://docs.discord.sex/topics/permissions#permission-overwrites, threads use their parent's overwrites
+pub fn channel_permissions(
+	guild: &Guild,
+	channel: &Channel,
+	user_id: Snowflake,
+	roles: &[Snowflake],
+) -> u64 {
+	let mut permissions = guild_permissions(guild, user_id, roles);
+	if permissions == u64::MAX {
+		return permissions;
+	}
+
+	let channel = match channel.parent_id {
+		| Some(parent_id) if THREADS.contains(&channel.r#type) => {
+			match guild.channels.iter().find(|c| c.id == parent_id) {
+				| Some(parent) => parent,
+				| None => return 0,
+			}
+		},
+		| _ => channel,
+	};
+	let overwrites = &channel.permission_overwrites;
+
+	if let Some(everyone) = overwrites.iter().find(|o| o.id == guild.id) {
+		permissions &= !bits(&everyone.deny);
+		permissions |= bits(&everyone.allow);
+	}
+
+	// role overwrites are applied together so their order doesn't matter
+	let (allow, deny) = overwrites
+		.iter()
+		.filter(|o| o.r#type == 0 && roles.contains(&o.id))
+		.fold((0, 0), |(allow, deny), o| (allow | bits(&o.allow), deny | bits(&o.deny)));
+	permissions &= !deny;
+	permissions |= allow;
+
+	if let Some(member) = overwrites.iter().find(|o| o.r#type == 1 && o.id == user_id) {
+		permissions &= !bits(&member.deny);
+		permissions |= bits(&member.allow);
+	}
+
+	permissions
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	const GUILD: u64 = 1;
+	const ME: u64 = 2;
+	const ROLE: u64 = 3;
+
+	fn guild(role_permissions: u64) -> Guild {
+		serde_json::from_value(json!({
+			"id": GUILD.to_string(),
+			"owner_id": "99",
+			"roles": [
+				{ "id": GUILD.to_string(), "name": "@everyone", "permissions": VIEW_CHANNEL.to_string() },
+				{ "id": ROLE.to_string(), "name": "moo", "permissions": role_permissions.to_string() },
+			],
+			"channels": [{
+				"id": "10",
+				"type": 0,
+				"permission_overwrites": [
+					{ "id": GUILD.to_string(), "type": 0, "allow": "0", "deny": VIEW_CHANNEL.to_string() },
+					{ "id": ROLE.to_string(), "type": 0, "allow": VIEW_CHANNEL.to_string(), "deny": "0" },
+				],
+			}],
+		}))
+		.unwrap()
+	}
+
+	fn can_view(
+		guild: &Guild,
+		roles: &[Snowflake],
+	) -> bool {
+		channel_permissions(guild, &guild.channels[0], Snowflake::new(ME), roles) & VIEW_CHANNEL != 0
+	}
+
+	#[test]
+	fn overwrites_apply_in_order() {
+		let guild = guild(0);
+
+		assert!(!can_view(&guild, &[]));
+		assert!(can_view(&guild, &[Snowflake::new(ROLE)]));
+	}
+
+	#[test]
+	fn administrator_skips_overwrites() {
+		let mut guild = guild(ADMINISTRATOR);
+		guild.channels[0].permission_overwrites.pop();
+
+		assert!(can_view(&guild, &[Snowflake::new(ROLE)]));
+	}
+
+	#[test]
+	fn member_overwrites_win() {
+		let mut guild = guild(0);
+		guild.channels[0].permission_overwrites.push(
+			serde_json::from_value(json!({ "id": ME.to_string(), "type": 1, "allow": "0", "deny": VIEW_CHANNEL.to_string() }))
+				.unwrap(),
+		);
+
+		assert!(!can_view(&guild, &[Snowflake::new(ROLE)]));
+	}
+}
@@ -0,0 +1,24 @@
+// the parts of the browser the client leans on, with native stand-ins so the same code runs on desktop and
+// in tests. storage lives in its own module
+
+use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender};
+use futures_channel::oneshot;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+#[cfg(target_arch = "wasm32")]
+mod web;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::{connect, error, log, sleep, spawn};
+#[cfg(target_arch = "wasm32")]
+pub use web::{connect, error, log, sleep, spawn};
+
+/// a text websocket as a pair of channels. `incoming` ends once the socket closes, and closing
+/// `outgoing` closes the socket
+pub struct Connection {
+	pub incoming:   UnboundedReceiver<String>,
+	pub outgoing:   UnboundedSender<String>,
+	/// why the other end closed it, there by the time `incoming` ends if it said
+	pub close_code: oneshot::Receiver<u16>,
+}
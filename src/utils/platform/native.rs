@@ -0,0 +1,71 @@
+use std::error::Error;
+use std::time::Duration;
+
+use futures_channel::mpsc::unbounded;
+use futures_channel::oneshot;
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+
+use super::Connection;
+
+/// resolves once the handshake is done, the socket is pumped by two tasks on the tokio runtime. `origin` is
+/// sent as the Origin header, for servers that only take connections from their own site
+pub async fn connect(
+	url: &str,
+	origin: Option<&str>,
+) -> Result<Connection, Box<dyn Error>> {
+	let mut request = url.into_client_request()?;
+	if let Some(origin) = origin {
+		request.headers_mut().insert("Origin", HeaderValue::from_str(origin)?);
+	}
+	let (socket, _) = tokio_tungstenite::connect_async(request).await?;
+	let (mut sink, mut stream) = socket.split();
+	let (incoming_tx, incoming) = unbounded();
+	let (outgoing, mut outgoing_rx) = unbounded::<String>();
+	let (close_code_tx, close_code) = oneshot::channel();
+
+	spawn(async move {
+		while let Some(Ok(message)) = stream.next().await {
+			match message {
+				| Message::Text(text) => {
+					if incoming_tx.unbounded_send(text).is_err() {
+						break;
+					}
+				},
+				| Message::Close(frame) => {
+					log(&format!("Websocket closed: {:?}", frame));
+					if let Some(frame) = frame {
+						let _ = close_code_tx.send(frame.code.into());
+					}
+					break;
+				},
+				| _ => {},
+			}
+		}
+	});
+
+	spawn(async move {
+		while let Some(text) = outgoing_rx.next().await {
+			if sink.send(Message::Text(text)).await.is_err() {
+				return;
+			}
+		}
+		let _ = sink.close().await;
+	});
+
+	Ok(Connection {
+		incoming,
+		outgoing,
+		close_code,
+	})
+}
+
+pub fn sleep(ms: u32) -> impl Future<Output = ()> { tokio::time::sleep(Duration::from_millis(ms.into())) }
+
+pub fn spawn(future: impl Future<Output = ()> + Send + 'static) { tokio::spawn(future); }
+
+pub fn log(message: &str) { println!("{}", message); }
+
+pub fn error(message: &str) { eprintln!("{}", message); }
@@ -0,0 +1,90 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::rc::Rc;
+
+use futures_channel::mpsc::unbounded;
+use futures_channel::oneshot;
+use futures_util::StreamExt;
+use gloo_timers::future::TimeoutFuture;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::prelude::*;
+use web_sys::{MessageEvent, WebSocket, console};
+
+use super::Connection;
+
+/// resolves once the socket is open, the browser throws on anything sent before that. the browser always
+/// sends its own origin, so `origin` is ignored
+pub async fn connect(
+	url: &str,
+	_origin: Option<&str>,
+) -> Result<Connection, Box<dyn Error>> {
+	let ws = WebSocket::new(url).map_err(|e| format!("{:?}", e))?;
+	let (incoming_tx, incoming) = unbounded();
+	let (outgoing, mut outgoing_rx) = unbounded::<String>();
+	let (opened_tx, opened) = oneshot::channel::<()>();
+	let (close_code_tx, close_code) = oneshot::channel();
+	// both get dropped on close, which is what ends `incoming` and fails the open
+	let incoming_tx = Rc::new(RefCell::new(Some(incoming_tx)));
+	let opened_tx = Rc::new(RefCell::new(Some(opened_tx)));
+	let close_code_tx = RefCell::new(Some(close_code_tx));
+
+	let message_tx = incoming_tx.clone();
+	let onmessage_callback = Closure::wrap(Box::new(move |e: MessageEvent| {
+		if let (Some(tx), Some(text)) = (&*message_tx.borrow(), e.data().as_string()) {
+			let _ = tx.unbounded_send(text);
+		}
+	}) as Box<dyn FnMut(MessageEvent)>);
+
+	let open_tx = opened_tx.clone();
+	let onopen_callback = Closure::wrap(Box::new(move |_| {
+		if let Some(tx) = open_tx.borrow_mut().take() {
+			let _ = tx.send(());
+		}
+	}) as Box<dyn FnMut(JsValue)>);
+
+	let onerror_callback = Closure::wrap(Box::new(move |_| {
+		error("Websocket error");
+	}) as Box<dyn FnMut(JsValue)>);
+
+	let onclose_callback = Closure::wrap(Box::new(move |e: web_sys::CloseEvent| {
+		log(&format!("Websocket closed: {} ({})", e.reason(), e.code()));
+		if let Some(tx) = close_code_tx.borrow_mut().take() {
+			let _ = tx.send(e.code());
+		}
+		opened_tx.borrow_mut().take();
+		incoming_tx.borrow_mut().take();
+	}) as Box<dyn FnMut(web_sys::CloseEvent)>);
+
+	ws.set_onmessage(Some(onmessage_callback.as_ref().unchecked_ref()));
+	ws.set_onopen(Some(onopen_callback.as_ref().unchecked_ref()));
+	ws.set_onerror(Some(onerror_callback.as_ref().unchecked_ref()));
+	ws.set_onclose(Some(onclose_callback.as_ref().unchecked_ref()));
+	onmessage_callback.forget();
+	onopen_callback.forget();
+	onerror_callback.forget();
+	onclose_callback.forget();
+
+	opened.await.map_err(|_| "Websocket closed before opening")?;
+
+	let ws_send = ws.clone();
+	spawn(async move {
+		while let Some(text) = outgoing_rx.next().await {
+			let _ = ws_send.send_with_str(&text);
+		}
+		let _ = ws_send.close();
+	});
+
+	Ok(Connection {
+		incoming,
+		outgoing,
+		close_code,
+	})
+}
+
+pub fn sleep(ms: u32) -> impl Future<Output = ()> { TimeoutFuture::new(ms) }
+
+pub fn spawn(future: impl Future<Output = ()> + 'static) { wasm_bindgen_futures::spawn_local(future); }
+
+pub fn log(message: &str) { console::log_1(&message.into()); }
+
+pub fn error(message: &str) { console::error_1(&message.into()); }
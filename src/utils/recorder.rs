@@ -0,0 +1,457 @@
+// opt-in recording of everything discord sends, raw gateway frames and rest responses, so rendering bugs can
+// be reproduced offline. tokens and contact details are blanked before anything's kept. a replay stands in
+// for both the gateway and the api. recordings can be exported to attach to bug reports and imported to
+// replay someone else's, and `write_fixtures` in the tests turns an exported one into models/fixtures files
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+
+#[cfg(not(feature = "desktop"))]
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::ser::PrettyFormatter;
+use serde_json::{Value, json};
+
+use crate::models::types::now_ms;
+use crate::models::websocket::GatewayRecieveEvent;
+use crate::utils::platform;
+use crate::utils::storage::{self, Storage};
+use crate::utils::token;
+
+// there's only ever the one, the next recording replaces it
+const RECORDING_KEY: &str = "recording";
+// the longest a replay waits between frames, nobody wants to sit through the quiet bits again
+const MAX_REPLAY_GAP_MS: u64 = 1000;
+const REDACTED: &str = "[redacted]";
+// blanked along with anything called a token, the remote auth ticket logs in as much as a token does
+const REDACTED_KEYS: [&str; 4] = ["token", "email", "phone", "ticket"];
+
+// saves a file the way a download link would
+#[cfg(not(feature = "desktop"))]
+const DOWNLOAD: &str = r#"
+	const [name, contents] = await dioxus.recv();
+	const url = URL.createObjectURL(new Blob([contents], { type: "application/json" }));
+	const link = document.createElement("a");
+	link.href = url;
+	link.download = name;
+	link.click();
+	URL.revokeObjectURL(url);
+"#;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Entry {
+	Gateway {
+		at:    u64, // ms since the recording started
+		frame: String,
+	},
+	Rest {
+		at:     u64,
+		method: String,
+		path:   String, // from /api on, with the query
+		status: u16,
+		body:   String,
+	},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Recording {
+	pub started: u64,
+	pub entries: Vec<Entry>,
+}
+
+impl Recording {
+	/// the gateway frames, in order
+	pub fn frames(&self) -> impl Iterator<Item = &str> {
+		self.entries.iter().filter_map(|entry| match entry {
+			| Entry::Gateway { frame, .. } => Some(frame.as_str()),
+			| Entry::Rest { .. } => None,
+		})
+	}
+
+	/// every frame and response as a tab indented file name and contents, named after the event or endpoint.
+	/// anything that comes up more than once gets numbered
+	pub fn fixtures(&self) -> Vec<(String, String)> {
+		let mut seen = HashMap::<String, usize>::new();
+		let mut fixtures = Vec::new();
+
+		for entry in &self.entries {
+			let (name, json) = match entry {
+				| Entry::Gateway { frame, .. } => {
+					let Ok(value) = serde_json::from_str::<Value>(frame) else {
+						continue;
+					};
+					let name = value["t"]
+						.as_str()
+						.map_or_else(|| format!("op_{}", value["op"]), str::to_lowercase);
+					(name, value)
+				},
+				| Entry::Rest { method, path, body, .. } => {
+					let Ok(value) = serde_json::from_str::<Value>(body) else {
+						continue;
+					};
+					(endpoint_name(method, path), value)
+				},
+			};
+
+			let count = seen.entry(name.clone()).or_default();
+			*count += 1;
+			let name = if *count == 1 { name } else { format!("{}_{}", name, count) };
+			fixtures.push((format!("{}.json", name), pretty(&json)));
+		}
+		fixtures
+	}
+}
+
+/// `GET /api/v9/channels/1/messages?limit=50` is `get_channels_messages`, ids and the version don't say much
+fn endpoint_name(
+	method: &str,
+	path: &str,
+) -> String {
+	let path = path.split('?').next().unwrap_or_default();
+	let segments = path
+		.split('/')
+		.filter(|s| !s.is_empty() && *s != "api" && !s.chars().all(|c| c.is_ascii_digit()))
+		.filter(|s| !(s.starts_with('v') && s[1..].chars().all(|c| c.is_ascii_digit())))
+		.map(|s| s.trim_start_matches('@').replace('-', "_"));
+
+	std::iter::once(method.to_lowercase()).chain(segments).collect::<Vec<_>>().join("_")
+}
+
+// the same as the files already in models/fixtures
+fn pretty(value: &Value) -> String {
+	let mut json = Vec::new();
+	let mut serializer = serde_json::Serializer::with_formatter(&mut json, PrettyFormatter::with_indent(b"\t"));
+	match value.serialize(&mut serializer) {
+		| Ok(()) => String::from_utf8(json).unwrap_or_default(),
+		| Err(_) => value.to_string(),
+	}
+}
+
+struct Replay {
+	recording: Recording,
+	served:    Vec<bool>, // which rest entries have been answered with
+}
+
+thread_local! {
+	static RECORDING: RefCell<Option<Recording>> = const { RefCell::new(None) };
+	static REPLAY: RefCell<Option<Replay>> = const { RefCell::new(None) };
+}
+
+pub fn recording() -> bool { RECORDING.with_borrow(Option::is_some) }
+
+pub fn replaying() -> bool { REPLAY.with_borrow(Option::is_some) }
+
+pub fn start() {
+	RECORDING.set(Some(Recording {
+		started: now_ms(),
+		entries: Vec::new(),
+	}));
+}
+
+/// saves what's been recorded, to a file on desktop and indexeddb on web
+pub async fn stop() {
+	let Some(recording) = RECORDING.take() else {
+		return;
+	};
+	if let Err(e) = storage::local().store(RECORDING_KEY, &recording).await {
+		platform::error(&format!("Failed to save recording: {}", e));
+	}
+}
+
+pub async fn saved() -> Option<Recording> { storage::local().load(RECORDING_KEY).await }
+
+/// saves the last recording somewhere it can be attached to a bug report, a download on web and the data dir on
+/// desktop. false if nothing's been recorded yet
+pub async fn export() -> Result<bool, Box<dyn Error>> {
+	let Some(recording) = saved().await else {
+		return Ok(false);
+	};
+	let name = format!("cowcord-recording-{}.json", recording.started);
+	let json = serde_json::to_string(&recording)?;
+
+	#[cfg(feature = "desktop")]
+	{
+		let dir = storage::data_dir().ok_or("No data directory")?;
+		std::fs::create_dir_all(&dir)?;
+		std::fs::write(dir.join(&name), json)?;
+		platform::log(&format!("Exported the recording to {}", dir.join(&name).display()));
+	}
+	#[cfg(not(feature = "desktop"))]
+	document::eval(DOWNLOAD)
+		.send((name, json))
+		.map_err(|e| format!("{:?}", e))?;
+
+	Ok(true)
+}
+
+/// replaces the last recording with an exported one, so it's what gets replayed
+pub async fn import(json: &str) -> Result<(), Box<dyn Error>> {
+	let recording: Recording = serde_json::from_str(json)?;
+	storage::local().store(RECORDING_KEY, &recording).await
+}
+
+fn record(entry: impl FnOnce(u64) -> Entry) {
+	RECORDING.with_borrow_mut(|recording| {
+		if let Some(recording) = recording {
+			recording.entries.push(entry(now_ms().saturating_sub(recording.started)));
+		}
+	});
+}
+
+/// keeps a frame as it came in, if the recorder's on
+pub fn gateway(frame: &str) {
+	if recording() {
+		let frame = redact(frame);
+		record(|at| Entry::Gateway { at, frame });
+	}
+}
+
+/// keeps a response, if the recorder's on
+pub fn rest(
+	method: &str,
+	path: &str,
+	status: u16,
+	body: &str,
+) {
+	if recording() {
+		let body = redact(body);
+		record(|at| Entry::Rest {
+			at,
+			method: method.to_string(),
+			path: path.to_string(),
+			status,
+			body,
+		});
+	}
+}
+
+/// swaps discord for the saved recording until `stop_replay`, false if nothing's been recorded yet
+pub async fn start_replay() -> bool {
+	let Some(recording) = saved().await else {
+		return false;
+	};
+	REPLAY.set(Some(Replay {
+		served: vec![false; recording.entries.len()],
+		recording,
+	}));
+	true
+}
+
+pub fn stop_replay() { REPLAY.set(None); }
+
+/// the recording being replayed, if there is one
+pub fn replay() -> Option<Recording> { REPLAY.with_borrow(|replay| replay.as_ref().map(|r| r.recording.clone())) }
+
+/// what the api answered the first time this was asked, and the last answer once they run out
+pub fn response(
+	method: &str,
+	path: &str,
+) -> Option<(u16, String)> {
+	REPLAY.with_borrow_mut(|replay| {
+		let replay = replay.as_mut()?;
+		let matching: Vec<usize> = replay
+			.recording
+			.entries
+			.iter()
+			.enumerate()
+			.filter(|(_, entry)| matches!(entry, Entry::Rest { method: m, path: p, .. } if m == method && p == path))
+			.map(|(i, _)| i)
+			.collect();
+
+		let index = matching
+			.iter()
+			.copied()
+			.find(|&i| !replay.served[i])
+			.or(matching.last().copied())?;
+		replay.served[index] = true;
+
+		match &replay.recording.entries[index] {
+			| Entry::Rest { status, body, .. } => Some((*status, body.clone())),
+			| Entry::Gateway { .. } => None,
+		}
+	})
+}
+
+/// dispatches the recorded events like the gateway would've, keeping the gaps between them
+pub async fn play(
+	recording: &Recording,
+	mut on_dispatch: impl FnMut(GatewayRecieveEvent),
+) {
+	let mut last = 0;
+	for entry in &recording.entries {
+		let Entry::Gateway { at, frame } = entry else {
+			continue;
+		};
+		let gap = at.saturating_sub(last).min(MAX_REPLAY_GAP_MS);
+		if gap > 0 {
+			platform::sleep(gap as u32).await;
+		}
+		last = *at;
+
+		match serde_json::from_str::<GatewayRecieveEvent>(frame) {
+			| Ok(event) if event.op == 0 => on_dispatch(event),
+			| Ok(_) => {},
+			| Err(e) => platform::error(&format!("Unparseable recorded frame {}: {}", frame, e)),
+		}
+	}
+}
+
+/// blanks anything that's called a token or is personal, and the token in use wherever else it turns up
+fn redact(text: &str) -> String {
+	let text = match token::get() {
+		| Some(token) if !token.is_empty() => text.replace(&token, REDACTED),
+		| _ => text.to_string(),
+	};
+
+	match serde_json::from_str::<Value>(&text) {
+		| Ok(mut value) => {
+			redact_value(&mut value);
+			value.to_string()
+		},
+		| Err(_) => text,
+	}
+}
+
+fn redact_value(value: &mut Value) {
+	match value {
+		| Value::Object(map) => {
+			for (key, value) in map {
+				if value.is_string() && (REDACTED_KEYS.contains(&key.as_str()) || key.ends_with("_token")) {
+					*value = json!(REDACTED);
+				} else {
+					redact_value(value);
+				}
+			}
+		},
+		| Value::Array(values) => values.iter_mut().for_each(redact_value),
+		| _ => {},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn tokens_get_redacted() {
+		let redacted: Value = serde_json::from_str(&redact(include_str!("../models/fixtures/login_success.json"))).unwrap();
+		assert_eq!(redacted["token"], REDACTED);
+		assert_eq!(redacted["user_id"], "696053426373869649");
+
+		let redacted: Value = serde_json::from_str(&redact(
+			r#"{"op": 0, "t": "READY", "d": {"analytics_token": "abc", "user": {"id": "1", "email": "cow@example.com", "phone": "+15555550100"}, "users": [{"id": "1", "token": "def"}]}}"#,
+		))
+		.unwrap();
+		assert_eq!(redacted["d"]["analytics_token"], REDACTED);
+		assert_eq!(redacted["d"]["user"]["email"], REDACTED);
+		assert_eq!(redacted["d"]["user"]["phone"], REDACTED);
+		assert_eq!(redacted["d"]["users"][0]["token"], REDACTED);
+		assert_eq!(redacted["d"]["users"][0]["id"], "1");
+
+		let redacted: Value = serde_json::from_str(&redact(r#"{"ticket": "ghi"}"#)).unwrap();
+		assert_eq!(redacted["ticket"], REDACTED);
+	}
+
+	#[tokio::test]
+	async fn recordings_replay() {
+		start();
+		gateway(include_str!("../models/fixtures/hello.json"));
+		gateway(include_str!("../models/fixtures/ready.json"));
+		rest("GET", "/api/v9/users/@me", 200, r#"{"id":"1"}"#);
+		rest("GET", "/api/v9/users/@me", 200, r#"{"id":"2"}"#);
+		gateway(&format!(
+			r#"{{"op": 0, "s": 2, "t": "MESSAGE_CREATE", "d": {}}}"#,
+			include_str!("../models/fixtures/message_create.json")
+		));
+		stop().await;
+		assert!(!recording());
+
+		assert!(start_replay().await);
+		let recording = replay().unwrap();
+		assert_eq!(recording.frames().count(), 3);
+
+		let mut dispatched = Vec::new();
+		play(&recording, |event| dispatched.push(event.t.unwrap_or_default())).await;
+		assert_eq!(dispatched, ["READY", "MESSAGE_CREATE"]);
+
+		// answers go in order, the last one sticks around
+		let id = || response("GET", "/api/v9/users/@me").map(|(_, body)| body);
+		assert_eq!(id().as_deref(), Some(r#"{"id":"1"}"#));
+		assert_eq!(id().as_deref(), Some(r#"{"id":"2"}"#));
+		assert_eq!(id().as_deref(), Some(r#"{"id":"2"}"#));
+		assert_eq!(response("GET", "/api/v9/guilds/1"), None);
+
+		stop_replay();
+		assert!(!replaying());
+	}
+
+	#[tokio::test]
+	async fn exports_can_be_imported() {
+		let recording = Recording {
+			started: 1,
+			entries: vec![Entry::Gateway {
+				at:    0,
+				frame: include_str!("../models/fixtures/hello.json").to_string(),
+			}],
+		};
+
+		import(&serde_json::to_string(&recording).unwrap()).await.unwrap();
+		assert_eq!(saved().await, Some(recording));
+		assert!(import("not a recording").await.is_err());
+	}
+
+	#[test]
+	fn fixtures_are_named_after_what_they_are() {
+		let rest = |path: &str| Entry::Rest {
+			at:     0,
+			method: "GET".to_string(),
+			path:   path.to_string(),
+			status: 200,
+			body:   "[]".to_string(),
+		};
+		let recording = Recording {
+			started: 0,
+			entries: vec![
+				Entry::Gateway {
+					at:    0,
+					frame: include_str!("../models/fixtures/hello.json").to_string(),
+				},
+				Entry::Gateway {
+					at:    0,
+					frame: include_str!("../models/fixtures/ready.json").to_string(),
+				},
+				rest("/api/v9/channels/1/messages?limit=50"),
+				rest("/api/v9/channels/2/messages?limit=50"),
+				rest("/api/v9/users/@me/billing/payment-sources"),
+			],
+		};
+
+		let fixtures = recording.fixtures();
+		let names: Vec<&str> = fixtures.iter().map(|(name, _)| name.as_str()).collect();
+		assert_eq!(names, [
+			"op_10.json",
+			"ready.json",
+			"get_channels_messages.json",
+			"get_channels_messages_2.json",
+			"get_users_me_billing_payment_sources.json",
+		]);
+		assert!(fixtures[0].1.contains("\n\t\""));
+	}
+
+	/// `COWCORD_RECORDING=recording.json cargo test write_fixtures -- --ignored` writes an exported recording
+	/// out to models/fixtures/recorded. swap the ids for fake ones before moving any of them up a directory
+	#[test]
+	#[ignore]
+	fn write_fixtures() {
+		let path = std::env::var("COWCORD_RECORDING").expect("COWCORD_RECORDING should point at an exported recording");
+		let recording: Recording = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+		let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/models/fixtures/recorded");
+		std::fs::create_dir_all(&dir).unwrap();
+		for (name, json) in recording.fixtures() {
+			std::fs::write(dir.join(name), json).unwrap();
+		}
+	}
+}
@@ -0,0 +1,478 @@
+// https://docs.discord.sex/remote-authentication/desktop
+// logging in by scanning a qr code with the mobile app, so the password never gets typed in here
+
+use std::error::Error;
+
+use dioxus::prelude::*;
+use futures_channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
+use futures_util::{Stream, StreamExt};
+
+use crate::models::remote_auth::{
+	PendingUser,
+	RemoteAuthClientMessage,
+	RemoteAuthLoginRequest,
+	RemoteAuthLoginResponse,
+	RemoteAuthServerMessage,
+};
+use crate::utils::config;
+use crate::utils::platform::{self, Connection};
+use crate::utils::request::RequestClient;
+
+const LOGIN_URL: &str = "https://discord.com/ra/";
+// the gateway hangs up on anything else
+const ORIGIN: &str = "https://discord.com";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum RemoteAuthStep {
+	Connecting,
+	/// the url to show as a qr code
+	Scan(String),
+	/// scanned, waiting for them to confirm on their phone
+	Confirm(PendingUser),
+	LoggingIn,
+	Cancelled,
+	Failed(String),
+}
+
+/// the keypair lives behind this so the flow can run against webcrypto in the browser and something fake in tests
+pub trait RemoteAuthCrypto {
+	/// base64 spki of the public key
+	fn public_key(&mut self) -> impl Future<Output = Result<String, Box<dyn Error>>>;
+
+	/// decrypts the nonce and hashes it into the base64url proof
+	fn proof(
+		&mut self,
+		encrypted_nonce: &str,
+	) -> impl Future<Output = Result<String, Box<dyn Error>>>;
+
+	fn decrypt(
+		&mut self,
+		data: &str,
+	) -> impl Future<Output = Result<String, Box<dyn Error>>>;
+}
+
+fn send(
+	outgoing: &UnboundedSender<RemoteAuthClientMessage>,
+	message: RemoteAuthClientMessage,
+) -> Result<(), Box<dyn Error>> {
+	outgoing
+		.unbounded_send(message)
+		.map_err(|e| format!("Remote auth connection closed: {}", e).into())
+}
+
+/// drives the handshake until there's a token, `None` if it got cancelled on the phone.
+/// `exchange` trades the ticket for the encrypted token, heartbeats are up to the connection
+pub async fn run(
+	mut incoming: impl Stream<Item = RemoteAuthServerMessage> + Unpin,
+	outgoing: &UnboundedSender<RemoteAuthClientMessage>,
+	crypto: &mut impl RemoteAuthCrypto,
+	exchange: impl AsyncFnOnce(String) -> Result<String, Box<dyn Error>>,
+	mut on_step: impl FnMut(RemoteAuthStep),
+) -> Result<Option<String>, Box<dyn Error>> {
+	on_step(RemoteAuthStep::Connecting);
+
+	while let Some(message) = incoming.next().await {
+		match message {
+			| RemoteAuthServerMessage::Hello { .. } => {
+				let encoded_public_key = crypto.public_key().await?;
+				send(outgoing, RemoteAuthClientMessage::Init { encoded_public_key })?;
+			},
+			| RemoteAuthServerMessage::NonceProof { encrypted_nonce } => {
+				let proof = crypto.proof(&encrypted_nonce).await?;
+				send(outgoing, RemoteAuthClientMessage::NonceProof { proof })?;
+			},
+			| RemoteAuthServerMessage::PendingRemoteInit { fingerprint } => {
+				on_step(RemoteAuthStep::Scan(format!("{}{}", LOGIN_URL, fingerprint)));
+			},
+			| RemoteAuthServerMessage::PendingTicket { encrypted_user_payload } => {
+				let payload = crypto.decrypt(&encrypted_user_payload).await?;
+				let user = PendingUser::parse(&payload).ok_or("Malformed remote auth user payload")?;
+				on_step(RemoteAuthStep::Confirm(user));
+			},
+			| RemoteAuthServerMessage::PendingLogin { ticket } => {
+				on_step(RemoteAuthStep::LoggingIn);
+				let encrypted_token = exchange(ticket).await?;
+				return Ok(Some(crypto.decrypt(&encrypted_token).await?));
+			},
+			| RemoteAuthServerMessage::Cancel => {
+				on_step(RemoteAuthStep::Cancelled);
+				return Ok(None);
+			},
+			| RemoteAuthServerMessage::HeartbeatAck => {},
+		}
+	}
+
+	Err("Remote auth connection closed".into())
+}
+
+pub async fn exchange_ticket(ticket: String) -> Result<String, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	let response: RemoteAuthLoginResponse = client
+		.post("/users/@me/remote-auth/login", &RemoteAuthLoginRequest { ticket })
+		.await?;
+	Ok(response.encrypted_token)
+}
+
+// an rsa-oaep keypair that never leaves webcrypto, the public key is sent first and then every
+// request gets one response back
+const WEBCRYPTO: &str = r#"
+	const base64 = (buffer) => btoa(String.fromCharCode(...new Uint8Array(buffer)));
+	const keys = await crypto.subtle.generateKey(
+		{ name: "RSA-OAEP", modulusLength: 2048, publicExponent: new Uint8Array([1, 0, 1]), hash: "SHA-256" },
+		false,
+		["decrypt"],
+	);
+	dioxus.send(base64(await crypto.subtle.exportKey("spki", keys.publicKey)));
+	while (true) {
+		const [kind, data] = await dioxus.recv();
+		const encrypted = Uint8Array.from(atob(data), (c) => c.charCodeAt(0));
+		const decrypted = await crypto.subtle.decrypt({ name: "RSA-OAEP" }, keys.privateKey, encrypted);
+		if (kind === "proof") {
+			const digest = await crypto.subtle.digest("SHA-256", decrypted);
+			dioxus.send(base64(digest).replace(/\+/g, "-").replace(/\//g, "_").replace(/=+$/, ""));
+		} else {
+			dioxus.send(new TextDecoder().decode(decrypted));
+		}
+	}
+"#;
+
+pub struct WebCrypto {
+	eval: document::Eval,
+}
+
+impl WebCrypto {
+	pub fn new() -> Self {
+		WebCrypto {
+			eval: document::eval(WEBCRYPTO),
+		}
+	}
+
+	async fn request(
+		&mut self,
+		kind: &str,
+		data: &str,
+	) -> Result<String, Box<dyn Error>> {
+		self.eval.send((kind, data)).map_err(|e| format!("{:?}", e))?;
+		Ok(self.eval.recv().await.map_err(|e| format!("{:?}", e))?)
+	}
+}
+
+impl Default for WebCrypto {
+	fn default() -> Self { Self::new() }
+}
+
+impl RemoteAuthCrypto for WebCrypto {
+	// the script sends it once the keypair is ready
+	async fn public_key(&mut self) -> Result<String, Box<dyn Error>> {
+		Ok(self.eval.recv().await.map_err(|e| format!("{:?}", e))?)
+	}
+
+	async fn proof(
+		&mut self,
+		encrypted_nonce: &str,
+	) -> Result<String, Box<dyn Error>> {
+		self.request("proof", encrypted_nonce).await
+	}
+
+	async fn decrypt(
+		&mut self,
+		data: &str,
+	) -> Result<String, Box<dyn Error>> {
+		self.request("decrypt", data).await
+	}
+}
+
+/// opens the socket and heartbeats on it, messages come out of the receiver and whatever goes into
+/// the sender gets sent, closing it closes the socket. the real gateway only lets discord.com connect,
+/// see `Config::remote_auth_reachable`. outlives whoever called it until the socket closes
+pub async fn connect(
+	url: &str,
+) -> Result<(UnboundedReceiver<RemoteAuthServerMessage>, UnboundedSender<RemoteAuthClientMessage>), Box<dyn Error>> {
+	let Connection {
+		incoming: mut socket_incoming,
+		outgoing: socket_outgoing,
+		..
+	} = platform::connect(url, Some(ORIGIN)).await?;
+	let (incoming_tx, incoming) = unbounded();
+	let (outgoing, mut outgoing_rx) = unbounded::<RemoteAuthClientMessage>();
+
+	let heartbeat_outgoing = outgoing.clone();
+	platform::spawn(async move {
+		while let Some(text) = socket_incoming.next().await {
+			match serde_json::from_str::<RemoteAuthServerMessage>(&text) {
+				| Ok(message) => {
+					if let RemoteAuthServerMessage::Hello { heartbeat_interval, .. } = message {
+						let outgoing = heartbeat_outgoing.clone();
+						platform::spawn(async move {
+							loop {
+								platform::sleep(heartbeat_interval).await;
+								if outgoing.unbounded_send(RemoteAuthClientMessage::Heartbeat).is_err() {
+									break;
+								}
+							}
+						});
+					}
+					let _ = incoming_tx.unbounded_send(message);
+				},
+				| Err(e) => platform::error(&format!("Unhandled remote auth message {}: {}", text, e)),
+			}
+		}
+		// the socket's gone, which also stops the heartbeat
+		heartbeat_outgoing.close_channel();
+	});
+
+	platform::spawn(async move {
+		while let Some(message) = outgoing_rx.next().await {
+			if let Ok(text) = serde_json::to_string(&message) {
+				let _ = socket_outgoing.unbounded_send(text);
+			}
+		}
+	});
+
+	Ok((incoming, outgoing))
+}
+
+/// runs the whole flow for as long as the calling component is mounted, resolving to the token
+pub fn use_remote_auth(on_token: EventHandler<String>) -> Signal<RemoteAuthStep> {
+	let mut step = use_signal(|| RemoteAuthStep::Connecting);
+	let mut socket = use_hook(|| CopyValue::new(None::<UnboundedSender<RemoteAuthClientMessage>>));
+
+	use_hook(move || {
+		spawn(async move {
+			let (incoming, outgoing) = match connect(&config::get().remote_auth_url).await {
+				| Ok(connection) => connection,
+				| Err(e) => {
+					step.set(RemoteAuthStep::Failed(e.to_string()));
+					return;
+				},
+			};
+			socket.set(Some(outgoing.clone()));
+
+			let mut crypto = WebCrypto::new();
+			match run(incoming, &outgoing, &mut crypto, exchange_ticket, |s| step.set(s)).await {
+				| Ok(Some(token)) => on_token.call(token),
+				| Ok(None) => {},
+				| Err(e) => {
+					platform::error(&format!("Remote auth failed: {}", e));
+					step.set(RemoteAuthStep::Failed(e.to_string()));
+				},
+			}
+			outgoing.close_channel();
+		});
+	});
+
+	use_drop(move || {
+		if let Some(outgoing) = socket.peek().as_ref() {
+			outgoing.close_channel();
+		}
+	});
+
+	step
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use base64::Engine;
+	use base64::engine::general_purpose::STANDARD;
+	use futures_executor::block_on;
+	use futures_util::SinkExt;
+	use futures_util::future::join;
+	use serde_json::{Value, json};
+	use tokio::net::TcpListener;
+	use tokio_tungstenite::tungstenite::Message as Frame;
+	use tokio_tungstenite::tungstenite::handshake::server::Request;
+
+	use super::*;
+	use crate::utils::config::Config;
+
+	/// "encrypts" with plain base64 so the mock server can produce payloads
+	struct MockCrypto;
+
+	impl RemoteAuthCrypto for MockCrypto {
+		async fn public_key(&mut self) -> Result<String, Box<dyn Error>> { Ok("public-key".to_string()) }
+
+		async fn proof(
+			&mut self,
+			encrypted_nonce: &str,
+		) -> Result<String, Box<dyn Error>> {
+			Ok(format!("proof-of-{}", self.decrypt(encrypted_nonce).await?))
+		}
+
+		async fn decrypt(
+			&mut self,
+			data: &str,
+		) -> Result<String, Box<dyn Error>> {
+			Ok(String::from_utf8(STANDARD.decode(data)?)?)
+		}
+	}
+
+	fn encrypt(data: &str) -> String { STANDARD.encode(data) }
+
+	/// a scripted remote auth server, checks what the client says before each reply
+	async fn mock_server(
+		to_client: UnboundedSender<RemoteAuthServerMessage>,
+		mut from_client: UnboundedReceiver<RemoteAuthClientMessage>,
+		cancel: bool,
+	) {
+		let send = |message| to_client.unbounded_send(message).unwrap();
+
+		send(RemoteAuthServerMessage::Hello {
+			heartbeat_interval: 41250,
+			timeout_ms:         120000,
+		});
+		assert_eq!(from_client.next().await, Some(RemoteAuthClientMessage::Init {
+			encoded_public_key: "public-key".to_string(),
+		}));
+
+		send(RemoteAuthServerMessage::NonceProof {
+			encrypted_nonce: encrypt("nonce"),
+		});
+		assert_eq!(from_client.next().await, Some(RemoteAuthClientMessage::NonceProof {
+			proof: "proof-of-nonce".to_string(),
+		}));
+
+		send(RemoteAuthServerMessage::PendingRemoteInit {
+			fingerprint: "fingerprint".to_string(),
+		});
+		send(RemoteAuthServerMessage::HeartbeatAck);
+		send(RemoteAuthServerMessage::PendingTicket {
+			encrypted_user_payload: encrypt("852892297661906993:0:a_hash:cow"),
+		});
+		send(if cancel {
+			RemoteAuthServerMessage::Cancel
+		} else {
+			RemoteAuthServerMessage::PendingLogin {
+				ticket: "ticket".to_string(),
+			}
+		});
+	}
+
+	/// the same handshake as `mock_server` but over a websocket, checking each reply as json and acking heartbeats
+	// the handshake callback's error type is tungstenite's, not ours
+	#[allow(clippy::result_large_err)]
+	async fn mock_gateway() -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let url = format!("ws://{}", listener.local_addr().unwrap());
+
+		tokio::spawn(async move {
+			let (stream, _) = listener.accept().await.unwrap();
+			let mut socket = tokio_tungstenite::accept_hdr_async(stream, |request: &Request, response| {
+				assert_eq!(request.headers()["Origin"], ORIGIN);
+				Ok(response)
+			})
+			.await
+			.unwrap();
+			let script = [
+				(
+					json!({ "op": "hello", "heartbeat_interval": 50, "timeout_ms": 120000 }),
+					Some(json!({ "op": "init", "encoded_public_key": "public-key" })),
+				),
+				(
+					json!({ "op": "nonce_proof", "encrypted_nonce": encrypt("nonce") }),
+					Some(json!({ "op": "nonce_proof", "proof": "proof-of-nonce" })),
+				),
+				(json!({ "op": "pending_remote_init", "fingerprint": "fingerprint" }), None),
+				(
+					json!({ "op": "pending_ticket", "encrypted_user_payload": encrypt("852892297661906993:0:a_hash:cow") }),
+					None,
+				),
+				(json!({ "op": "pending_login", "ticket": "ticket" }), None),
+			];
+
+			for (message, reply) in script {
+				socket.send(Frame::Text(message.to_string())).await.unwrap();
+				let Some(reply) = reply else {
+					continue;
+				};
+				loop {
+					let Some(Ok(Frame::Text(text))) = socket.next().await else {
+						panic!("Client hung up before sending {}", reply);
+					};
+					let payload: Value = serde_json::from_str(&text).unwrap();
+					if payload["op"] == "heartbeat" {
+						socket.send(Frame::Text(json!({ "op": "heartbeat_ack" }).to_string())).await.unwrap();
+						continue;
+					}
+					assert_eq!(payload, reply);
+					break;
+				}
+			}
+
+			// keeps the socket open until the client's done with it
+			while let Some(Ok(_)) = socket.next().await {}
+		});
+
+		url
+	}
+
+	fn login(cancel: bool) -> (Result<Option<String>, String>, Vec<RemoteAuthStep>) {
+		let (to_client, incoming) = unbounded();
+		let (outgoing, from_client) = unbounded();
+		let mut steps = Vec::new();
+		let mut crypto = MockCrypto;
+
+		let exchange = async |ticket: String| {
+			assert_eq!(ticket, "ticket");
+			Ok(encrypt("the.token"))
+		};
+		let client = run(incoming, &outgoing, &mut crypto, exchange, |step| steps.push(step));
+		let (result, _) = block_on(join(client, mock_server(to_client, from_client, cancel)));
+
+		(result.map_err(|e| e.to_string()), steps)
+	}
+
+	#[test]
+	fn logs_in_against_a_mock_server() {
+		let (result, steps) = login(false);
+
+		assert_eq!(result, Ok(Some("the.token".to_string())));
+		assert_eq!(steps, [
+			RemoteAuthStep::Connecting,
+			RemoteAuthStep::Scan("https://discord.com/ra/fingerprint".to_string()),
+			RemoteAuthStep::Confirm(PendingUser::parse("852892297661906993:0:a_hash:cow").unwrap()),
+			RemoteAuthStep::LoggingIn,
+		]);
+	}
+
+	#[test]
+	fn cancelling_on_the_phone_ends_the_flow() {
+		let (result, steps) = login(true);
+
+		assert_eq!(result, Ok(None));
+		assert_eq!(steps.last(), Some(&RemoteAuthStep::Cancelled));
+	}
+
+	#[tokio::test]
+	async fn logs_in_over_a_websocket() {
+		config::set(Config {
+			remote_auth_url: mock_gateway().await,
+			..Config::default()
+		});
+
+		let (incoming, outgoing) = connect(&config::get().remote_auth_url).await.unwrap();
+		let mut steps = Vec::new();
+		let mut crypto = MockCrypto;
+		let exchange = async |ticket: String| {
+			assert_eq!(ticket, "ticket");
+			Ok(encrypt("the.token"))
+		};
+		let client = run(incoming, &outgoing, &mut crypto, exchange, |step| steps.push(step));
+		let result = tokio::time::timeout(Duration::from_secs(5), client).await.unwrap();
+		outgoing.close_channel();
+
+		assert_eq!(result.unwrap(), Some("the.token".to_string()));
+		assert_eq!(steps.last(), Some(&RemoteAuthStep::LoggingIn));
+	}
+
+	#[test]
+	fn pending_user_payload() {
+		let user = PendingUser::parse("852892297661906993:0:0:cow").unwrap();
+
+		assert_eq!(user.username, "cow");
+		assert_eq!(user.avatar, None);
+		assert!(PendingUser::parse("not a payload").is_none());
+	}
+}
@@ -1,41 +1,243 @@
 use std::error::Error;
+use std::fmt;
 
-use reqwest::{Client, Response, StatusCode};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 
+use crate::models::error::RateLimited;
+use crate::models::mfa::{MfaFinishRequest, MfaFinishResponse, MfaMethods, MfaObject};
+use crate::utils::{config, platform, recorder, sudo, token};
+
 pub struct RequestClient {
-	client:   Client,
-	api_base: String,
+	client:      Client,
+	origin:      String,
+	api_version: u8,
+	token:       Option<String>,
+	headers:     Vec<(&'static str, String)>,
+}
+
+/// a non 2xx response, the body is kept so callers can pick the json error apart
+#[derive(Debug)]
+pub struct ApiError {
+	pub status: u16,
+	pub body:   String,
+}
+
+impl fmt::Display for ApiError {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		write!(f, "Request failed with status: {}. Response: {}", self.status, self.body)
+	}
 }
 
-const DISCORD: &str = "https://discord.com/";
-const API_VERSION: &str = "9";
+impl Error for ApiError {}
+
+// how many times a rate limited request gets waited out before giving up
+const RATE_LIMIT_ATTEMPTS: u32 = 3;
+
+impl Default for RequestClient {
+	fn default() -> Self { Self::new() }
+}
 
 impl RequestClient {
 	pub fn new() -> Self {
+		let config = config::get();
 		RequestClient {
-			client:   Client::new(),
-			api_base: format!("/api/v{}", API_VERSION),
+			client:      Client::new(),
+			origin:      config.api_base,
+			api_version: config.api_version,
+			token:       token::get(),
+			headers:     Vec::new(),
+		}
+	}
+
+	/// sent with every request made by this client, eg. captcha solutions
+	pub fn with_header(
+		mut self,
+		name: &'static str,
+		value: String,
+	) -> Self {
+		self.headers.push((name, value));
+		self
+	}
+
+	fn request(
+		&self,
+		method: Method,
+		endpoint: &str,
+	) -> RequestBuilder {
+		let url = format!("{}/api/v{}{}", self.origin, self.api_version, endpoint);
+
+		let mut request = self
+			.client
+			.request(method, &url)
+			.header("Origin", &self.origin);
+		for (name, value) in &self.headers {
+			request = request.header(*name, value);
+		}
+
+		match &self.token {
+			| Some(token) => request.header("Authorization", token),
+			| None => request,
+		}
+	}
+
+	/// retries once with a fresh mfa token if the request turns out to need sudo mode, and flags the
+	/// session as expired on a 401
+	async fn send<T>(
+		&self,
+		request: RequestBuilder,
+	) -> Result<T, Box<dyn Error>>
+	where
+		T: DeserializeOwned,
+	{
+		let retry = request.try_clone();
+		let result = Self::exchange(request).await;
+
+		let mfa = match &result {
+			| Err(e) => e.downcast_ref::<ApiError>().and_then(|e| sudo::required(&e.body)),
+			| Ok(_) => None,
+		};
+		let result = match (mfa, retry) {
+			| (Some(mfa), Some(retry)) if self.reauthenticate(&mfa).await => {
+				Self::exchange(retry).await
+			},
+			| _ => result,
+		};
+
+		// a 401 without a token is just a login endpoint saying no
+		if let Err(e) = &result
+			&& self.token.is_some()
+			&& e.downcast_ref::<ApiError>().is_some_and(|e| e.status == 401)
+		{
+			token::expired();
 		}
+		result
+	}
+
+	/// sends it, or finds it in the recording while one's being replayed. responses get recorded when the
+	/// recorder's on
+	async fn exchange<T>(request: RequestBuilder) -> Result<T, Box<dyn Error>>
+	where
+		T: DeserializeOwned,
+	{
+		let Some((method, path)) = request.try_clone().and_then(|r| r.build().ok()).map(|r| {
+			let query = r.url().query().map(|q| format!("?{}", q)).unwrap_or_default();
+			(r.method().to_string(), format!("{}{}", r.url().path(), query))
+		}) else {
+			return Self::handle_response(Self::send_waiting(request).await?).await;
+		};
+
+		if recorder::replaying() {
+			let (status, body) = recorder::response(&method, &path).unwrap_or_else(|| {
+				(404, format!(r#"{{"message": "{} {} isn't in the recording", "code": 0}}"#, method, path))
+			});
+			return Self::parse(status, body);
+		}
+
+		let response = Self::send_waiting(request).await?;
+		let status = response.status().as_u16();
+		let body = response.text().await?;
+		recorder::rest(&method, &path, status, &body);
+		Self::parse(status, body)
+	}
+
+	/// sends with the mfa token if there is one, waiting out 429s and trying again a couple of times
+	async fn send_waiting(mut request: RequestBuilder) -> Result<Response, Box<dyn Error>> {
+		let mut attempts = 1;
+		loop {
+			let retry = request.try_clone();
+			let response = sudo::authorize(request).send().await?;
+
+			let Some(retry) = retry.filter(|_| {
+				response.status() == StatusCode::TOO_MANY_REQUESTS && attempts < RATE_LIMIT_ATTEMPTS
+			}) else {
+				return Ok(response);
+			};
+			let retry_after = response.json::<RateLimited>().await.map_or(1.0, |r| r.retry_after);
+			platform::sleep((retry_after * 1000.0) as u32).await;
+
+			request = retry;
+			attempts += 1;
+		}
+	}
+
+	/// keeps asking until mfa goes through or the dialog is cancelled
+	async fn reauthenticate(
+		&self,
+		mfa: &MfaObject,
+	) -> bool {
+		let mut error = None;
+
+		while let Some((method, data)) = sudo::ask(mfa, error.take()).await {
+			match self.finish_mfa(&mfa.ticket, method, data).await {
+				| Ok(token) => {
+					sudo::set_token(token);
+					return true;
+				},
+				| Err(e) => {
+					let message = e
+						.downcast_ref::<ApiError>()
+						.and_then(|e| serde_json::from_str::<serde_json::Value>(&e.body).ok())
+						.and_then(|body| body["message"].as_str().map(str::to_string));
+					error = Some(message.unwrap_or_else(|| e.to_string()));
+				},
+			}
+		}
+
+		false
+	}
+
+	// goes around `send` so a bad code can't end up asking for mfa again
+	async fn finish_mfa(
+		&self,
+		ticket: &str,
+		mfa_type: MfaMethods,
+		data: String,
+	) -> Result<String, Box<dyn Error>> {
+		let request = MfaFinishRequest {
+			ticket: ticket.to_string(),
+			mfa_type,
+			data,
+		};
+		let response = self.request(Method::POST, "/mfa/finish").json(&request).send().await?;
+
+		let response: MfaFinishResponse = Self::handle_response(response).await?;
+		Ok(response.token)
 	}
 
 	async fn handle_response<T>(response: Response) -> Result<T, Box<dyn Error>>
 	where
 		T: DeserializeOwned,
 	{
-		let status = response.status();
-		let response_text = response.text().await?;
+		let status = response.status().as_u16();
+		Self::parse(status, response.text().await?)
+	}
 
-		if status.is_success() {
-			let result: T = serde_json::from_str(&response_text)?;
+	fn parse<T>(
+		status: u16,
+		response_text: String,
+	) -> Result<T, Box<dyn Error>>
+	where
+		T: DeserializeOwned,
+	{
+		if (200..300).contains(&status) {
+			// 204 No Content and friends, lets `()` be used as the response type
+			let body = if response_text.is_empty() {
+				"null"
+			} else {
+				&response_text
+			};
+			let result: T = serde_json::from_str(body)?;
 			Ok(result)
 		} else {
-			Err(format!(
-				"Request failed with status: {}. Response: {}",
-				status, response_text
-			)
-			.into())
+			Err(Box::new(ApiError {
+				status,
+				body: response_text,
+			}))
 		}
 	}
 
@@ -48,17 +250,31 @@ impl RequestClient {
 		T: Serialize,
 		R: DeserializeOwned,
 	{
-		let url = format!("{}{}{}", DISCORD, self.api_base, endpoint);
+		self.send(self.request(Method::POST, endpoint).json(body)).await
+	}
 
-		let response = self
-			.client
-			.post(&url)
-			.json(body)
-			.header("Origin", DISCORD)
-			.send()
-			.await?;
+	pub async fn patch<T, R>(
+		&self,
+		endpoint: &str,
+		body: &T,
+	) -> Result<R, Box<dyn Error>>
+	where
+		T: Serialize,
+		R: DeserializeOwned,
+	{
+		self.send(self.request(Method::PATCH, endpoint).json(body)).await
+	}
 
-		Self::handle_response(response).await
+	pub async fn put<T, R>(
+		&self,
+		endpoint: &str,
+		body: &T,
+	) -> Result<R, Box<dyn Error>>
+	where
+		T: Serialize,
+		R: DeserializeOwned,
+	{
+		self.send(self.request(Method::PUT, endpoint).json(body)).await
 	}
 
 	pub async fn get<R>(
@@ -68,16 +284,19 @@ impl RequestClient {
 	where
 		R: DeserializeOwned,
 	{
-		let url = format!("{}{}{}", DISCORD, self.api_base, endpoint);
-
-		let response = self
-			.client
-			.get(&url)
-			.header("Origin", DISCORD)
-			.send()
-			.await?;
+		self.send(self.request(Method::GET, endpoint)).await
+	}
 
-		Self::handle_response(response).await
+	pub async fn get_with_query<Q, R>(
+		&self,
+		endpoint: &str,
+		query: &Q,
+	) -> Result<R, Box<dyn Error>>
+	where
+		Q: Serialize,
+		R: DeserializeOwned,
+	{
+		self.send(self.request(Method::GET, endpoint).query(query)).await
 	}
 
 	pub async fn delete<R>(
@@ -87,15 +306,79 @@ impl RequestClient {
 	where
 		R: DeserializeOwned,
 	{
-		let url = format!("{}{}{}", DISCORD, self.api_base, endpoint);
+		self.send(self.request(Method::DELETE, endpoint)).await
+	}
+}
 
-		let response = self
-			.client
-			.delete(&url)
-			.header("Origin", DISCORD)
-			.send()
-			.await?;
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::models::chat::Message;
+	use crate::testing::in_runtime;
+	use crate::testing::rest::{self, MockRest, Route};
+	use crate::utils::config::Config;
+	use crate::utils::recorder;
+
+	const MESSAGES: &str = "/api/v9/channels/1/messages";
+
+	async fn mock(routes: Vec<Route>) -> MockRest {
+		let rest = MockRest::start(routes).await;
+		config::set(Config {
+			api_base: rest.url.clone(),
+			..Config::default()
+		});
+		rest
+	}
+
+	#[tokio::test]
+	async fn rate_limits_are_waited_out() {
+		let rest = mock(vec![
+			Route::new("GET", MESSAGES)
+				.respond(429, include_str!("../models/fixtures/rate_limited.json"))
+				.respond(200, include_str!("../models/fixtures/message_history.json")),
+		])
+		.await;
+
+		let messages = in_runtime(RequestClient::new().get::<Vec<Message>>("/channels/1/messages")).await;
+		assert!(!messages.unwrap().is_empty());
+		assert_eq!(rest.requests().len(), 2);
+	}
+
+	#[tokio::test]
+	async fn rate_limits_give_up_eventually() {
+		let rest = mock(vec![
+			Route::new("GET", MESSAGES).respond(429, include_str!("../models/fixtures/rate_limited.json")),
+		])
+		.await;
+
+		let error = in_runtime(RequestClient::new().get::<Vec<Message>>("/channels/1/messages"))
+			.await
+			.unwrap_err();
+		assert_eq!(error.downcast_ref::<ApiError>().map(|e| e.status), Some(429));
+		assert_eq!(rest.requests().len(), RATE_LIMIT_ATTEMPTS as usize);
+	}
+
+	#[tokio::test]
+	async fn recorded_responses_come_back() {
+		let live = mock(rest::discord()).await;
+		recorder::start();
+		let fetched = in_runtime(RequestClient::new().get::<Vec<Message>>("/channels/1/messages?limit=50")).await;
+		recorder::stop().await;
+		let recording = recorder::saved().await.unwrap();
+
+		// replays don't go anywhere
+		assert!(recorder::start_replay().await);
+		let replayed = in_runtime(RequestClient::new().get::<Vec<Message>>("/channels/1/messages?limit=50")).await;
+		assert_eq!(replayed.unwrap().len(), fetched.unwrap().len());
+		let missing = in_runtime(RequestClient::new().get::<Vec<Message>>("/channels/2/messages")).await;
+		assert_eq!(missing.unwrap_err().downcast_ref::<ApiError>().map(|e| e.status), Some(404));
+		assert_eq!(live.requests().len(), 1);
+		recorder::stop_replay();
 
-		Self::handle_response(response).await
+		// and they make a mock
+		let replayed = mock(rest::recorded(&recording)).await;
+		let messages = in_runtime(RequestClient::new().get::<Vec<Message>>("/channels/1/messages")).await;
+		assert!(!messages.unwrap().is_empty());
+		assert_eq!(replayed.requests().len(), 1);
 	}
 }
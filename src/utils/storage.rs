@@ -0,0 +1,522 @@
+// everything the client keeps between visits goes through here, so the same code works on web and desktop.
+// small values are read synchronously during render, big ones (caches) go through the async half since
+// indexeddb can't be read any other way
+
+use std::error::Error;
+#[cfg(any(feature = "desktop", test))]
+use std::path::PathBuf;
+
+use dioxus::prelude::*;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::models::types::Snowflake;
+use crate::utils::token;
+
+pub trait Storage {
+	fn get_raw(
+		&self,
+		key: &str,
+	) -> Option<String>;
+
+	fn set_raw(
+		&self,
+		key: &str,
+		value: &str,
+	) -> Result<(), Box<dyn Error>>;
+
+	fn remove(
+		&self,
+		key: &str,
+	) -> Result<(), Box<dyn Error>>;
+
+	/// for values too big for `set_raw`, the same as the sync methods unless the backend has somewhere better
+	fn load_raw(
+		&self,
+		key: &str,
+	) -> impl Future<Output = Option<String>> {
+		std::future::ready(self.get_raw(key))
+	}
+
+	fn store_raw(
+		&self,
+		key: &str,
+		value: &str,
+	) -> impl Future<Output = Result<(), Box<dyn Error>>> {
+		std::future::ready(self.set_raw(key, value))
+	}
+
+	fn delete(
+		&self,
+		key: &str,
+	) -> impl Future<Output = Result<(), Box<dyn Error>>> {
+		std::future::ready(self.remove(key))
+	}
+
+	/// `load_raw` for each key, backends where every request costs something can do them all in one go
+	fn load_many_raw(
+		&self,
+		keys: &[String],
+	) -> impl Future<Output = Vec<Option<String>>> {
+		async move {
+			let mut values = Vec::with_capacity(keys.len());
+			for key in keys {
+				values.push(self.load_raw(key).await);
+			}
+			values
+		}
+	}
+
+	fn get<T: DeserializeOwned>(
+		&self,
+		key: &str,
+	) -> Option<T> {
+		serde_json::from_str(&self.get_raw(key)?).ok()
+	}
+
+	fn set<T: Serialize>(
+		&self,
+		key: &str,
+		value: &T,
+	) -> Result<(), Box<dyn Error>> {
+		self.set_raw(key, &serde_json::to_string(value)?)
+	}
+
+	fn load<T: DeserializeOwned>(
+		&self,
+		key: &str,
+	) -> impl Future<Output = Option<T>> {
+		async move { serde_json::from_str(&self.load_raw(key).await?).ok() }
+	}
+
+	fn load_many<T: DeserializeOwned>(
+		&self,
+		keys: &[String],
+	) -> impl Future<Output = Vec<Option<T>>> {
+		async move {
+			self.load_many_raw(keys)
+				.await
+				.into_iter()
+				.map(|value| serde_json::from_str(&value?).ok())
+				.collect()
+		}
+	}
+
+	fn store<T: Serialize>(
+		&self,
+		key: &str,
+		value: &T,
+	) -> impl Future<Output = Result<(), Box<dyn Error>>> {
+		let json = serde_json::to_string(value);
+		async move { self.store_raw(key, &json?).await }
+	}
+}
+
+/// localStorage for small values and an indexeddb object store for big ones
+pub struct WebStorage;
+
+// one request against the "values" store, sends back the value for gets and null otherwise
+const INDEXED_DB: &str = r#"
+	const [action, key, value] = await dioxus.recv();
+	try {
+		const db = await new Promise((resolve, reject) => {
+			const open = indexedDB.open("cowcord", 1);
+			open.onupgradeneeded = () => open.result.createObjectStore("values");
+			open.onsuccess = () => resolve(open.result);
+			open.onerror = () => reject(open.error);
+		});
+		const result = await new Promise((resolve, reject) => {
+			const store = db.transaction("values", action === "get" ? "readonly" : "readwrite").objectStore("values");
+			const request = action === "get" ? store.get(key) : action === "put" ? store.put(value, key) : store.delete(key);
+			request.onsuccess = () => resolve(action === "get" ? request.result ?? null : null);
+			request.onerror = () => reject(request.error);
+		});
+		dioxus.send([true, result]);
+	} catch (e) {
+		dioxus.send([false, String(e)]);
+	}
+"#;
+
+// a get for each key in one transaction, sends back the values in the same order
+const INDEXED_DB_MANY: &str = r#"
+	const keys = await dioxus.recv();
+	try {
+		const db = await new Promise((resolve, reject) => {
+			const open = indexedDB.open("cowcord", 1);
+			open.onupgradeneeded = () => open.result.createObjectStore("values");
+			open.onsuccess = () => resolve(open.result);
+			open.onerror = () => reject(open.error);
+		});
+		const store = db.transaction("values", "readonly").objectStore("values");
+		const values = await Promise.all(keys.map((key) => new Promise((resolve, reject) => {
+			const request = store.get(key);
+			request.onsuccess = () => resolve(request.result ?? null);
+			request.onerror = () => reject(request.error);
+		})));
+		dioxus.send([true, values]);
+	} catch (e) {
+		dioxus.send([false, keys.map(() => null)]);
+	}
+"#;
+
+impl WebStorage {
+	fn local_storage() -> Result<web_sys::Storage, Box<dyn Error>> {
+		web_sys::window()
+			.ok_or("No window")?
+			.local_storage()
+			.map_err(|e| format!("{:?}", e))?
+			.ok_or_else(|| "No localStorage".into())
+	}
+
+	async fn indexed_db(
+		action: &str,
+		key: &str,
+		value: Option<&str>,
+	) -> Result<Option<String>, Box<dyn Error>> {
+		let mut eval = document::eval(INDEXED_DB);
+		eval.send((action, key, value)).map_err(|e| format!("{:?}", e))?;
+
+		let (ok, result) = eval
+			.recv::<(bool, Option<String>)>()
+			.await
+			.map_err(|e| format!("{:?}", e))?;
+		if ok {
+			Ok(result)
+		} else {
+			Err(result.unwrap_or_default().into())
+		}
+	}
+}
+
+impl Storage for WebStorage {
+	fn get_raw(
+		&self,
+		key: &str,
+	) -> Option<String> {
+		Self::local_storage()
+			.ok()?
+			.get_item(key)
+			.ok()
+			.flatten()
+			.filter(|value| !value.is_empty())
+	}
+
+	fn set_raw(
+		&self,
+		key: &str,
+		value: &str,
+	) -> Result<(), Box<dyn Error>> {
+		Self::local_storage()?
+			.set_item(key, value)
+			.map_err(|e| format!("{:?}", e).into())
+	}
+
+	fn remove(
+		&self,
+		key: &str,
+	) -> Result<(), Box<dyn Error>> {
+		Self::local_storage()?
+			.remove_item(key)
+			.map_err(|e| format!("{:?}", e).into())
+	}
+
+	async fn load_raw(
+		&self,
+		key: &str,
+	) -> Option<String> {
+		Self::indexed_db("get", key, None).await.ok().flatten()
+	}
+
+	async fn store_raw(
+		&self,
+		key: &str,
+		value: &str,
+	) -> Result<(), Box<dyn Error>> {
+		Self::indexed_db("put", key, Some(value)).await.map(|_| ())
+	}
+
+	async fn delete(
+		&self,
+		key: &str,
+	) -> Result<(), Box<dyn Error>> {
+		Self::indexed_db("delete", key, None).await.map(|_| ())
+	}
+
+	async fn load_many_raw(
+		&self,
+		keys: &[String],
+	) -> Vec<Option<String>> {
+		let mut eval = document::eval(INDEXED_DB_MANY);
+		if eval.send(keys).is_err() {
+			return vec![None; keys.len()];
+		}
+
+		match eval.recv::<(bool, Vec<Option<String>>)>().await {
+			| Ok((true, values)) if values.len() == keys.len() => values,
+			| _ => vec![None; keys.len()],
+		}
+	}
+}
+
+/// where desktop keeps its files, `cowcord` in the platform's data dir
+#[cfg(any(feature = "desktop", test))]
+pub fn data_dir() -> Option<PathBuf> {
+	let data = std::env::var_os("XDG_DATA_HOME")
+		.or_else(|| std::env::var_os("APPDATA"))
+		.map(PathBuf::from)
+		.or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))?;
+	Some(data.join("cowcord"))
+}
+
+/// one file per key under a directory
+#[cfg(any(feature = "desktop", test))]
+pub struct FileStorage {
+	dir: PathBuf,
+}
+
+#[cfg(any(feature = "desktop", test))]
+impl FileStorage {
+	pub fn new(dir: PathBuf) -> Self { FileStorage { dir } }
+
+	// keys have colons in them, which windows won't take
+	fn path(
+		&self,
+		key: &str,
+	) -> PathBuf {
+		let name: String = key
+			.chars()
+			.map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+			.collect();
+		self.dir.join(format!("{}.json", name))
+	}
+}
+
+#[cfg(any(feature = "desktop", test))]
+impl Storage for FileStorage {
+	fn get_raw(
+		&self,
+		key: &str,
+	) -> Option<String> {
+		std::fs::read_to_string(self.path(key)).ok()
+	}
+
+	fn set_raw(
+		&self,
+		key: &str,
+		value: &str,
+	) -> Result<(), Box<dyn Error>> {
+		std::fs::create_dir_all(&self.dir)?;
+		std::fs::write(self.path(key), value)?;
+		Ok(())
+	}
+
+	fn remove(
+		&self,
+		key: &str,
+	) -> Result<(), Box<dyn Error>> {
+		match std::fs::remove_file(self.path(key)) {
+			| Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+			| _ => Ok(()),
+		}
+	}
+}
+
+/// nothing leaves the process, for tests. clones share their values
+#[cfg(test)]
+#[derive(Default, Clone)]
+pub struct MemoryStorage {
+	values: std::rc::Rc<std::cell::RefCell<std::collections::HashMap<String, String>>>,
+}
+
+#[cfg(test)]
+thread_local! {
+	static MEMORY: MemoryStorage = MemoryStorage::default();
+}
+
+#[cfg(test)]
+impl Storage for MemoryStorage {
+	fn get_raw(
+		&self,
+		key: &str,
+	) -> Option<String> {
+		self.values.borrow().get(key).cloned()
+	}
+
+	fn set_raw(
+		&self,
+		key: &str,
+		value: &str,
+	) -> Result<(), Box<dyn Error>> {
+		self.values.borrow_mut().insert(key.to_string(), value.to_string());
+		Ok(())
+	}
+
+	fn remove(
+		&self,
+		key: &str,
+	) -> Result<(), Box<dyn Error>> {
+		self.values.borrow_mut().remove(key);
+		Ok(())
+	}
+}
+
+/// prefixes every key with the account's id, so accounts don't see each other's settings or caches
+pub struct Scoped<S> {
+	inner:  S,
+	prefix: String,
+}
+
+impl<S: Storage> Scoped<S> {
+	pub fn new(
+		inner: S,
+		account: Snowflake,
+	) -> Self {
+		Scoped {
+			inner,
+			prefix: format!("{}:", account),
+		}
+	}
+
+	fn key(
+		&self,
+		key: &str,
+	) -> String {
+		format!("{}{}", self.prefix, key)
+	}
+}
+
+impl<S: Storage> Storage for Scoped<S> {
+	fn get_raw(
+		&self,
+		key: &str,
+	) -> Option<String> {
+		self.inner.get_raw(&self.key(key))
+	}
+
+	fn set_raw(
+		&self,
+		key: &str,
+		value: &str,
+	) -> Result<(), Box<dyn Error>> {
+		self.inner.set_raw(&self.key(key), value)
+	}
+
+	fn remove(
+		&self,
+		key: &str,
+	) -> Result<(), Box<dyn Error>> {
+		self.inner.remove(&self.key(key))
+	}
+
+	async fn load_raw(
+		&self,
+		key: &str,
+	) -> Option<String> {
+		self.inner.load_raw(&self.key(key)).await
+	}
+
+	async fn store_raw(
+		&self,
+		key: &str,
+		value: &str,
+	) -> Result<(), Box<dyn Error>> {
+		self.inner.store_raw(&self.key(key), value).await
+	}
+
+	async fn delete(
+		&self,
+		key: &str,
+	) -> Result<(), Box<dyn Error>> {
+		self.inner.delete(&self.key(key)).await
+	}
+
+	async fn load_many_raw(
+		&self,
+		keys: &[String],
+	) -> Vec<Option<String>> {
+		let keys: Vec<String> = keys.iter().map(|key| self.key(key)).collect();
+		self.inner.load_many_raw(&keys).await
+	}
+}
+
+#[cfg(all(not(feature = "desktop"), not(test)))]
+pub type Platform = WebStorage;
+#[cfg(all(feature = "desktop", not(test)))]
+pub type Platform = FileStorage;
+#[cfg(test)]
+pub type Platform = MemoryStorage;
+
+/// whatever this platform stores things in, shared by every account
+pub fn local() -> Platform {
+	#[cfg(all(not(feature = "desktop"), not(test)))]
+	return WebStorage;
+	#[cfg(all(feature = "desktop", not(test)))]
+	return FileStorage::new(data_dir().unwrap_or_else(|| PathBuf::from("cowcord")));
+	#[cfg(test)]
+	return MEMORY.with(MemoryStorage::clone);
+}
+
+/// storage only the logged in account sees, falls back to the shared one before anyone is
+pub fn account() -> Scoped<Platform> {
+	let id = token::current_id().unwrap_or(Snowflake::new(0));
+	Scoped::new(local(), id)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashSet;
+
+	use futures_executor::block_on;
+
+	use super::*;
+
+	#[test]
+	fn typed_values_round_trip() {
+		let storage = MemoryStorage::default();
+		let collapsed: HashSet<Snowflake> = [Snowflake::new(1), Snowflake::new(2)].into();
+
+		storage.set("collapsed", &collapsed).unwrap();
+		assert_eq!(storage.get::<HashSet<Snowflake>>("collapsed"), Some(collapsed));
+		// wrong shape reads as missing instead of blowing up
+		assert_eq!(storage.get::<u32>("collapsed"), None);
+
+		block_on(async {
+			storage.store("big", &vec![1, 2, 3]).await.unwrap();
+			assert_eq!(storage.load::<Vec<u32>>("big").await, Some(vec![1, 2, 3]));
+			storage.delete("big").await.unwrap();
+			assert_eq!(storage.load::<Vec<u32>>("big").await, None);
+
+			storage.store("small", &vec![4]).await.unwrap();
+			let keys = ["big", "small"].map(String::from);
+			assert_eq!(storage.load_many::<Vec<u32>>(&keys).await, [None, Some(vec![4])]);
+		});
+	}
+
+	#[test]
+	fn accounts_dont_share_keys() {
+		let first = Scoped::new(MemoryStorage::default(), Snowflake::new(1));
+		first.set("theme", &"dark").unwrap();
+
+		assert_eq!(first.get::<String>("theme").as_deref(), Some("dark"));
+		assert_eq!(first.inner.get::<String>("1:theme").as_deref(), Some("dark"));
+		assert_eq!(first.inner.get::<String>("theme"), None);
+
+		let second = Scoped::new(first.inner, Snowflake::new(2));
+		assert_eq!(second.get::<String>("theme"), None);
+	}
+
+	#[test]
+	fn files_round_trip() {
+		let dir = std::env::temp_dir().join(format!("cowcord-storage-{}", std::process::id()));
+		let storage = FileStorage::new(dir.clone());
+
+		storage.set("1:collapsed_categories_2", &[3, 4]).unwrap();
+		assert_eq!(storage.get::<Vec<u32>>("1:collapsed_categories_2"), Some(vec![3, 4]));
+		assert!(dir.join("1_collapsed_categories_2.json").exists());
+
+		storage.remove("1:collapsed_categories_2").unwrap();
+		storage.remove("1:collapsed_categories_2").unwrap();
+		assert_eq!(storage.get::<Vec<u32>>("1:collapsed_categories_2"), None);
+		let _ = std::fs::remove_dir_all(dir);
+	}
+}
@@ -0,0 +1,85 @@
+// https://docs.discord.sex/authentication#mfa-verification
+// sensitive actions answer with code 60003 and a ticket until mfa is redone, the token from
+// /mfa/finish then goes in X-Discord-MFA-Authorization for a few minutes
+
+use dioxus::prelude::*;
+use futures_channel::oneshot;
+use reqwest::RequestBuilder;
+
+use crate::models::mfa::{MfaMethod, MfaMethods, MfaObject, MfaRequired};
+use crate::models::types::now_ms;
+
+pub const MFA_REQUIRED: u64 = 60003;
+pub const HEADER: &str = "X-Discord-MFA-Authorization";
+// the token is good for 5 minutes, drop it a bit before that
+const TOKEN_LIFETIME_MS: u64 = 4 * 60 * 1000;
+
+/// what the mfa dialog is asking for, `answer` resolves the request that's waiting on it
+pub struct MfaPrompt {
+	pub ticket:  String,
+	pub methods: Vec<MfaMethod>,
+	pub error:   Option<String>,
+	answer:      oneshot::Sender<Option<(MfaMethods, String)>>,
+}
+
+pub static MFA_PROMPT: GlobalSignal<Option<MfaPrompt>> = Signal::global(|| None);
+// the token and when it stops being usable
+static MFA_TOKEN: GlobalSignal<Option<(String, u64)>> = Signal::global(|| None);
+
+/// the mfa ticket if `body` is a 60003 response
+pub fn required(body: &str) -> Option<MfaObject> {
+	serde_json::from_str::<MfaRequired>(body)
+		.ok()
+		.filter(|r| r.code == MFA_REQUIRED)
+		.map(|r| r.mfa)
+}
+
+/// adds the mfa token while it's still fresh
+pub fn authorize(request: RequestBuilder) -> RequestBuilder {
+	match &*MFA_TOKEN.peek() {
+		| Some((token, expires)) if *expires > now_ms() => request.header(HEADER, token),
+		| _ => request,
+	}
+}
+
+pub fn set_token(token: String) { *MFA_TOKEN.write() = Some((token, now_ms() + TOKEN_LIFETIME_MS)); }
+
+pub fn clear_token() { *MFA_TOKEN.write() = None; }
+
+/// shows the mfa dialog and waits for a method and its code, `None` if it got cancelled
+pub async fn ask(
+	mfa: &MfaObject,
+	error: Option<String>,
+) -> Option<(MfaMethods, String)> {
+	let (answer, response) = oneshot::channel();
+
+	*MFA_PROMPT.write() = Some(MfaPrompt {
+		ticket: mfa.ticket.clone(),
+		methods: mfa.methods.clone(),
+		error,
+		answer,
+	});
+
+	response.await.ok().flatten()
+}
+
+pub fn answer(response: Option<(MfaMethods, String)>) {
+	if let Some(prompt) = MFA_PROMPT.write().take() {
+		let _ = prompt.answer.send(response);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn only_60003_needs_mfa() {
+		let mfa = required(include_str!("../models/fixtures/mfa_required.json")).unwrap();
+		assert_eq!(mfa.methods.len(), 3);
+
+		let other = r#"{"message": "Invalid two-factor code", "code": 60008, "mfa": {"ticket": "t", "methods": []}}"#;
+		assert!(required(other).is_none());
+		assert!(required(r#"{"message": "401: Unauthorized", "code": 0}"#).is_none());
+	}
+}
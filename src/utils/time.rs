@@ -0,0 +1,143 @@
+#[cfg(not(target_arch = "wasm32"))]
+use iso8601_timestamp::{Duration, Timestamp};
+#[cfg(target_arch = "wasm32")]
+use js_sys::{Date, Object, Reflect};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::JsValue;
+
+use crate::models::types::now_ms;
+
+const MINUTE: i64 = 60;
+const HOUR: i64 = 60 * MINUTE;
+const DAY: i64 = 24 * HOUR;
+const MONTH: i64 = 30 * DAY;
+const YEAR: i64 = 365 * DAY;
+// the furthest a js Date goes either side of the epoch, timestamps in messages can be anything
+const MAX_DATE_MS: i64 = 8_640_000_000_000_000;
+
+/// formats a unix timestamp in ms like discord's timestamp styles,
+/// https://docs.discord.sex/reference#timestamp-styles
+pub fn format(
+	unix_ms: i64,
+	style: char,
+) -> String {
+	let unix_ms = unix_ms.clamp(-MAX_DATE_MS, MAX_DATE_MS);
+	if style == 'R' {
+		return relative(unix_ms.saturating_sub(now_ms() as i64));
+	}
+
+	locale_format(unix_ms, style)
+}
+
+// the browser formats in the user's locale and timezone
+#[cfg(target_arch = "wasm32")]
+fn locale_format(
+	unix_ms: i64,
+	style: char,
+) -> String {
+	let options: &[(&str, &str)] = match style {
+		| 't' => &[("hour", "numeric"), ("minute", "2-digit")],
+		| 'T' => &[("hour", "numeric"), ("minute", "2-digit"), ("second", "2-digit")],
+		| 'd' => &[("day", "2-digit"), ("month", "2-digit"), ("year", "numeric")],
+		| 'D' => &[("day", "numeric"), ("month", "long"), ("year", "numeric")],
+		| 'F' => &[
+			("weekday", "long"),
+			("day", "numeric"),
+			("month", "long"),
+			("year", "numeric"),
+			("hour", "numeric"),
+			("minute", "2-digit"),
+		],
+		| _ => &[
+			("day", "numeric"),
+			("month", "long"),
+			("year", "numeric"),
+			("hour", "numeric"),
+			("minute", "2-digit"),
+		],
+	};
+
+	let object = Object::new();
+	for (key, value) in options {
+		let _ = Reflect::set(&object, &JsValue::from_str(key), &JsValue::from_str(value));
+	}
+
+	Date::new(&JsValue::from_f64(unix_ms as f64))
+		.to_locale_string("default", &object)
+		.into()
+}
+
+// there's no Intl natively, so desktop gets one fixed format in utc
+#[cfg(not(target_arch = "wasm32"))]
+fn locale_format(
+	unix_ms: i64,
+	style: char,
+) -> String {
+	let time = Timestamp::UNIX_EPOCH.saturating_add(Duration::milliseconds(unix_ms));
+	let clock = format!("{}:{:02}", time.hour(), time.minute());
+
+	match style {
+		| 't' => clock,
+		| 'T' => format!("{}:{:02}", clock, time.second()),
+		| 'd' => format!("{:02}/{:02}/{}", time.day(), time.month() as u8, time.year()),
+		| 'D' => format!("{} {} {}", time.day(), time.month(), time.year()),
+		| 'F' => format!("{}, {} {} {} {}", time.weekday(), time.day(), time.month(), time.year(), clock),
+		| _ => format!("{} {} {} {}", time.day(), time.month(), time.year(), clock),
+	}
+}
+
+/// "in 5 minutes", "2 days ago" etc. for an offset from now in ms
+pub fn relative(offset_ms: i64) -> String {
+	let seconds = offset_ms / 1000;
+	let distance = seconds.abs();
+
+	let (amount, unit) = match distance {
+		| d if d < MINUTE => (d, "second"),
+		| d if d < HOUR => (d / MINUTE, "minute"),
+		| d if d < DAY => (d / HOUR, "hour"),
+		| d if d < MONTH => (d / DAY, "day"),
+		| d if d < YEAR => (d / MONTH, "month"),
+		| d => (d / YEAR, "year"),
+	};
+
+	let unit = if amount == 1 {
+		unit.to_string()
+	} else {
+		format!("{}s", unit)
+	};
+
+	if seconds < 0 {
+		format!("{} {} ago", amount, unit)
+	} else {
+		format!("in {} {}", amount, unit)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn relative_times() {
+		assert_eq!(relative(-5_000), "5 seconds ago");
+		assert_eq!(relative(60_000), "in 1 minute");
+		assert_eq!(relative(-3 * 24 * 60 * 60 * 1000), "3 days ago");
+		assert_eq!(relative(2 * 365 * 24 * 60 * 60 * 1000), "in 2 years");
+	}
+
+	#[test]
+	fn native_styles() {
+		let ms = 1_744_309_293_146; // 2025-04-10T18:21:33.146Z
+		assert_eq!(format(ms, 't'), "18:21");
+		assert_eq!(format(ms, 'd'), "10/04/2025");
+		assert_eq!(format(ms, 'F'), "Thursday, 10 April 2025 18:21");
+	}
+
+	#[test]
+	fn out_of_range_timestamps() {
+		for ms in [i64::MAX, i64::MIN] {
+			assert!(format(ms, 'R').contains("years"));
+			assert!(!format(ms, 'F').is_empty());
+		}
+	}
+}
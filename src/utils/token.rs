@@ -0,0 +1,570 @@
+// where the tokens live between visits. everything reads the current one synchronously through `get`,
+// so they get loaded once before the router renders and are kept in memory after that
+
+use std::cell::RefCell;
+use std::error::Error;
+#[cfg(any(feature = "desktop", test))]
+use std::path::PathBuf;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::models::types::Snowflake;
+use crate::models::user::user::PartialUser;
+use crate::utils::platform;
+use crate::utils::storage::{self, Storage};
+
+const KEY: &str = "token";
+// what the keyring files the accounts under
+#[cfg(feature = "desktop")]
+const KEYRING_SERVICE: &str = "cowcord";
+const ENCRYPTED_KEY: &str = "encrypted_token";
+// set to "true" when the token should be encrypted at rest
+const ENCRYPT_SETTING: &str = "encrypt_token";
+
+thread_local! {
+	static SAVED: RefCell<Accounts> = RefCell::new(Accounts::default());
+}
+
+/// the same as what's saved, for showing in the account switcher
+pub static ACCOUNTS: GlobalSignal<Accounts> = Signal::global(Accounts::default);
+
+/// set when a request gets a 401 back, the token stopped working and they need to log in again
+pub static SESSION_EXPIRED: GlobalSignal<bool> = Signal::global(|| false);
+
+/// keeps one string around, the saved accounts
+pub trait TokenStore {
+	fn load(&self) -> impl Future<Output = Result<Option<String>, Box<dyn Error>>>;
+
+	fn save(
+		&self,
+		token: &str,
+	) -> impl Future<Output = Result<(), Box<dyn Error>>>;
+
+	fn clear(&self) -> impl Future<Output = Result<(), Box<dyn Error>>>;
+}
+
+/// plain localStorage, what the client always did
+#[cfg(not(feature = "desktop"))]
+pub struct LocalStorageStore;
+
+#[cfg(not(feature = "desktop"))]
+impl TokenStore for LocalStorageStore {
+	async fn load(&self) -> Result<Option<String>, Box<dyn Error>> { Ok(storage::local().get_raw(KEY)) }
+
+	async fn save(
+		&self,
+		token: &str,
+	) -> Result<(), Box<dyn Error>> {
+		storage::local().set_raw(KEY, token)
+	}
+
+	async fn clear(&self) -> Result<(), Box<dyn Error>> {
+		storage::local().remove(KEY)
+	}
+}
+
+// a non extractable aes-gcm key kept in indexeddb, so the ciphertext in localStorage is useless
+// without this origin's database. sends back the result or null when it can't be decrypted
+#[cfg(not(feature = "desktop"))]
+const WEBCRYPTO: &str = r#"
+	const [action, data] = await dioxus.recv();
+	const db = await new Promise((resolve, reject) => {
+		const open = indexedDB.open("cowcord-keys", 1);
+		open.onupgradeneeded = () => open.result.createObjectStore("keys");
+		open.onsuccess = () => resolve(open.result);
+		open.onerror = () => reject(open.error);
+	});
+	const keys = (mode, f) => new Promise((resolve, reject) => {
+		const request = f(db.transaction("keys", mode).objectStore("keys"));
+		request.onsuccess = () => resolve(request.result);
+		request.onerror = () => reject(request.error);
+	});
+	try {
+		let key = await keys("readonly", (s) => s.get("token"));
+		if (!key) {
+			key = await crypto.subtle.generateKey({ name: "AES-GCM", length: 256 }, false, ["encrypt", "decrypt"]);
+			await keys("readwrite", (s) => s.put(key, "token"));
+		}
+		if (action === "encrypt") {
+			const iv = crypto.getRandomValues(new Uint8Array(12));
+			const encrypted = new Uint8Array(await crypto.subtle.encrypt({ name: "AES-GCM", iv }, key, new TextEncoder().encode(data)));
+			dioxus.send(btoa(String.fromCharCode(...iv, ...encrypted)));
+		} else {
+			const bytes = Uint8Array.from(atob(data), (c) => c.charCodeAt(0));
+			const decrypted = await crypto.subtle.decrypt({ name: "AES-GCM", iv: bytes.slice(0, 12) }, key, bytes.slice(12));
+			dioxus.send(new TextDecoder().decode(decrypted));
+		}
+	} catch (e) {
+		console.error(e);
+		dioxus.send(null);
+	}
+"#;
+
+/// the token encrypted with a webcrypto key that never leaves indexeddb
+#[cfg(not(feature = "desktop"))]
+pub struct EncryptedStore;
+
+#[cfg(not(feature = "desktop"))]
+impl EncryptedStore {
+	async fn webcrypto(
+		action: &str,
+		data: &str,
+	) -> Result<Option<String>, Box<dyn Error>> {
+		let mut eval = document::eval(WEBCRYPTO);
+		eval.send((action, data)).map_err(|e| format!("{:?}", e))?;
+		Ok(eval.recv().await.map_err(|e| format!("{:?}", e))?)
+	}
+}
+
+#[cfg(not(feature = "desktop"))]
+impl TokenStore for EncryptedStore {
+	async fn load(&self) -> Result<Option<String>, Box<dyn Error>> {
+		match storage::local().get_raw(ENCRYPTED_KEY) {
+			| Some(encrypted) => Self::webcrypto("decrypt", &encrypted).await,
+			| None => Ok(None),
+		}
+	}
+
+	async fn save(
+		&self,
+		token: &str,
+	) -> Result<(), Box<dyn Error>> {
+		let encrypted = Self::webcrypto("encrypt", token).await?.ok_or("Couldn't encrypt the token")?;
+		storage::local().set_raw(ENCRYPTED_KEY, &encrypted)
+	}
+
+	async fn clear(&self) -> Result<(), Box<dyn Error>> {
+		storage::local().remove(ENCRYPTED_KEY)
+	}
+}
+
+/// a file only the current user can read, where desktop kept the accounts before the keyring. only read
+/// anymore to move them over
+#[cfg(any(feature = "desktop", test))]
+pub struct FileStore {
+	path: PathBuf,
+}
+
+#[cfg(any(feature = "desktop", test))]
+impl FileStore {
+	pub fn new(path: PathBuf) -> Self { FileStore { path } }
+
+	/// `token` in the platform's data dir
+	pub fn default_path() -> Option<PathBuf> { Some(storage::data_dir()?.join(KEY)) }
+}
+
+#[cfg(any(feature = "desktop", test))]
+impl TokenStore for FileStore {
+	async fn load(&self) -> Result<Option<String>, Box<dyn Error>> {
+		match std::fs::read_to_string(&self.path) {
+			| Ok(token) => Ok(Some(token.trim().to_string()).filter(|t| !t.is_empty())),
+			| Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+			| Err(e) => Err(e.into()),
+		}
+	}
+
+	async fn save(
+		&self,
+		token: &str,
+	) -> Result<(), Box<dyn Error>> {
+		use std::io::Write;
+
+		if let Some(dir) = self.path.parent() {
+			std::fs::create_dir_all(dir)?;
+		}
+		let mut options = std::fs::OpenOptions::new();
+		options.write(true).create(true).truncate(true);
+		#[cfg(unix)]
+		std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+		options.open(&self.path)?.write_all(token.as_bytes())?;
+		Ok(())
+	}
+
+	async fn clear(&self) -> Result<(), Box<dyn Error>> {
+		match std::fs::remove_file(&self.path) {
+			| Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+			| _ => Ok(()),
+		}
+	}
+}
+
+/// the os keyring, so the secret service on linux, the keychain on macos and the credential manager on windows
+#[cfg(feature = "desktop")]
+pub struct KeyringStore;
+
+#[cfg(feature = "desktop")]
+impl KeyringStore {
+	fn entry() -> Result<keyring::Entry, Box<dyn Error>> { Ok(keyring::Entry::new(KEYRING_SERVICE, KEY)?) }
+
+	/// moves whatever's left in the old plaintext file into the keyring, and deletes the file
+	async fn migrate(&self) -> Result<Option<String>, Box<dyn Error>> {
+		let Some(file) = FileStore::default_path().map(FileStore::new) else {
+			return Ok(None);
+		};
+		let Some(saved) = file.load().await? else {
+			return Ok(None);
+		};
+
+		self.save(&saved).await?;
+		file.clear().await?;
+		Ok(Some(saved))
+	}
+}
+
+#[cfg(feature = "desktop")]
+impl TokenStore for KeyringStore {
+	async fn load(&self) -> Result<Option<String>, Box<dyn Error>> {
+		match Self::entry()?.get_password() {
+			| Ok(saved) => Ok(Some(saved)),
+			| Err(keyring::Error::NoEntry) => self.migrate().await,
+			| Err(e) => Err(e.into()),
+		}
+	}
+
+	async fn save(
+		&self,
+		token: &str,
+	) -> Result<(), Box<dyn Error>> {
+		Ok(Self::entry()?.set_password(token)?)
+	}
+
+	async fn clear(&self) -> Result<(), Box<dyn Error>> {
+		match Self::entry()?.delete_credential() {
+			| Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+			| Err(e) => Err(e.into()),
+		}
+	}
+}
+
+/// whichever store this platform and the encryption setting call for
+enum Backend {
+	#[cfg(not(feature = "desktop"))]
+	Plain(LocalStorageStore),
+	#[cfg(not(feature = "desktop"))]
+	Encrypted(EncryptedStore),
+	#[cfg(feature = "desktop")]
+	Keyring(KeyringStore),
+}
+
+fn backend() -> Backend { backend_for(encrypted()) }
+
+// never falls back to a plain file, nothing gets saved if there's no keyring
+#[cfg(feature = "desktop")]
+fn backend_for(_encrypt: bool) -> Backend { Backend::Keyring(KeyringStore) }
+
+#[cfg(not(feature = "desktop"))]
+fn backend_for(encrypt: bool) -> Backend {
+	if encrypt {
+		Backend::Encrypted(EncryptedStore)
+	} else {
+		Backend::Plain(LocalStorageStore)
+	}
+}
+
+impl TokenStore for Backend {
+	async fn load(&self) -> Result<Option<String>, Box<dyn Error>> {
+		match self {
+			#[cfg(not(feature = "desktop"))]
+			| Backend::Plain(store) => store.load().await,
+			#[cfg(not(feature = "desktop"))]
+			| Backend::Encrypted(store) => store.load().await,
+			#[cfg(feature = "desktop")]
+			| Backend::Keyring(store) => store.load().await,
+		}
+	}
+
+	async fn save(
+		&self,
+		token: &str,
+	) -> Result<(), Box<dyn Error>> {
+		match self {
+			#[cfg(not(feature = "desktop"))]
+			| Backend::Plain(store) => store.save(token).await,
+			#[cfg(not(feature = "desktop"))]
+			| Backend::Encrypted(store) => store.save(token).await,
+			#[cfg(feature = "desktop")]
+			| Backend::Keyring(store) => store.save(token).await,
+		}
+	}
+
+	async fn clear(&self) -> Result<(), Box<dyn Error>> {
+		match self {
+			#[cfg(not(feature = "desktop"))]
+			| Backend::Plain(store) => store.clear().await,
+			#[cfg(not(feature = "desktop"))]
+			| Backend::Encrypted(store) => store.clear().await,
+			#[cfg(feature = "desktop")]
+			| Backend::Keyring(store) => store.clear().await,
+		}
+	}
+}
+
+/// one logged in account, the user gets cached from its last READY so the switcher can show it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Account {
+	pub id:    Snowflake,
+	pub token: String,
+	pub user:  Option<PartialUser>,
+}
+
+/// every saved account and which one is in use, what the stores actually keep (as json)
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Accounts {
+	pub current:  Option<Snowflake>,
+	pub accounts: Vec<Account>,
+}
+
+impl Accounts {
+	/// the saved json, or a bare token from before there could be more than one account
+	fn parse(saved: &str) -> Self {
+		serde_json::from_str(saved).unwrap_or_else(|_| {
+			let mut accounts = Accounts::default();
+			accounts.add(saved.to_string());
+			accounts
+		})
+	}
+
+	pub fn current(&self) -> Option<&Account> { self.accounts.iter().find(|a| Some(a.id) == self.current) }
+
+	/// switches to the account behind `token`, replacing its old token if it was already saved
+	pub fn add(
+		&mut self,
+		token: String,
+	) {
+		let id = user_id(&token).unwrap_or(Snowflake::new(0));
+
+		match self.accounts.iter_mut().find(|a| a.id == id) {
+			| Some(account) => account.token = token,
+			| None => self.accounts.push(Account { id, token, user: None }),
+		}
+		self.current = Some(id);
+	}
+
+	pub fn switch(
+		&mut self,
+		id: Snowflake,
+	) -> bool {
+		let found = self.accounts.iter().any(|a| a.id == id);
+		if found {
+			self.current = Some(id);
+		}
+		found
+	}
+
+	/// forgets the current account, nobody is logged in afterwards
+	pub fn remove_current(&mut self) {
+		if let Some(id) = self.current.take() {
+			self.accounts.retain(|a| a.id != id);
+		}
+	}
+
+	/// caches who the current account is, which also fixes its id when the token didn't have one
+	pub fn remember(
+		&mut self,
+		user: PartialUser,
+	) {
+		let Some(current) = self.current else {
+			return;
+		};
+		if current != user.id {
+			self.accounts.retain(|a| a.id != user.id);
+		}
+
+		if let Some(account) = self.accounts.iter_mut().find(|a| a.id == current) {
+			account.id = user.id;
+			account.user = Some(user);
+			self.current = Some(account.id);
+		}
+	}
+}
+
+/// user tokens start with the user id in base64
+pub fn user_id(token: &str) -> Option<Snowflake> {
+	let encoded = token.split('.').next()?.trim_end_matches('=');
+	let decoded = STANDARD_NO_PAD.decode(encoded).ok()?;
+	String::from_utf8(decoded).ok()?.parse().ok()
+}
+
+pub fn current_id() -> Option<Snowflake> { SAVED.with_borrow(|saved| saved.current) }
+
+/// the token of whoever is logged in
+pub fn get() -> Option<String> { SAVED.with_borrow(|saved| saved.current().map(|a| a.token.clone())) }
+
+/// reads the saved accounts into memory, has to happen before anything asks for the token
+pub async fn load() {
+	match backend().load().await {
+		| Ok(saved) => {
+			let accounts = saved.as_deref().map(Accounts::parse).unwrap_or_default();
+			*ACCOUNTS.write() = accounts.clone();
+			SAVED.set(accounts);
+		},
+		| Err(e) => platform::error(&format!("Failed to load accounts: {}", e)),
+	}
+}
+
+/// changes the saved accounts and writes them back
+async fn update(change: impl FnOnce(&mut Accounts)) {
+	let accounts = SAVED.with_borrow_mut(|saved| {
+		change(saved);
+		saved.clone()
+	});
+	*ACCOUNTS.write() = accounts.clone();
+
+	if let Err(e) = write(&backend(), &accounts).await {
+		platform::error(&format!("Failed to save accounts: {}", e));
+	}
+}
+
+async fn write(
+	store: &Backend,
+	accounts: &Accounts,
+) -> Result<(), Box<dyn Error>> {
+	if accounts.accounts.is_empty() {
+		store.clear().await
+	} else {
+		store.save(&serde_json::to_string(accounts)?).await
+	}
+}
+
+/// logs in as `token` without logging out of anything else
+pub async fn add(token: String) {
+	update(|accounts| accounts.add(token)).await;
+	*SESSION_EXPIRED.write() = false;
+}
+
+pub async fn switch(id: Snowflake) {
+	update(|accounts| {
+		accounts.switch(id);
+	})
+	.await;
+	*SESSION_EXPIRED.write() = false;
+}
+
+/// logs out of the current account, the others stay saved
+pub async fn clear() {
+	update(Accounts::remove_current).await;
+	*SESSION_EXPIRED.write() = false;
+}
+
+pub async fn remember(user: PartialUser) {
+	if SAVED.with_borrow(|saved| saved.current().and_then(|a| a.user.as_ref()) == Some(&user)) {
+		return;
+	}
+	update(|accounts| accounts.remember(user)).await;
+}
+
+pub fn encrypted() -> bool { storage::local().get_raw(ENCRYPT_SETTING).as_deref() == Some("true") }
+
+/// moves the saved accounts over to the other store. the old one is only cleared once the new one has
+/// them, if anything fails the setting stays as it was
+pub async fn set_encrypted(encrypt: bool) {
+	if encrypt == encrypted() {
+		return;
+	}
+
+	let accounts = SAVED.with_borrow(Accounts::clone);
+	if let Err(e) = write(&backend_for(encrypt), &accounts).await {
+		platform::error(&format!("Failed to move accounts: {}", e));
+		return;
+	}
+	#[cfg(not(feature = "desktop"))]
+	let old = backend();
+	if let Err(e) = storage::local().set_raw(ENCRYPT_SETTING, if encrypt { "true" } else { "false" }) {
+		platform::error(&format!("Failed to save encryption setting: {}", e));
+		return;
+	}
+	// the keyring is the same store either way
+	#[cfg(not(feature = "desktop"))]
+	if let Err(e) = old.clear().await {
+		platform::error(&format!("Failed to clear the old accounts: {}", e));
+	}
+}
+
+/// the api stopped taking the token
+pub fn expired() { *SESSION_EXPIRED.write() = true; }
+
+#[cfg(test)]
+mod tests {
+	use futures_executor::block_on;
+
+	use super::*;
+	use crate::testing::in_runtime;
+
+	const TOKEN: &str = "ODUyODkyMjk3NjYxOTA2OTkz.GMoo0b.cow";
+
+	fn user(id: u64) -> PartialUser {
+		serde_json::from_value(serde_json::json!({ "id": id.to_string(), "username": "cow" })).unwrap()
+	}
+
+	#[test]
+	fn tokens_carry_the_user_id() {
+		assert_eq!(user_id(TOKEN), Some(Snowflake::new(852892297661906993)));
+		assert_eq!(user_id("the.token"), None);
+	}
+
+	#[test]
+	fn switching_between_accounts() {
+		let mut accounts = Accounts::parse(TOKEN);
+		assert_eq!(accounts.current().map(|a| a.id), Some(Snowflake::new(852892297661906993)));
+
+		// no id in the token until READY says who it is
+		accounts.add("the.token".to_string());
+		assert_eq!(accounts.current, Some(Snowflake::new(0)));
+		accounts.remember(user(1));
+		assert_eq!(accounts.current, Some(Snowflake::new(1)));
+		assert_eq!(accounts.accounts.len(), 2);
+
+		// logging in again replaces the token instead of adding another account
+		accounts.add("ODUyODkyMjk3NjYxOTA2OTkz.new".to_string());
+		assert_eq!(accounts.accounts.len(), 2);
+		assert_eq!(accounts.current().unwrap().token, "ODUyODkyMjk3NjYxOTA2OTkz.new");
+
+		assert!(accounts.switch(Snowflake::new(1)));
+		assert!(!accounts.switch(Snowflake::new(2)));
+		accounts.remove_current();
+		assert_eq!(accounts.current, None);
+		assert_eq!(accounts.accounts.len(), 1);
+
+		let json = serde_json::to_string(&accounts).unwrap();
+		assert_eq!(Accounts::parse(&json), accounts);
+	}
+
+	#[cfg(not(feature = "desktop"))]
+	#[tokio::test]
+	async fn failed_moves_keep_the_accounts() {
+		in_runtime(async {
+			add(TOKEN.to_string()).await;
+			// there's no webview to encrypt with
+			set_encrypted(true).await;
+			assert!(!encrypted());
+			assert_eq!(LocalStorageStore.load().await.unwrap().map(|json| Accounts::parse(&json)), Some(Accounts::parse(TOKEN)));
+		})
+		.await;
+	}
+
+	#[test]
+	fn file_store_round_trip() {
+		let path = std::env::temp_dir().join(format!("cowcord-token-{}", std::process::id()));
+		let store = FileStore::new(path.clone());
+
+		block_on(async {
+			assert_eq!(store.load().await.unwrap(), None);
+			store.save("the.token").await.unwrap();
+			assert_eq!(store.load().await.unwrap().as_deref(), Some("the.token"));
+
+			#[cfg(unix)]
+			{
+				use std::os::unix::fs::PermissionsExt;
+				assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+			}
+
+			store.clear().await.unwrap();
+			assert_eq!(store.load().await.unwrap(), None);
+			store.clear().await.unwrap();
+		});
+	}
+}
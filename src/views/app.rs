@@ -1,12 +1,12 @@
 use dioxus::prelude::*;
 
-use crate::utils::local_storage::get_value;
+use crate::utils::token;
 
 #[component]
 pub fn FateDecider() -> Element {
 	let navigator = use_navigator();
 
-	let redirect = match get_value("token") {
+	let redirect = match token::get() {
 		| Some(_token) => "/channels/@me",
 		| None => "/login",
 	};
@@ -1,13 +1,21 @@
+use std::collections::HashMap;
 use std::error::Error;
 
 use dioxus::prelude::*;
-use web_sys::console;
+use qrcodegen::{QrCode, QrCodeEcc};
 
-use crate::components::modals::mfa_code::Modal;
+use crate::Route;
+use crate::components::modals::mfa_code::MfaModal;
 use crate::models::auth::login::{LoginRequest, LoginResponse, MfaRequest};
-use crate::utils::local_storage::save_value;
-use crate::utils::request::RequestClient;
-use crate::views::auth::{SmsMfaRequest, send_sms_mfa};
+use crate::models::error::ErrorResponse;
+use crate::models::mfa::{MfaMethod, MfaMethods};
+use crate::utils::cdn;
+use crate::utils::config::{self, Config, Instance};
+use crate::utils::platform;
+use crate::utils::remote_auth::{RemoteAuthStep, use_remote_auth};
+use crate::utils::request::{ApiError, RequestClient};
+use crate::utils::token;
+use crate::views::auth::send_sms_mfa;
 
 async fn login(info: LoginRequest) -> Result<LoginResponse, Box<dyn Error>> {
 	let client = RequestClient::new();
@@ -15,105 +23,402 @@ async fn login(info: LoginRequest) -> Result<LoginResponse, Box<dyn Error>> {
 	let response: LoginResponse = client.post("/auth/login", &info).await?;
 
 	if let Some(token) = &response.token {
-		save_value("token", token);
+		token::add(token.clone()).await;
 	}
 	Ok(response)
 }
 
-async fn mfa_login(info: MfaRequest) -> Result<LoginResponse, Box<dyn Error>> {
+async fn mfa_login(
+	method: MfaMethods,
+	info: MfaRequest,
+) -> Result<LoginResponse, Box<dyn Error>> {
 	let client = RequestClient::new();
 
 	let response: LoginResponse = client
-		.post(&format!("/auth/mfa/{}", info.code.to_lowercase()), &info)
+		.post(&format!("/auth/mfa/{}", method.as_str()), &info)
 		.await?;
 
 	if let Some(token) = &response.token {
-		save_value("token", token);
+		token::add(token.clone()).await;
 	}
 	Ok(response)
 }
 
+/// the api's message for each invalid field, or its general message when no field is to blame
+fn login_errors(error: &(dyn Error + 'static)) -> (HashMap<String, String>, Option<String>) {
+	let response = error
+		.downcast_ref::<ApiError>()
+		.and_then(|e| serde_json::from_str::<ErrorResponse>(&e.body).ok());
+
+	match response {
+		| Some(response) => {
+			let fields = response.field_errors();
+			let message = fields.is_empty().then_some(response.message);
+			(fields, message)
+		},
+		| None => (HashMap::new(), Some("Something went wrong, try again later.".to_string())),
+	}
+}
+
 #[component]
 pub fn Login() -> Element {
-	let mut show_modal = use_signal(|| false);
-	let mut ticket = use_signal(|| None);
+	let mut identifier = use_signal(String::new);
+	let mut password = use_signal(String::new);
+	let mut errors = use_signal(HashMap::<String, String>::new);
+	let mut message = use_signal(|| None::<String>);
+	let mut submitting = use_signal(|| false);
+	// the ticket and methods while mfa is pending
+	let mut mfa = use_signal(|| None::<(String, Vec<MfaMethod>)>);
+	let mut mfa_error = use_signal(|| None::<String>);
+	// bumped to remount the qr login with a fresh connection
+	let mut qr_attempt = use_signal(|| 0);
+	let mut encrypt = use_signal(token::encrypted);
+	let instance = use_signal(config::get);
+	let navigator = navigator();
 
-	rsx! {
-		form {
-			onsubmit: move |event: Event<FormData>| {
-				spawn(async move {
-					let identifier = event.values().get("identifier")
-						.and_then(|val| val.get(0).cloned())
-						.unwrap_or_default();
-
-					let password = event.values().get("password")
-						.and_then(|val| val.get(0).cloned())
-						.unwrap_or_default();
-
-					let request = LoginRequest {
-						login: identifier,
-						password,
-						undelete: None,
-						login_source: None,
-					};
+	let onsubmit = move |event: Event<FormData>| {
+		event.prevent_default();
+		let request = LoginRequest {
+			login:        identifier().trim().to_string(),
+			password:     password(),
+			undelete:     None,
+			login_source: None,
+		};
 
-					match login(request).await {
-						| Ok(login_response) => {
-							if login_response.mfa.unwrap_or(false) {
-								if let Some(ticket_value) = login_response.ticket {
-									let sms_request = SmsMfaRequest {
-										token: ticket_value.clone(),
-									};
-
-									match send_sms_mfa(sms_request).await {
-										  Ok(_) => {
-											 ticket.set(Some(ticket_value));
-											 show_modal.set(true);
-										  }
-										  Err(e) => console::error_1(&format!("Error sending sms MFA code: {}", e).into()),
-									}
-								} else {
-									console::error_1(&"MFA required, but no ticket received!".into());
-								}
-							}
+		submitting.set(true);
+		spawn(async move {
+			match login(request).await {
+				| Ok(response) if response.token.is_some() => {
+					navigator.replace(Route::FateDecider {});
+				},
+				| Ok(response) => match response.ticket.clone().filter(|_| response.mfa.unwrap_or(false)) {
+					| Some(ticket) => {
+						mfa_error.set(None);
+						mfa.set(Some((ticket, response.mfa_methods())));
+					},
+					| None => platform::error("MFA required, but no ticket received!"),
+				},
+				| Err(e) => {
+					platform::error(&format!("Login failed: {}", e));
+					let (fields, general) = login_errors(e.as_ref());
+					errors.set(fields);
+					message.set(general);
+				},
+			}
+
+			submitting.set(false);
+		});
+	};
+
+	let error = move |field: &str| errors.read().get(field).cloned();
+	// set when this is adding another account on top of the one in use
+	let current_account = token::ACCOUNTS
+		.read()
+		.current()
+		.map(|a| a.user.as_ref().map_or("your account".to_string(), |u| u.display_name().to_string()));
+
+	rsx! {
+		div {
+			class: "login-page",
+			form {
+				class: "auth-form login",
+				onsubmit,
+				h1 { "Welcome back!" }
+				if let Some(message) = message() {
+					p { class: "error", "{message}" }
+				}
+				label {
+					"Email or Phone Number"
+					if let Some(error) = error("login") {
+						span { class: "field-error", " - {error}" }
+					}
+					input {
+						name: "identifier",
+						value: "{identifier}",
+						oninput: move |event| identifier.set(event.value()),
+					}
+				}
+				label {
+					"Password"
+					if let Some(error) = error("password") {
+						span { class: "field-error", " - {error}" }
+					}
+					input {
+						name: "password",
+						r#type: "password",
+						value: "{password}",
+						oninput: move |event| password.set(event.value()),
+					}
+				}
+				label {
+					class: "checkbox",
+					input {
+						r#type: "checkbox",
+						checked: encrypt(),
+						onchange: move |event| {
+							encrypt.set(event.checked());
+							spawn(token::set_encrypted(event.checked()));
 						},
-						| Err(e) => console::error_1(&format!("Login failed: {}", e).into()),
 					}
-				});
-			},
-			input { name: "identifier", placeholder: "Email or Phone number" }
-			input { name: "password", placeholder: "Password" }
-			input { r#type: "submit", value: "Login" }
+					"Encrypt my login on this device"
+				}
+				InstancePicker { config: instance }
+				input { r#type: "submit", value: "Log In", disabled: submitting() }
+				Link { to: Route::Register {}, "Need an account? Register" }
+				if let Some(name) = current_account {
+					Link { to: Route::FateDecider {}, "Back to {name}" }
+				}
+			}
+			// remote auth only exists on discord
+			if !instance().instance.custom() {
+				if instance().remote_auth_reachable() {
+					QrLogin { key: "{qr_attempt}", on_retry: move |_| qr_attempt += 1 }
+				} else {
+					div {
+						class: "qr-login",
+						h2 { "Log in with QR Code" }
+						p { "Discord only lets its own site use QR code login, so it needs a proxy in the browser. Use the desktop app, or log in with your email and password." }
+					}
+				}
+			}
 		}
 
-		if show_modal() {
-			Modal {
-				on_submit: move |code: String| {
-					show_modal.set(false);
-
-					if let Some(ticket_value) = ticket() {
-						let mfa_request = MfaRequest {
-							ticket: ticket_value,
-							code,
-							login_source: None,
-						};
-
+		if let Some((ticket, methods)) = mfa() {
+			MfaModal {
+				methods,
+				error: mfa_error(),
+				on_send_sms: {
+					let ticket = ticket.clone();
+					move |_| {
+						let ticket = ticket.clone();
 						spawn(async move {
-							match mfa_login(mfa_request).await {
-								Ok(_login_response) => {
-									ticket.set(None);
-									show_modal.set(false);
-								}
-								Err(e) => {
-									console::error_1(&format!("MFA Login failed: {}", e).into());
-								}
+							if let Err(e) = send_sms_mfa(ticket).await {
+								platform::error(&format!("Error sending sms MFA code: {}", e));
+								mfa_error.set(Some("Couldn't send the code, try another method.".to_string()));
 							}
 						});
-					} else {
-						console::error_1(&"No ticket found for MFA submission!".into());
 					}
+				},
+				on_submit: move |(method, code): (MfaMethods, String)| {
+					let request = MfaRequest {
+						ticket: ticket.clone(),
+						code,
+						login_source: None,
+					};
+
+					spawn(async move {
+						match mfa_login(method, request).await {
+							| Ok(_) => {
+								mfa.set(None);
+								navigator.replace(Route::FateDecider {});
+							},
+							| Err(e) => {
+								platform::error(&format!("MFA Login failed: {}", e));
+								// the wrong code comes back as a field error on `code`
+								let (fields, general) = login_errors(e.as_ref());
+								mfa_error.set(general.or_else(|| fields.into_values().next()));
+							},
+						}
+					});
+				},
+				on_cancel: move |_| mfa.set(None),
+			}
+		}
+	}
+}
+
+/// which backend to log in to, the urls can be changed for the self-hosted ones
+#[component]
+fn InstancePicker(config: Signal<Config>) -> Element {
+	let mut update = move |updated: Config| {
+		config::set(updated.clone());
+		config.set(updated);
+	};
+	let current = config();
+
+	rsx! {
+		label {
+			"Instance"
+			select {
+				name: "instance",
+				onchange: move |event| {
+					if let Some(instance) = Instance::ALL.into_iter().find(|i| i.name() == event.value()) {
+						update(instance.config());
+					}
+				},
+				for instance in Instance::ALL {
+					option {
+						value: instance.name(),
+						selected: instance == current.instance,
+						"{instance.name()}"
+					}
+				}
+			}
+		}
+		if current.instance.custom() {
+			label {
+				"API URL"
+				input {
+					name: "api_base",
+					value: "{current.api_base}",
+					onchange: move |event| update(Config {
+						api_base: event.value().trim().trim_end_matches('/').to_string(),
+						// a different server has its own gateway
+						gateway_url: None,
+						..config()
+					}),
 				}
 			}
+			label {
+				"CDN URL"
+				input {
+					name: "cdn_base",
+					value: "{current.cdn_base}",
+					onchange: move |event| update(Config {
+						cdn_base: event.value().trim().trim_end_matches('/').to_string(),
+						..config()
+					}),
+				}
+			}
+		}
+	}
+}
+
+/// a unit square per dark module, shifted in by `border` for the quiet zone
+fn svg_path(
+	code: &QrCode,
+	border: i32,
+) -> String {
+	let mut path = String::new();
+	for y in 0..code.size() {
+		for x in (0..code.size()).filter(|&x| code.get_module(x, y)) {
+			path.push_str(&format!("M{},{}h1v1h-1z", x + border, y + border));
+		}
+	}
+
+	path
+}
+
+/// the qr code side of the login page, the connection lives as long as this is mounted
+#[component]
+fn QrLogin(on_retry: EventHandler<()>) -> Element {
+	let navigator = navigator();
+	let step = use_remote_auth(EventHandler::new(move |token: String| {
+		spawn(async move {
+			token::add(token).await;
+			navigator.replace(Route::FateDecider {});
+		});
+	}));
+
+	let content = match step() {
+		| RemoteAuthStep::Connecting | RemoteAuthStep::LoggingIn => rsx! {
+			div { class: "qr-placeholder", "Loading..." }
+		},
+		| RemoteAuthStep::Scan(url) => match QrCode::encode_text(&url, QrCodeEcc::Medium) {
+			| Ok(code) => {
+				// 4 modules of quiet zone on each side
+				let size = code.size() + 8;
+				rsx! {
+					svg {
+						class: "qr-code",
+						view_box: "0 0 {size} {size}",
+						rect { width: "100%", height: "100%", fill: "white" }
+						path { d: svg_path(&code, 4), fill: "black" }
+					}
+				}
+			},
+			| Err(_) => rsx! {
+				p { class: "error", "Couldn't make a QR code." }
+			},
+		},
+		| RemoteAuthStep::Confirm(user) => rsx! {
+			img { class: "avatar", src: cdn::avatar(&user.clone().into(), 128) }
+			h2 { "Check your phone!" }
+			p { "Logging in as {user.username}" }
+		},
+		| RemoteAuthStep::Cancelled => rsx! {
+			p { "Login cancelled." }
+			button { onclick: move |_| on_retry.call(()), "Try Again" }
+		},
+		| RemoteAuthStep::Failed(_) => rsx! {
+			p { class: "error", "This QR code expired or couldn't connect." }
+			button { onclick: move |_| on_retry.call(()), "Try Again" }
+		},
+	};
+
+	rsx! {
+		div {
+			class: "qr-login",
+			{content}
+			h2 { "Log in with QR Code" }
+			p { "Scan this with the Discord mobile app to log in instantly." }
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::testing::in_runtime;
+	use crate::testing::rest::{self, MockRest};
+	use crate::utils::config::{self, Config};
+
+	fn api_error(body: &str) -> Box<dyn Error> {
+		Box::new(ApiError {
+			status: 400,
+			body:   body.to_string(),
+		})
+	}
+
+	#[test]
+	fn invalid_logins_map_to_fields() {
+		let error = api_error(
+			r#"{"code": 50035, "message": "Invalid Form Body", "errors": {"login": {"_errors": [{"code": "INVALID_LOGIN", "message": "Login or password is invalid."}]}}}"#,
+		);
+		let (fields, message) = login_errors(error.as_ref());
+		assert_eq!(fields["login"], "Login or password is invalid.");
+		assert_eq!(message, None);
+
+		let (fields, message) = login_errors(api_error(r#"{"code": 60008, "message": "Invalid two-factor code"}"#).as_ref());
+		assert!(fields.is_empty());
+		assert_eq!(message.as_deref(), Some("Invalid two-factor code"));
+	}
+
+	#[tokio::test]
+	async fn mfa_logins_save_the_token() {
+		let rest = MockRest::start(rest::discord()).await;
+		config::set(Config {
+			api_base: rest.url.clone(),
+			..Config::default()
+		});
+
+		in_runtime(async {
+			let request = LoginRequest {
+				login:        "cow@example.com".to_string(),
+				password:     "hunter2".to_string(),
+				undelete:     None,
+				login_source: None,
+			};
+			let response = login(request).await.unwrap();
+			assert_eq!(response.token, None);
+			assert!(response.mfa_methods().iter().any(|m| m.r#type == MfaMethods::totp));
+			assert_eq!(token::get(), None);
+
+			let request = MfaRequest {
+				ticket:       response.ticket.unwrap(),
+				code:         "123456".to_string(),
+				login_source: None,
+			};
+			let response = mfa_login(MfaMethods::totp, request).await.unwrap();
+			assert!(response.token.is_some());
+			assert_eq!(token::get(), response.token);
+		})
+		.await;
+
+		let requests = rest.requests();
+		assert_eq!(requests[0].header("origin"), Some(rest.url.as_str()));
+		assert_eq!(requests[1].path, "/api/v9/auth/mfa/totp");
+		assert_eq!(requests[1].json()["code"], "123456");
+		assert_eq!(requests[1].json()["ticket"], "WzAsIjY5NjA1MzQyNjM3Mzg2OTY0OSIsImxvZ2luIl0.Z_ghAA.mock");
+	}
+}
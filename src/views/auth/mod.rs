@@ -1,15 +1,22 @@
 mod login;
 use std::error::Error;
 
+use dioxus::prelude::*;
 pub use login::Login;
 mod register;
 pub use register::Register;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::components::users::{PROFILE_MODAL, PROFILE_POPUP};
+use crate::models::types::Snowflake;
+use crate::store::{self, cache};
 use crate::utils::request::RequestClient;
+use crate::utils::{gateway, platform, sudo, token};
 
 #[derive(Serialize)]
 pub struct SmsMfaRequest {
-	token: String,
+	ticket: String,
 }
 
 #[derive(Deserialize)]
@@ -17,14 +24,61 @@ struct SmsMfaResponse {
 	phone: String, // phone number in response is redacted, eg. "+*******6080"
 }
 
-pub async fn send_sms_mfa(
-    info: SmsMfaRequest
-) -> Result<String, Box<dyn Error>> {
-    let client = RequestClient::new();
+/// texts a login mfa code to the account's phone, returns the redacted number it went to
+pub async fn send_sms_mfa(ticket: String) -> Result<String, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	let mfa_response: SmsMfaResponse = client
+		.post("/auth/mfa/sms/send", &SmsMfaRequest { ticket })
+		.await?;
+
+	Ok(mfa_response.phone)
+}
+
+async fn logout() -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.post("/auth/logout", &json!({ "provider": null, "voip_provider": null }))
+		.await
+}
+
+// everything that belongs to the account that's in use
+fn tear_down() {
+	gateway::disconnect();
+	store::clear();
+	sudo::clear_token();
+	*PROFILE_POPUP.write() = None;
+	*PROFILE_MODAL.write() = None;
+}
+
+/// drops the connection, the stores, the offline cache and the token, whether or not discord heard about it
+pub async fn end_session() {
+	tear_down();
+	// the cache is scoped by the account that's still current
+	cache::clear().await;
+	token::clear().await;
+}
+
+/// invalidates the token on discord's side before forgetting it here, then moves on to the next
+/// saved account if there is one
+pub async fn log_out() {
+	if let Err(e) = logout().await {
+		platform::error(&format!("Failed to log out: {}", e));
+	}
+	end_session().await;
+
+	let next = token::ACCOUNTS.peek().accounts.first().map(|a| a.id);
+	if let Some(id) = next {
+		token::switch(id).await;
+	}
+}
 
-    let mfa_response: SmsMfaResponse = client
-        .post("/mfa/sms/send", &info)
-        .await?;
+/// starts the session over from READY, the gateway connects again once the app remounts
+pub fn reconnect() { tear_down(); }
 
-    Ok(mfa_response.phone)
+/// the gateway reconnects as the other account once the app remounts
+pub async fn switch_account(id: Snowflake) {
+	tear_down();
+	token::switch(id).await;
 }
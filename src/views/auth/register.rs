@@ -1,8 +1,402 @@
+use std::collections::HashMap;
+use std::error::Error;
+
 use dioxus::prelude::*;
+use serde_json::json;
+
+use crate::Route;
+use crate::components::modals::captcha::CaptchaModal;
+use crate::models::auth::register::{
+	Experiments,
+	RegisterRequest,
+	RegisterResponse,
+	UsernameAttemptRequest,
+	UsernameAttemptResponse,
+};
+use crate::models::error::{CaptchaRequired, ErrorResponse};
+use crate::utils::platform;
+use crate::utils::request::{ApiError, RequestClient};
+use crate::utils::token;
+
+// don't hit the username check on every keystroke
+const USERNAME_CHECK_DELAY_MS: u32 = 500;
+
+async fn get_fingerprint() -> Result<Option<String>, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	let experiments: Experiments = client.get("/experiments").await?;
+	Ok(experiments.fingerprint)
+}
+
+async fn username_taken(username: String) -> Result<bool, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	let response: UsernameAttemptResponse = client
+		.post("/unique-username/username-attempt-unauthed", &UsernameAttemptRequest { username })
+		.await?;
+	Ok(response.taken)
+}
+
+/// `captcha` is the solved key and the rqtoken from the challenge, for retrying after a captcha
+async fn register(
+	request: RegisterRequest,
+	captcha: Option<(String, Option<String>)>,
+) -> Result<RegisterResponse, Box<dyn Error>> {
+	let mut client = RequestClient::new();
+	if let Some(fingerprint) = &request.fingerprint {
+		client = client.with_header("X-Fingerprint", fingerprint.clone());
+	}
+	if let Some((key, rqtoken)) = captcha {
+		client = client.with_header("X-Captcha-Key", key);
+		if let Some(rqtoken) = rqtoken {
+			client = client.with_header("X-Captcha-Rqtoken", rqtoken);
+		}
+	}
+
+	let response: RegisterResponse = client.post("/auth/register", &request).await?;
+
+	token::add(response.token.clone()).await;
+	Ok(response)
+}
+
+async fn resend_verification() -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client.post("/auth/verify/resend", &json!({})).await
+}
+
+/// lowercase letters, numbers, underscores and periods, without two periods in a row
+fn username_error(username: &str) -> Option<&'static str> {
+	if !(2..=32).contains(&username.chars().count()) {
+		return Some("Must be between 2 and 32 in length.");
+	}
+	if !username.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.') {
+		return Some("Please only use numbers, lowercase letters, underscores _ or periods.");
+	}
+	if username.contains("..") {
+		return Some("Username cannot contain consecutive periods.");
+	}
+
+	None
+}
+
+/// checks what can be checked before sending anything, keyed by the same field names the api uses
+fn validate(request: &RegisterRequest) -> HashMap<String, String> {
+	let mut errors = HashMap::new();
+	let mut error = |field: &str, message: &str| {
+		errors.insert(field.to_string(), message.to_string());
+	};
+
+	if !request.email.contains('@') {
+		error("email", "Not a well formed email address.");
+	}
+	if let Some(message) = username_error(&request.username) {
+		error("username", message);
+	}
+	if request.password.len() < 8 {
+		error("password", "Must be at least 8 characters long.");
+	}
+	if request.date_of_birth.is_empty() {
+		error("date_of_birth", "Required");
+	}
+	if !request.consent {
+		error("consent", "You need to agree to the Terms of Service and Privacy Policy.");
+	}
+
+	errors
+}
+
+#[component]
+fn FieldError(message: Option<String>) -> Element {
+	rsx! {
+		if let Some(message) = message {
+			span { class: "field-error", " - {message}" }
+		}
+	}
+}
 
 #[component]
 pub fn Register() -> Element {
+	let mut email = use_signal(String::new);
+	let mut global_name = use_signal(String::new);
+	let mut username = use_signal(String::new);
+	let mut password = use_signal(String::new);
+	let mut date_of_birth = use_signal(String::new);
+	let mut consent = use_signal(|| false);
+	let mut promotional = use_signal(|| false);
+
+	let mut errors = use_signal(HashMap::<String, String>::new);
+	let mut message = use_signal(|| None::<String>);
+	let mut available = use_signal(|| None::<bool>);
+	let mut check_task = use_hook(|| CopyValue::new(None::<Task>));
+	let mut submitting = use_signal(|| false);
+	let mut captcha = use_signal(|| None::<CaptchaRequired>);
+	// only fetched once, retries after a captcha reuse it
+	let mut fingerprint = use_signal(|| None::<String>);
+	let mut verify_email = use_signal(|| None::<String>);
+
+	let mut check_username = move |name: String| {
+		if let Some(task) = check_task.write().take() {
+			task.cancel();
+		}
+		available.set(None);
+		if username_error(&name).is_some() {
+			return;
+		}
+
+		check_task.set(Some(spawn(async move {
+			platform::sleep(USERNAME_CHECK_DELAY_MS).await;
+
+			match username_taken(name).await {
+				| Ok(taken) => available.set(Some(!taken)),
+				| Err(e) => platform::error(&format!("Failed to check username: {}", e)),
+			}
+		})));
+	};
+
+	let mut submit = move |solved: Option<(String, Option<String>)>| {
+		let mut request = RegisterRequest {
+			fingerprint:              None,
+			email:                    email().trim().to_string(),
+			username:                 username().trim().to_string(),
+			global_name:              Some(global_name().trim().to_string()).filter(|n| !n.is_empty()),
+			password:                 password(),
+			date_of_birth:            date_of_birth(),
+			consent:                  consent(),
+			promotional_email_opt_in: promotional(),
+		};
+
+		let invalid = validate(&request);
+		message.set(None);
+		if !invalid.is_empty() {
+			errors.set(invalid);
+			return;
+		}
+		errors.set(HashMap::new());
+
+		submitting.set(true);
+		spawn(async move {
+			if fingerprint.peek().is_none() {
+				match get_fingerprint().await {
+					| Ok(fetched) => fingerprint.set(fetched),
+					| Err(e) => platform::error(&format!("Failed to get fingerprint: {}", e)),
+				}
+			}
+			request.fingerprint = fingerprint();
+			let address = request.email.clone();
+
+			match register(request, solved).await {
+				| Ok(_) => verify_email.set(Some(address)),
+				| Err(e) => {
+					let body = e.downcast_ref::<ApiError>().map(|e| e.body.as_str()).unwrap_or_default();
+
+					if let Ok(challenge) = serde_json::from_str::<CaptchaRequired>(body) {
+						captcha.set(Some(challenge));
+					} else if let Ok(response) = serde_json::from_str::<ErrorResponse>(body) {
+						let fields = response.field_errors();
+						if fields.is_empty() {
+							message.set(Some(response.message));
+						}
+						errors.set(fields);
+					} else {
+						platform::error(&format!("Registration failed: {}", e));
+						message.set(Some("Something went wrong, try again later.".to_string()));
+					}
+				},
+			}
+
+			submitting.set(false);
+		});
+	};
+
+	if let Some(address) = verify_email() {
+		return rsx! {
+			VerifyEmail { email: address }
+		};
+	}
+
+	let error = move |field: &str| errors.read().get(field).cloned();
+	let username_status = match (error("username"), available()) {
+		| (Some(message), _) => Some(message),
+		| (None, Some(false)) => Some("Username is unavailable. Try adding numbers, letters, underscores _ , or periods.".to_string()),
+		| _ => None,
+	};
+
+	rsx! {
+		form {
+			class: "auth-form register",
+			onsubmit: move |event: Event<FormData>| {
+				event.prevent_default();
+				submit(None);
+			},
+			h1 { "Create an account" }
+			if let Some(message) = message() {
+				p { class: "error", "{message}" }
+			}
+
+			label {
+				"Email"
+				FieldError { message: error("email") }
+				input {
+					r#type: "email",
+					value: "{email}",
+					oninput: move |event| email.set(event.value()),
+				}
+			}
+
+			label {
+				"Display Name"
+				FieldError { message: error("global_name") }
+				input {
+					r#type: "text",
+					value: "{global_name}",
+					oninput: move |event| global_name.set(event.value()),
+				}
+			}
+
+			label {
+				"Username"
+				FieldError { message: username_status }
+				input {
+					r#type: "text",
+					value: "{username}",
+					oninput: move |event| {
+						username.set(event.value());
+						check_username(event.value());
+					},
+				}
+				if available() == Some(true) {
+					span { class: "field-success", "Username is available. Nice!" }
+				}
+			}
+
+			label {
+				"Password"
+				FieldError { message: error("password") }
+				input {
+					r#type: "password",
+					value: "{password}",
+					oninput: move |event| password.set(event.value()),
+				}
+			}
+
+			label {
+				"Date of Birth"
+				FieldError { message: error("date_of_birth") }
+				input {
+					r#type: "date",
+					value: "{date_of_birth}",
+					oninput: move |event| date_of_birth.set(event.value()),
+				}
+			}
+
+			label {
+				class: "checkbox",
+				input {
+					r#type: "checkbox",
+					checked: promotional(),
+					onchange: move |event| promotional.set(event.checked()),
+				}
+				"(Optional) It's okay to send me emails with Discord updates, tips, and special offers. You can opt out at any time."
+			}
+
+			label {
+				class: "checkbox",
+				input {
+					r#type: "checkbox",
+					checked: consent(),
+					onchange: move |event| consent.set(event.checked()),
+				}
+				"I have read and agree to Discord's Terms of Service and Privacy Policy."
+				FieldError { message: error("consent") }
+			}
+
+			input {
+				r#type: "submit",
+				value: "Continue",
+				disabled: submitting(),
+			}
+			Link { to: Route::Login {}, "Already have an account?" }
+		}
+
+		if let Some(challenge) = captcha() {
+			CaptchaModal {
+				on_solved: move |key| {
+					let rqtoken = captcha.peek().as_ref().and_then(|c| c.captcha_rqtoken.clone());
+					captcha.set(None);
+					submit(Some((key, rqtoken)));
+				},
+				on_cancel: move |_| captcha.set(None),
+				challenge,
+			}
+		}
+	}
+}
+
+/// the account works without verifying but discord keeps asking, so offer to resend before going in
+#[component]
+fn VerifyEmail(email: String) -> Element {
+	let mut resent = use_signal(|| false);
+
 	rsx! {
-	   p { "registering accounts is currently unsupported (will be soon trust)" }
+		div {
+			class: "auth-form verify-email",
+			h1 { "Verify by Email" }
+			p { "We've sent a verification link to {email}. Click it to verify your account." }
+			button {
+				disabled: resent(),
+				onclick: move |_| {
+					spawn(async move {
+						match resend_verification().await {
+							| Ok(()) => resent.set(true),
+							| Err(e) => platform::error(&format!("Failed to resend verification email: {}", e)),
+						}
+					});
+				},
+				if resent() { "Sent!" } else { "Resend Verification Email" }
+			}
+			Link { to: Route::FateDecider {}, "Continue to Discord" }
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn request() -> RegisterRequest {
+		RegisterRequest {
+			fingerprint:              None,
+			email:                    "cow@example.com".to_string(),
+			username:                 "cow".to_string(),
+			global_name:              None,
+			password:                 "hunter22".to_string(),
+			date_of_birth:            "2000-01-01".to_string(),
+			consent:                  true,
+			promotional_email_opt_in: false,
+		}
+	}
+
+	#[test]
+	fn usernames_follow_discord_rules() {
+		assert_eq!(username_error("cow.moo_2"), None);
+		assert!(username_error("c").is_some());
+		assert!(username_error("Cow").is_some());
+		assert!(username_error("cow..moo").is_some());
+		assert!(username_error(&"a".repeat(33)).is_some());
+	}
+
+	#[test]
+	fn validation_keys_match_api_fields() {
+		assert!(validate(&request()).is_empty());
+
+		let invalid = validate(&RegisterRequest {
+			email: "cow".to_string(),
+			consent: false,
+			..request()
+		});
+		let mut fields: Vec<_> = invalid.keys().map(String::as_str).collect();
+		fields.sort();
+		assert_eq!(fields, ["consent", "email"]);
 	}
 }
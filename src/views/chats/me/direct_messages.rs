@@ -0,0 +1,16 @@
+use dioxus::prelude::*;
+
+use crate::Route;
+use crate::components::direct_messages::dm_list::DmList;
+
+/// the dm sidebar next to the friends page or whichever dm is open
+#[component]
+pub fn DirectMessages() -> Element {
+	rsx! {
+		div {
+			class: "direct-messages",
+			DmList {}
+			Outlet::<Route> {}
+		}
+	}
+}
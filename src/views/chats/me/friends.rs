@@ -0,0 +1,322 @@
+use dioxus::prelude::*;
+
+use crate::components::direct_messages::open_private_channel;
+use crate::components::modals::confirm::ConfirmModal;
+use crate::components::relationships::{add_friend, block, remove_relationship, send_friend_request, set_nickname};
+use crate::components::users::user_profile_popup::ProfileTrigger;
+use crate::models::presence::Status;
+use crate::models::relationship::{BLOCKED, FRIEND, INCOMING_REQUEST, OUTGOING_REQUEST, Relationship};
+use crate::models::user::user::PartialUser;
+use crate::store::{PRESENCES, RELATIONSHIPS, USERS};
+use crate::utils::cdn;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Tab {
+	Online,
+	All,
+	Pending,
+	Blocked,
+	AddFriend,
+}
+
+impl Tab {
+	fn label(self) -> &'static str {
+		match self {
+			| Tab::Online => "Online",
+			| Tab::All => "All",
+			| Tab::Pending => "Pending",
+			| Tab::Blocked => "Blocked",
+			| Tab::AddFriend => "Add Friend",
+		}
+	}
+
+	fn includes(
+		self,
+		relationship: &Relationship,
+		status: Status,
+	) -> bool {
+		match self {
+			| Tab::Online => relationship.r#type == FRIEND && !matches!(status, Status::Offline | Status::Invisible),
+			| Tab::All => relationship.r#type == FRIEND,
+			| Tab::Pending => matches!(relationship.r#type, INCOMING_REQUEST | OUTGOING_REQUEST),
+			| Tab::Blocked => relationship.r#type == BLOCKED,
+			| Tab::AddFriend => false,
+		}
+	}
+}
+
+const TABS: [Tab; 5] = [Tab::Online, Tab::All, Tab::Pending, Tab::Blocked, Tab::AddFriend];
+
+/// friend nicknames win over display names
+fn name(
+	relationship: &Relationship,
+	user: &PartialUser,
+) -> String {
+	relationship
+		.nickname
+		.clone()
+		.unwrap_or_else(|| user.display_name().to_string())
+}
+
+#[component]
+pub fn Friends() -> Element {
+	let mut tab = use_signal(|| Tab::Online);
+	let mut query = use_signal(String::new);
+
+	let search = query().to_lowercase();
+	let users = USERS.read();
+	let presences = PRESENCES.read();
+	let relationships = RELATIONSHIPS.read();
+	let incoming = relationships.of_type(INCOMING_REQUEST).len();
+
+	// incoming requests go above outgoing ones in pending
+	let mut rows: Vec<(Relationship, PartialUser)> = [FRIEND, BLOCKED, INCOMING_REQUEST, OUTGOING_REQUEST]
+		.into_iter()
+		.flat_map(|r#type| relationships.of_type(r#type))
+		.filter(|r| tab().includes(r, presences.status(r.id)))
+		.filter_map(|r| Some((r.clone(), r.user.clone().or_else(|| users.user(r.id).cloned())?)))
+		.filter(|(r, user)| name(r, user).to_lowercase().contains(&search) || user.username.contains(&search))
+		.collect();
+	rows.sort_by_key(|(r, user)| (r.r#type == OUTGOING_REQUEST, name(r, user).to_lowercase()));
+
+	let heading = match tab() {
+		| Tab::Online => format!("Online — {}", rows.len()),
+		| Tab::All => format!("All Friends — {}", rows.len()),
+		| Tab::Pending => format!("Pending — {}", rows.len()),
+		| Tab::Blocked => format!("Blocked — {}", rows.len()),
+		| Tab::AddFriend => String::new(),
+	};
+
+	rsx! {
+		div {
+			class: "friends",
+			div {
+				class: "friends-tabs",
+				h2 { "Friends" }
+				for t in TABS {
+					button {
+						key: "{t.label()}",
+						class: match (t == tab(), t == Tab::AddFriend) {
+							| (true, _) => "friends-tab selected",
+							| (false, true) => "friends-tab add-friend",
+							| (false, false) => "friends-tab",
+						},
+						onclick: move |_| tab.set(t),
+						"{t.label()}"
+						if t == Tab::Pending && incoming > 0 {
+							span { class: "mention-badge", "{incoming}" }
+						}
+					}
+				}
+			}
+
+			if tab() == Tab::AddFriend {
+				AddFriend {}
+			} else {
+				input {
+					class: "friends-search",
+					r#type: "search",
+					placeholder: "Search",
+					value: "{query}",
+					oninput: move |event| query.set(event.value()),
+				}
+				h3 { class: "friends-heading", "{heading}" }
+				div {
+					class: "friends-list",
+					for (relationship, user) in rows {
+						FriendRow { key: "{relationship.id}", relationship, user }
+					}
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn FriendRow(
+	relationship: Relationship,
+	user: PartialUser,
+) -> Element {
+	let mut editing_nick = use_signal(|| false);
+	let mut nickname = use_signal(|| relationship.nickname.clone().unwrap_or_default());
+	let mut confirm_remove = use_signal(|| false);
+
+	let user_id = relationship.id;
+	let presence = PRESENCES.read().get(user_id).cloned();
+	let status = presence.as_ref().map(|p| p.status).unwrap_or_default();
+	let display = name(&relationship, &user);
+
+	let subtitle = match relationship.r#type {
+		| INCOMING_REQUEST => "Incoming Friend Request".to_string(),
+		| OUTGOING_REQUEST => "Outgoing Friend Request".to_string(),
+		| BLOCKED => "Blocked".to_string(),
+		| _ => presence
+			.as_ref()
+			.and_then(|p| p.activities.first())
+			.map(|a| a.state.clone().unwrap_or_else(|| a.name.clone()))
+			.unwrap_or_else(|| status.as_str().to_string()),
+	};
+
+	rsx! {
+		div {
+			class: "friend-row",
+			ProfileTrigger {
+				user_id,
+				class: "member-avatar",
+				img { src: cdn::avatar(&user, 64), alt: "" }
+				if relationship.r#type == FRIEND {
+					span { class: "status {status.as_str()}" }
+				}
+			}
+
+			div {
+				class: "friend-text",
+				if editing_nick() {
+					input {
+						r#type: "text",
+						placeholder: "{user.display_name()}",
+						value: "{nickname}",
+						onmounted: move |event| async move {
+							let _ = event.set_focus(true).await;
+						},
+						oninput: move |event| nickname.set(event.value()),
+						onkeydown: move |event: KeyboardEvent| match event.key() {
+							| Key::Enter => {
+								editing_nick.set(false);
+								set_nickname(user_id, nickname());
+							},
+							| Key::Escape => editing_nick.set(false),
+							| _ => {},
+						},
+					}
+				} else {
+					span { class: "friend-name", "{display}" }
+					span { class: "friend-username", "{user.username}" }
+				}
+				span { class: "friend-subtitle", "{subtitle}" }
+			}
+
+			div {
+				class: "friend-actions",
+				match relationship.r#type {
+					FRIEND => rsx! {
+						button { onclick: move |_| open_private_channel(vec![user_id]), "Message" }
+						button { onclick: move |_| editing_nick.set(true), "Edit Nickname" }
+						button { class: "danger", onclick: move |_| confirm_remove.set(true), "Remove Friend" }
+						button { class: "danger", onclick: move |_| block(user_id), "Block" }
+					},
+					INCOMING_REQUEST => rsx! {
+						button { onclick: move |_| add_friend(user_id), "Accept" }
+						button { class: "danger", onclick: move |_| remove_relationship(user_id), "Ignore" }
+					},
+					OUTGOING_REQUEST => rsx! {
+						button { class: "danger", onclick: move |_| remove_relationship(user_id), "Cancel" }
+					},
+					BLOCKED => rsx! {
+						button { onclick: move |_| remove_relationship(user_id), "Unblock" }
+					},
+					_ => rsx! {},
+				}
+			}
+
+			if confirm_remove() {
+				ConfirmModal {
+					title: "Remove '{display}'",
+					message: "Are you sure you want to remove {display} from your friends?",
+					confirm_label: "Remove Friend",
+					on_confirm: move |_| {
+						confirm_remove.set(false);
+						remove_relationship(user_id);
+					},
+					on_cancel: move |_| confirm_remove.set(false),
+				}
+			}
+		}
+	}
+}
+
+#[component]
+fn AddFriend() -> Element {
+	let mut username = use_signal(String::new);
+	// whether the last request went through, and who it was for
+	let mut result = use_signal(|| None::<(bool, String)>);
+	let mut sending = use_signal(|| false);
+
+	let mut send = move || {
+		let name = username().trim().to_lowercase();
+		if name.is_empty() || sending() {
+			return;
+		}
+
+		sending.set(true);
+		spawn(async move {
+			let sent = send_friend_request(name.clone()).await.is_ok();
+			if sent {
+				username.set(String::new());
+			}
+			result.set(Some((sent, name)));
+			sending.set(false);
+		});
+	};
+
+	rsx! {
+		div {
+			class: "add-friend",
+			h3 { "Add Friend" }
+			p { "You can add friends with their Discord username." }
+			div {
+				class: "add-friend-input",
+				input {
+					r#type: "text",
+					placeholder: "You can add friends with their Discord username.",
+					value: "{username}",
+					oninput: move |event| {
+						username.set(event.value());
+						result.set(None);
+					},
+					onkeydown: move |event: KeyboardEvent| {
+						if event.key() == Key::Enter {
+							send();
+						}
+					},
+				}
+				button {
+					disabled: username().trim().is_empty() || sending(),
+					onclick: move |_| send(),
+					"Send Friend Request"
+				}
+			}
+			match result() {
+				Some((true, name)) => rsx! {
+					p { class: "success", "Success! Your friend request to {name} was sent." }
+				},
+				Some((false, _)) => rsx! {
+					p { class: "error", "Hm, didn't work. Double check that the username is correct." }
+				},
+				None => rsx! {},
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	fn relationship(r#type: u8) -> Relationship {
+		serde_json::from_value(json!({ "id": "1", "type": r#type })).unwrap()
+	}
+
+	#[test]
+	fn tabs_filter_by_type_and_status() {
+		assert!(Tab::Online.includes(&relationship(FRIEND), Status::Idle));
+		assert!(!Tab::Online.includes(&relationship(FRIEND), Status::Offline));
+		assert!(Tab::All.includes(&relationship(FRIEND), Status::Offline));
+		assert!(Tab::Pending.includes(&relationship(INCOMING_REQUEST), Status::Offline));
+		assert!(Tab::Pending.includes(&relationship(OUTGOING_REQUEST), Status::Offline));
+		assert!(!Tab::Pending.includes(&relationship(BLOCKED), Status::Offline));
+		assert!(Tab::Blocked.includes(&relationship(BLOCKED), Status::Offline));
+	}
+}
@@ -1 +1,3 @@
-
+pub mod direct_messages;
+pub mod friends;
+pub mod user;
@@ -0,0 +1,154 @@
+use dioxus::prelude::*;
+
+use crate::components::chat::Chat;
+use crate::components::direct_messages::dm_list::MAX_GROUP_RECIPIENTS;
+use crate::components::direct_messages::user_profile::DmUserProfile;
+use crate::components::direct_messages::{add_recipient, open_private_channel, remove_recipient};
+use crate::components::modals::recipient_picker::RecipientPicker;
+use crate::components::users::user_profile_popup::ProfileTrigger;
+use crate::models::chat::Channel;
+use crate::models::types::Snowflake;
+use crate::store::private_channels::{display_name, recipients};
+use crate::store::{PRIVATE_CHANNELS, USERS};
+use crate::utils::cdn;
+use crate::utils::platform;
+
+// https://docs.discord.sex/resources/channel#channel-type
+const GROUP_DM: u8 = 3;
+
+#[component]
+pub fn UserChat(channel_id: String) -> Element {
+	let mut show_members = use_signal(|| true);
+	let mut adding = use_signal(|| false);
+
+	let Ok(channel_id) = channel_id.parse::<Snowflake>() else {
+		return rsx! {};
+	};
+	let Some(channel) = PRIVATE_CHANNELS.read().channel(channel_id).cloned() else {
+		return rsx! {
+			div { class: "message-list-loading", "Loading..." }
+		};
+	};
+
+	let recipients = recipients(&channel, &USERS.read());
+	let name = display_name(&channel, &recipients);
+	let is_group = channel.r#type == GROUP_DM;
+	let other = recipients.first().map(|u| u.id).filter(|_| !is_group);
+
+	rsx! {
+		div {
+			class: "channel",
+			div {
+				class: "channel-header",
+				h2 { "{name}" }
+				if is_group {
+					button {
+						title: "Add Friends to DM",
+						onclick: move |_| adding.set(true),
+						"Add Friends"
+					}
+					button {
+						title: if show_members() { "Hide Member List" } else { "Show Member List" },
+						onclick: move |_| show_members.toggle(),
+						"Members"
+					}
+				} else {
+					button {
+						title: if show_members() { "Hide User Profile" } else { "Show User Profile" },
+						onclick: move |_| show_members.toggle(),
+						"User Profile"
+					}
+				}
+			}
+
+			Chat { key: "{channel_id}", channel_id, around: None }
+			if show_members() {
+				if is_group {
+					GroupMembers { channel: channel.clone() }
+				} else if let Some(user_id) = other {
+					DmUserProfile { key: "{user_id}", user_id }
+				}
+			}
+
+			if adding() {
+				RecipientPicker {
+					title: "Select Friends",
+					confirm_label: "Add",
+					exclude: channel.recipient_ids.clone(),
+					max: MAX_GROUP_RECIPIENTS.saturating_sub(channel.recipient_ids.len()),
+					on_confirm: move |users: Vec<Snowflake>| {
+						adding.set(false);
+						spawn(async move {
+							for user_id in users {
+								if let Err(e) = add_recipient(channel_id, user_id).await {
+									platform::error(&format!("Failed to add recipient: {}", e));
+								}
+							}
+						});
+					},
+					on_cancel: move |_| adding.set(false),
+				}
+			}
+		}
+	}
+}
+
+/// everyone in a group dm, the owner can kick people
+#[component]
+fn GroupMembers(channel: Channel) -> Element {
+	let users = USERS.read();
+	let current = users.current().cloned();
+	let owner = channel.owner_id;
+	let is_owner = current.as_ref().is_some_and(|u| Some(u.id) == owner);
+	let channel_id = channel.id;
+
+	let mut members = recipients(&channel, &users);
+	members.extend(current.clone());
+	members.sort_by_key(|u| u.display_name().to_lowercase());
+
+	rsx! {
+		div {
+			class: "member-list",
+			h3 { class: "member-group", "Members — {members.len()}" }
+			for user in members {
+				div {
+					key: "{user.id}",
+					class: "member",
+					ProfileTrigger {
+						user_id: user.id,
+						class: "member-avatar",
+						img { src: cdn::avatar(&user, 64), alt: "" }
+					}
+					span {
+						class: "member-name",
+						{channel.nicks.iter().find(|n| n.id == user.id).map_or(user.display_name(), |n| n.nick.as_str())}
+					}
+					if Some(user.id) == owner {
+						span { class: "member-owner", title: "Group Owner", "👑" }
+					}
+					if current.as_ref().is_none_or(|me| me.id != user.id) {
+						button {
+							title: "Message",
+							onclick: move |_| open_private_channel(vec![user.id]),
+							"Message"
+						}
+						if is_owner {
+							button {
+								class: "danger",
+								title: "Remove From Group",
+								onclick: move |_| {
+									spawn(async move {
+										if let Err(e) = remove_recipient(channel_id, user.id).await {
+											platform::error(&format!("Failed to remove recipient: {}", e));
+										}
+									});
+								},
+								"Remove"
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+}
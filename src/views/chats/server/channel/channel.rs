@@ -1,9 +1,44 @@
 use dioxus::prelude::*;
 
+use crate::components::chat::Chat;
+use crate::components::server::member_list::MemberList;
+use crate::models::types::Snowflake;
+
 #[component]
 pub fn Channel(
 	server_id: String,
 	channel_id: String,
 ) -> Element {
-	rsx! {}
+	let (Ok(server_id), Ok(channel_id)) = (
+		server_id.parse::<Snowflake>(),
+		channel_id.parse::<Snowflake>(),
+	) else {
+		return rsx! {};
+	};
+
+	rsx! {
+		div {
+			class: "channel",
+			Chat { key: "{channel_id}", channel_id, around: None }
+			MemberList { key: "{channel_id}", guild_id: server_id, channel_id }
+		}
+	}
+}
+
+#[component]
+pub fn JumpToMessage(
+	server_id: String,
+	channel_id: String,
+	message_id: String,
+) -> Element {
+	let (Ok(channel_id), Ok(message_id)) = (
+		channel_id.parse::<Snowflake>(),
+		message_id.parse::<Snowflake>(),
+	) else {
+		return rsx! {};
+	};
+
+	rsx! {
+		Chat { key: "{channel_id}-{message_id}", channel_id, around: message_id }
+	}
 }
@@ -1,2 +1,3 @@
+#[allow(clippy::module_inception)]
 pub mod channel;
 pub mod channels_and_roles;
@@ -1,2 +1,3 @@
 pub mod channel;
+#[allow(clippy::module_inception)]
 pub mod server;
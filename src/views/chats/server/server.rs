@@ -1,9 +1,26 @@
-// jsut redirect to the default channel and also add the channel lsit and member list sidebars
 use dioxus::prelude::*;
 
+use crate::Route;
+use crate::components::server::channel_list::ChannelList;
+use crate::models::types::Snowflake;
+
+/// the channel sidebar next to whatever channel is open
 #[component]
 pub fn Server() -> Element {
+	let server_id = match use_route::<Route>() {
+		| Route::Roles { server_id } | Route::Channel { server_id, .. } | Route::JumpToMessage { server_id, .. } => {
+			server_id.parse::<Snowflake>().ok()
+		},
+		| _ => None,
+	};
+
 	rsx! {
-	   Outlet::<crate::Route> {}
+		div {
+			class: "server",
+			if let Some(guild_id) = server_id {
+				ChannelList { key: "{guild_id}", guild_id }
+			}
+			Outlet::<Route> {}
+		}
 	}
 }
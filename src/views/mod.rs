@@ -7,12 +7,18 @@ mod app;
 pub use app::FateDecider;
 
 mod auth;
-pub use auth::{Login, Register};
+pub use auth::{Login, Register, end_session, log_out, reconnect, switch_account};
 
 mod not_found;
 pub use not_found::PageNotFound;
 
+mod settings;
+pub use settings::sessions::Sessions;
+
 mod chats;
-pub use chats::server::channel::channel::Channel;
+pub use chats::me::direct_messages::DirectMessages;
+pub use chats::me::friends::Friends;
+pub use chats::me::user::user_chat::UserChat;
+pub use chats::server::channel::channel::{Channel, JumpToMessage};
 pub use chats::server::channel::channels_and_roles::Roles;
 pub use chats::server::server::Server;
@@ -1,7 +1,7 @@
 use dioxus::prelude::*;
 
 #[component]
-pub fn PageNotFound() -> Element {
+pub fn PageNotFound(segments: Vec<String>) -> Element {
 	rsx! {
 	   p { "404" }
 	}
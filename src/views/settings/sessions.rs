@@ -0,0 +1,195 @@
+use std::error::Error;
+
+use dioxus::prelude::*;
+use iso8601_timestamp::Timestamp;
+
+use crate::Route;
+use crate::components::modals::confirm::ConfirmModal;
+use crate::models::auth::sessions::{AuthSession, AuthSessions, LogoutSessionsRequest};
+use crate::store::SESSIONS;
+use crate::utils::platform;
+use crate::utils::request::RequestClient;
+use crate::utils::time;
+
+async fn get_sessions() -> Result<Vec<AuthSession>, Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	let response: AuthSessions = client.get("/auth/sessions").await?;
+	Ok(response.user_sessions)
+}
+
+// asks for mfa through sudo mode when the account has it on
+async fn logout_sessions(session_id_hashes: Vec<String>) -> Result<(), Box<dyn Error>> {
+	let client = RequestClient::new();
+
+	client
+		.post("/auth/sessions/logout", &LogoutSessionsRequest { session_id_hashes })
+		.await
+}
+
+/// the current session first, then the most recently used
+fn sort_sessions(
+	sessions: &mut [AuthSession],
+	current: Option<&str>,
+) {
+	sessions.sort_by(|a, b| {
+		(Some(b.id_hash.as_str()) == current)
+			.cmp(&(Some(a.id_hash.as_str()) == current))
+			.then(b.approx_last_used_time.cmp(&a.approx_last_used_time))
+	});
+}
+
+fn last_used(session: &AuthSession) -> i64 {
+	session
+		.approx_last_used_time
+		.duration_since(Timestamp::UNIX_EPOCH)
+		.whole_milliseconds() as i64
+}
+
+#[component]
+pub fn Sessions() -> Element {
+	let mut sessions = use_signal(|| None::<Vec<AuthSession>>);
+	let mut error = use_signal(|| None::<String>);
+	// the hashes waiting on the confirm dialog
+	let mut confirm = use_signal(|| None::<Vec<String>>);
+
+	// refetches whenever the gateway says sessions changed
+	use_effect(move || {
+		let _ = SESSIONS.read().replacements;
+
+		spawn(async move {
+			match get_sessions().await {
+				| Ok(fetched) => sessions.set(Some(fetched)),
+				| Err(e) => {
+					platform::error(&format!("Failed to load sessions: {}", e));
+					error.set(Some("Couldn't load your devices.".to_string()));
+				},
+			}
+		});
+	});
+
+	let logout = move |hashes: Vec<String>| {
+		spawn(async move {
+			match logout_sessions(hashes.clone()).await {
+				| Ok(()) => {
+					if let Some(sessions) = sessions.write().as_mut() {
+						sessions.retain(|s| !hashes.contains(&s.id_hash));
+					}
+				},
+				| Err(e) => {
+					platform::error(&format!("Failed to log out sessions: {}", e));
+					error.set(Some("Couldn't log out of that device.".to_string()));
+				},
+			}
+		});
+	};
+
+	let current = SESSIONS.read().current.clone();
+	let Some(mut list) = sessions() else {
+		return rsx! {
+			div { class: "settings sessions", "Loading..." }
+		};
+	};
+	sort_sessions(&mut list, current.as_deref());
+	let others: Vec<String> = list
+		.iter()
+		.filter(|s| Some(&s.id_hash) != current.as_ref())
+		.map(|s| s.id_hash.clone())
+		.collect();
+
+	rsx! {
+		div {
+			class: "settings sessions",
+			Link { to: Route::Friends {}, "Back" }
+			h1 { "Devices" }
+			p { "Here are all the devices that are currently logged in with your Discord account." }
+			if let Some(error) = error() {
+				p { class: "error", "{error}" }
+			}
+
+			for session in list {
+				div {
+					key: "{session.id_hash}",
+					class: "session",
+					div {
+						class: "session-info",
+						span { class: "session-client", "{session.client_info.describe()}" }
+						span {
+							class: "session-details",
+							if let Some(location) = &session.client_info.location {
+								"{location} · "
+							}
+							if Some(&session.id_hash) == current.as_ref() {
+								"This Device"
+							} else {
+								{time::format(last_used(&session), 'R')}
+							}
+						}
+					}
+					if Some(&session.id_hash) != current.as_ref() {
+						button {
+							title: "Log Out",
+							onclick: move |_| confirm.set(Some(vec![session.id_hash.clone()])),
+							"Log Out"
+						}
+					}
+				}
+			}
+
+			if !others.is_empty() {
+				button {
+					class: "danger",
+					onclick: move |_| confirm.set(Some(others.clone())),
+					"Log Out All Known Devices"
+				}
+			}
+		}
+
+		if let Some(hashes) = confirm() {
+			ConfirmModal {
+				title: "Log Out",
+				message: if hashes.len() == 1 {
+					"Log out of this device?".to_string()
+				} else {
+					"Log out of all other devices? You'll stay logged in here.".to_string()
+				},
+				confirm_label: "Log Out",
+				on_confirm: move |_| {
+					confirm.set(None);
+					logout(hashes.clone());
+				},
+				on_cancel: move |_| confirm.set(None),
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn session(
+		id_hash: &str,
+		last_used: &str,
+	) -> AuthSession {
+		serde_json::from_value(serde_json::json!({
+			"id_hash": id_hash,
+			"approx_last_used_time": last_used,
+			"client_info": { "os": null, "platform": null, "location": null },
+		}))
+		.unwrap()
+	}
+
+	#[test]
+	fn current_session_goes_first() {
+		let mut sessions = vec![
+			session("old", "2025-01-01T00:00:00+00:00"),
+			session("here", "2024-01-01T00:00:00+00:00"),
+			session("new", "2025-06-01T00:00:00+00:00"),
+		];
+		sort_sessions(&mut sessions, Some("here"));
+
+		let order: Vec<&str> = sessions.iter().map(|s| s.id_hash.as_str()).collect();
+		assert_eq!(order, ["here", "new", "old"]);
+	}
+}